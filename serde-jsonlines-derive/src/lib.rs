@@ -0,0 +1,309 @@
+//! Derive macro for the `JsonLinesRecord` trait of
+//! [`serde-jsonlines`](https://docs.rs/serde-jsonlines).
+//!
+//! This crate is not meant to be used directly; instead, enable the `derive`
+//! feature of `serde-jsonlines` and use `serde_jsonlines::JsonLinesRecord`.
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::quote;
+use std::collections::BTreeSet;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Data, DataEnum, DeriveInput, Fields, LitInt, LitStr, Token, Type, parenthesized,
+    parse_macro_input,
+};
+
+/// Derive `serde_jsonlines::JsonLinesRecord` for an enum of record kinds or a
+/// versioned struct.
+///
+/// See the documentation of the `JsonLinesRecord` trait for the supported
+/// `#[jsonlines(...)]` attributes.
+#[proc_macro_derive(JsonLinesRecord, attributes(jsonlines))]
+pub fn derive_json_lines_record(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+    let body = match &input.data {
+        Data::Struct(_) => expand_struct(&attrs)?,
+        Data::Enum(data) => expand_enum(&attrs, data)?,
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "JsonLinesRecord cannot be derived for unions",
+            ));
+        }
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::serde_jsonlines::JsonLinesRecord for #name #ty_generics #where_clause {
+            fn from_json_line(line: &str) -> ::std::io::Result<Self> {
+                <Self as ::serde_jsonlines::JsonLinesRecord>::from_json_line_with(
+                    line,
+                    &::core::default::Default::default(),
+                )
+            }
+
+            #[allow(unused_variables)]
+            fn from_json_line_with(
+                line: &str,
+                cx: &::serde_jsonlines::__private::Context,
+            ) -> ::std::io::Result<Self> {
+                #body
+            }
+        }
+    })
+}
+
+fn expand_struct(attrs: &ContainerAttrs) -> syn::Result<TokenStream2> {
+    if let Some(tag) = &attrs.tag {
+        return Err(syn::Error::new(
+            tag.span(),
+            "`tag` is only supported on enums",
+        ));
+    }
+    let Some(version_lit) = &attrs.version else {
+        if let Some(span) = attrs.versioning_span() {
+            return Err(syn::Error::new(
+                span,
+                "`version` must be set in order to use versioning attributes",
+            ));
+        }
+        return Ok(quote!(cx.decode::<Self>(line)));
+    };
+    let version = version_lit.base10_parse::<u64>()?;
+    let pointer = to_pointer(
+        &attrs
+            .version_field
+            .as_ref()
+            .map_or_else(|| String::from("schema_version"), LitStr::value),
+    );
+    let default = if let Some(lit) = &attrs.default_version {
+        let v = Literal::u64_suffixed(lit.base10_parse::<u64>()?);
+        quote!(::core::option::Option::Some(#v))
+    } else {
+        quote!(::core::option::Option::None)
+    };
+    let mut seen = BTreeSet::new();
+    let mut arms = Vec::with_capacity(attrs.upgrades.len());
+    for Upgrade { version: lit, ty } in &attrs.upgrades {
+        let old = lit.base10_parse::<u64>()?;
+        if old >= version {
+            return Err(syn::Error::new(
+                lit.span(),
+                "upgrades must be from versions older than the current version",
+            ));
+        }
+        if !seen.insert(old) {
+            return Err(syn::Error::new(lit.span(), "duplicate upgrade version"));
+        }
+        let old = Literal::u64_suffixed(old);
+        arms.push(quote! {
+            #old => cx.decode::<#ty>(line)
+                .map(::core::convert::Into::into),
+        });
+    }
+    let version = Literal::u64_suffixed(version);
+    Ok(quote! {
+        static VERSION: ::serde_jsonlines::__private::Field =
+            ::serde_jsonlines::__private::Field::new(#pointer);
+        match VERSION.version(line, #default)? {
+            #version => cx.decode::<Self>(line),
+            #(#arms)*
+            v => ::core::result::Result::Err(
+                ::serde_jsonlines::__private::unsupported_version(#pointer, v)
+            ),
+        }
+    })
+}
+
+fn expand_enum(attrs: &ContainerAttrs, data: &DataEnum) -> syn::Result<TokenStream2> {
+    if let Some(span) = attrs
+        .version
+        .as_ref()
+        .map(LitInt::span)
+        .or_else(|| attrs.versioning_span())
+    {
+        return Err(syn::Error::new(
+            span,
+            "versioning attributes are only supported on structs",
+        ));
+    }
+    let pointer = to_pointer(
+        &attrs
+            .tag
+            .as_ref()
+            .map_or_else(|| String::from("type"), LitStr::value),
+    );
+    let mut seen = BTreeSet::new();
+    let mut arms = Vec::with_capacity(data.variants.len());
+    let mut fallback = None;
+    for variant in &data.variants {
+        let vattrs = VariantAttrs::parse(&variant.attrs)?;
+        let ident = &variant.ident;
+        let value = match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                let decode = if vattrs.record {
+                    quote!(<#ty as ::serde_jsonlines::JsonLinesRecord>::from_json_line_with(line, cx))
+                } else {
+                    quote!(cx.decode::<#ty>(line))
+                };
+                quote!(#decode.map(Self::#ident))
+            }
+            Fields::Unit if !vattrs.record => quote!(::core::result::Result::Ok(Self::#ident)),
+            _ => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "JsonLinesRecord variants must be unit variants or have exactly one unnamed field",
+                ));
+            }
+        };
+        if vattrs.other {
+            if let Some(rename) = &vattrs.rename {
+                return Err(syn::Error::new(
+                    rename.span(),
+                    "`rename` cannot be used with `other`",
+                ));
+            }
+            if fallback.replace(value).is_some() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "only one variant can be marked `other`",
+                ));
+            }
+        } else {
+            let name = vattrs
+                .rename
+                .as_ref()
+                .map_or_else(|| ident.to_string(), LitStr::value);
+            if !seen.insert(name.clone()) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("duplicate record type {name:?}"),
+                ));
+            }
+            arms.push(quote!(::core::option::Option::Some(#name) => #value,));
+        }
+    }
+    let fallback = fallback.unwrap_or_else(|| {
+        quote! {
+            ::core::result::Result::Err(
+                ::serde_jsonlines::__private::unknown_kind(#pointer, kind.as_ref())
+            )
+        }
+    });
+    Ok(quote! {
+        static TAG: ::serde_jsonlines::__private::Field =
+            ::serde_jsonlines::__private::Field::new(#pointer);
+        let kind = TAG.get(line)?;
+        match kind.as_ref().and_then(::serde_jsonlines::__private::Value::as_str) {
+            #(#arms)*
+            _ => #fallback,
+        }
+    })
+}
+
+/// Convert a top-level field name to a JSON Pointer
+fn to_pointer(field: &str) -> String {
+    format!("/{}", field.replace('~', "~0").replace('/', "~1"))
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    tag: Option<LitStr>,
+    version: Option<LitInt>,
+    version_field: Option<LitStr>,
+    default_version: Option<LitInt>,
+    upgrades: Vec<Upgrade>,
+}
+
+impl ContainerAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = ContainerAttrs::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("jsonlines")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tag") {
+                    out.tag = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("version") {
+                    out.version = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("version_field") {
+                    out.version_field = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("default_version") {
+                    out.default_version = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("upgrade") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    out.upgrades
+                        .extend(Punctuated::<Upgrade, Token![,]>::parse_terminated(
+                            &content,
+                        )?);
+                } else {
+                    return Err(meta.error("unknown jsonlines container attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+
+    /// Return the span of the first attribute other than `version` that
+    /// only applies to versioned structs, if any
+    fn versioning_span(&self) -> Option<Span> {
+        self.version_field
+            .as_ref()
+            .map(LitStr::span)
+            .or_else(|| self.default_version.as_ref().map(LitInt::span))
+            .or_else(|| self.upgrades.first().map(|u| u.version.span()))
+    }
+}
+
+/// An `N = Type` entry in `#[jsonlines(upgrade(...))]`
+struct Upgrade {
+    version: LitInt,
+    ty: Type,
+}
+
+impl Parse for Upgrade {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let version = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let ty = input.parse()?;
+        Ok(Upgrade { version, ty })
+    }
+}
+
+#[derive(Default)]
+struct VariantAttrs {
+    rename: Option<LitStr>,
+    record: bool,
+    other: bool,
+}
+
+impl VariantAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = VariantAttrs::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("jsonlines")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    out.rename = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("record") {
+                    out.record = true;
+                } else if meta.path.is_ident("other") {
+                    out.other = true;
+                } else {
+                    return Err(meta.error("unknown jsonlines variant attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+}
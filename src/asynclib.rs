@@ -1,13 +1,18 @@
 #![cfg_attr(docsrs, doc(cfg(feature = "async")))]
+use crate::{LineHandling, LineTerminator};
 use futures_core::Stream;
 use futures_sink::Sink;
 use pin_project_lite::pin_project;
-use serde::{Serialize, de::DeserializeOwned};
-use std::io::Result;
+use serde::{
+    Serialize,
+    de::{DeserializeOwned, DeserializeSeed},
+};
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
 use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::{Context, Poll, ready};
-use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, Lines};
+use tokio::io::{AsyncBufRead, AsyncWrite, AsyncWriteExt};
 
 pin_project! {
     /// A structure for asynchronously reading JSON values from JSON Lines
@@ -77,6 +82,13 @@ pin_project! {
     pub struct AsyncJsonLinesReader<R> {
         #[pin]
         inner: R,
+        // Reused between reads in order to avoid reallocating; always empty
+        // between method calls
+        buf: String,
+        // Holds the start of a line whose reading was interrupted by the
+        // underlying reader returning `Poll::Pending`
+        pending: Vec<u8>,
+        lines: LineHandling,
     }
 }
 
@@ -84,7 +96,65 @@ impl<R> AsyncJsonLinesReader<R> {
     /// Construct a new `AsyncJsonLinesReader` from a
     /// [`tokio::io::AsyncBufRead`] instance
     pub fn new(reader: R) -> Self {
-        AsyncJsonLinesReader { inner: reader }
+        AsyncJsonLinesReader {
+            inner: reader,
+            buf: String::new(),
+            pending: Vec::new(),
+            lines: LineHandling::default(),
+        }
+    }
+
+    /// Set whether to strip a UTF-8 byte order mark from the start of the
+    /// first line.
+    ///
+    /// By default, byte order marks are not stripped, and so a first line
+    /// that begins with one fails to parse as JSON.
+    pub fn strip_bom(mut self, strip: bool) -> Self {
+        self.lines.strip_bom = strip;
+        self
+    }
+
+    /// Set whether to reject lines that contain carriage returns.
+    ///
+    /// By default, lines terminated by `\r\n` are read without complaint, as
+    /// JSON treats the carriage return as whitespace.  When strict line
+    /// endings are enabled, reading a line that ends in `\r\n` or that
+    /// contains a lone `\r` results in an error of kind
+    /// [`std::io::ErrorKind::InvalidData`].
+    pub fn strict_line_endings(mut self, strict: bool) -> Self {
+        self.lines.strict = strict;
+        self
+    }
+
+    /// Set whether to report the path to the field at which deserialization
+    /// failed when a line cannot be deserialized by
+    /// [`read()`][AsyncJsonLinesReader::read],
+    /// [`read_into()`][AsyncJsonLinesReader::read_into], or
+    /// [`read_seed()`][AsyncJsonLinesReader::read_seed] (or the streams built
+    /// on them).
+    ///
+    /// See
+    /// [`JsonLinesReader::field_paths()`][crate::JsonLinesReader::field_paths]
+    /// for more information.
+    #[cfg(feature = "path-to-error")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "path-to-error")))]
+    pub fn field_paths(mut self, enabled: bool) -> Self {
+        self.lines.field_paths = enabled;
+        self
+    }
+
+    /// Set whether to report errors that occur while reading a line with
+    /// [`read()`][AsyncJsonLinesReader::read],
+    /// [`read_into()`][AsyncJsonLinesReader::read_into], or
+    /// [`read_seed()`][AsyncJsonLinesReader::read_seed] (or the streams built
+    /// on them) as [`Diagnostic`][crate::Diagnostic]s.
+    ///
+    /// See
+    /// [`JsonLinesReader::diagnostics()`][crate::JsonLinesReader::diagnostics]
+    /// for more information.
+    pub fn diagnostics(mut self, enabled: bool) -> Self {
+        self.lines.diagnostics = enabled;
+        self
     }
 
     /// Consume the `AsyncJsonLinesReader` and return the underlying reader
@@ -133,12 +203,161 @@ impl<R: AsyncBufRead> AsyncJsonLinesReader<R> {
         T: DeserializeOwned,
         R: Unpin,
     {
-        let mut s = String::new();
-        let r = self.inner.read_line(&mut s).await?;
-        if r == 0 {
-            Ok(None)
-        } else {
-            Ok(Some(serde_json::from_str::<T>(&s)?))
+        if !self.read_line().await? {
+            return Ok(None);
+        }
+        let r = self.lines.decode_seed(&self.buf, PhantomData::<T>);
+        self.buf.clear();
+        r.map(Some)
+    }
+
+    /// Asynchronously read & deserialize the first line of JSON from the
+    /// underlying reader as a header of type `H`, and return it along with an
+    /// asynchronous stream over the deserialized values of type `T` on the
+    /// remaining lines.
+    ///
+    /// This is useful for formats in which a metadata record on the first
+    /// line is followed by homogeneous data records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::UnexpectedEof`] if the
+    /// input is empty.  Otherwise, has the same error conditions as
+    /// [`read()`][AsyncJsonLinesReader::read].
+    #[allow(clippy::future_not_send)] // The Future is Send if R is Send
+    pub async fn read_with_header<H, T>(mut self) -> Result<(H, JsonLinesStream<R, T>)>
+    where
+        H: DeserializeOwned,
+        R: Unpin,
+    {
+        match self.read::<H>().await? {
+            Some(header) => Ok((header, self.read_all())),
+            None => Err(Error::new(ErrorKind::UnexpectedEof, "missing header line")),
+        }
+    }
+
+    /// Asynchronously read a line of JSON from the underlying reader and
+    /// deserialize it into an existing value, reusing the value's
+    /// allocations where possible.
+    ///
+    /// Returns `Ok(true)` if a line was read into `place`, or `Ok(false)` if
+    /// end-of-file was reached, in which case `place` is left unchanged.
+    ///
+    /// See [`JsonLinesReader::read_into()`][crate::JsonLinesReader::read_into]
+    /// for more information.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`read()`][AsyncJsonLinesReader::read].  If deserialization fails,
+    /// `place` may have been partially updated.
+    #[allow(clippy::future_not_send)] // The Future is Send if R and T are Send
+    pub async fn read_into<T>(&mut self, place: &mut T) -> Result<bool>
+    where
+        T: DeserializeOwned,
+        R: Unpin,
+    {
+        if !self.read_line().await? {
+            return Ok(false);
+        }
+        let r = self.lines.decode_in_place(&self.buf, place);
+        self.buf.clear();
+        r?;
+        Ok(true)
+    }
+
+    /// Asynchronously read a line of JSON from the underlying reader and
+    /// deserialize it using the given [`serde::de::DeserializeSeed`] value.
+    ///
+    /// If end-of-file is reached, this method returns `Ok(None)` without
+    /// using the seed.
+    ///
+    /// See [`JsonLinesReader::read_seed()`][crate::JsonLinesReader::read_seed]
+    /// for more information.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`read()`][AsyncJsonLinesReader::read].
+    #[allow(clippy::future_not_send)] // The Future is Send if R and S are Send
+    pub async fn read_seed<S, V>(&mut self, seed: S) -> Result<Option<V>>
+    where
+        S: for<'de> DeserializeSeed<'de, Value = V>,
+        R: Unpin,
+    {
+        if !self.read_line().await? {
+            return Ok(None);
+        }
+        let r = self.lines.decode_seed(&self.buf, seed);
+        self.buf.clear();
+        r.map(Some)
+    }
+
+    /// Asynchronously read a line from the underlying reader into
+    /// `self.buf`, returning `false` at end of file
+    #[allow(clippy::future_not_send)] // The Future is Send if R is Send
+    async fn read_line(&mut self) -> Result<bool>
+    where
+        R: Unpin,
+    {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_read_line(cx)).await
+    }
+
+    /// Read a line from the underlying reader into `self.buf`, returning
+    /// `false` at end of file
+    fn poll_read_line(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        let mut this = self.project();
+        if this.pending.is_empty() {
+            // Reuse the allocation of the (empty) line buffer
+            *this.pending = std::mem::take(this.buf).into_bytes();
+        }
+        loop {
+            let available = ready!(this.inner.as_mut().poll_fill_buf(cx))?;
+            if available.is_empty() {
+                break;
+            }
+            let (used, done) = match available.iter().position(|&b| b == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (available.len(), false),
+            };
+            this.pending.extend_from_slice(&available[..used]);
+            this.inner.as_mut().consume(used);
+            if done {
+                break;
+            }
+        }
+        if this.pending.is_empty() {
+            return Poll::Ready(Ok(false));
+        }
+        match String::from_utf8(std::mem::take(this.pending)) {
+            Ok(line) => *this.buf = line,
+            Err(e) => {
+                *this.pending = e.into_bytes();
+                this.pending.clear();
+                return Poll::Ready(Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "stream did not contain valid UTF-8",
+                )));
+            }
+        }
+        if let Err(e) = this.lines.process(this.buf) {
+            this.buf.clear();
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(true))
+    }
+
+    /// Consume the `AsyncJsonLinesReader` and return an asynchronous stream
+    /// that deserializes each line using a fresh
+    /// [`serde::de::DeserializeSeed`] value obtained by calling `factory`.
+    ///
+    /// The returned stream has an `Item` type of `std::io::Result<V>`, where
+    /// `V` is the seed's output type.  Each call to `next()` has the same
+    /// error conditions as [`read_seed()`][AsyncJsonLinesReader::read_seed].
+    pub fn read_all_seeded<F>(self, factory: F) -> JsonLinesSeedStream<R, F> {
+        JsonLinesSeedStream {
+            reader: self,
+            factory,
         }
     }
 
@@ -154,7 +373,7 @@ impl<R: AsyncBufRead> AsyncJsonLinesReader<R> {
     /// [`read()`][AsyncJsonLinesReader::read] method instead.
     pub fn read_all<T>(self) -> JsonLinesStream<R, T> {
         JsonLinesStream {
-            inner: self.inner.lines(),
+            reader: self,
             _output: PhantomData,
         }
     }
@@ -175,7 +394,7 @@ pin_project! {
     #[must_use = "streams do nothing unless polled"]
     pub struct JsonLinesStream<R, T> {
         #[pin]
-        inner: Lines<R>,
+        reader: AsyncJsonLinesReader<R>,
         _output: PhantomData<T>,
     }
 }
@@ -187,9 +406,67 @@ where
     type Item = Result<T>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match ready!(self.project().inner.poll_next_line(cx)) {
-            Ok(Some(line)) => Some(serde_json::from_str::<T>(&line).map_err(Into::into)).into(),
-            Ok(None) => None.into(),
+        let mut reader = self.project().reader;
+        match ready!(reader.as_mut().poll_read_line(cx)) {
+            Ok(true) => {
+                let reader = reader.project();
+                let r = reader.lines.decode_seed(reader.buf, PhantomData::<T>);
+                reader.buf.clear();
+                Some(r).into()
+            }
+            Ok(false) => None.into(),
+            Err(e) => Some(Err(e)).into(),
+        }
+    }
+}
+
+pin_project! {
+    /// An asynchronous stream over the lines of an [`AsyncBufRead`] value `R`
+    /// that decodes each line as JSON using a [`serde::de::DeserializeSeed`]
+    /// value produced by the seed factory `F`.
+    ///
+    /// This stream yields items of type `Result<V, std::io::Error>`, where
+    /// `V` is the seed's output type.  Errors occur under the same conditions
+    /// as for [`AsyncJsonLinesReader::read_seed()`].
+    ///
+    /// Streams of this type are returned by
+    /// [`AsyncJsonLinesReader::read_all_seeded()`].
+    #[must_use = "streams do nothing unless polled"]
+    pub struct JsonLinesSeedStream<R, F> {
+        #[pin]
+        reader: AsyncJsonLinesReader<R>,
+        factory: F,
+    }
+}
+
+impl<R: fmt::Debug, F> fmt::Debug for JsonLinesSeedStream<R, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLinesSeedStream")
+            .field("reader", &self.reader)
+            .field("factory", &"<function>")
+            .finish()
+    }
+}
+
+impl<R: AsyncBufRead, F, S, V> Stream for JsonLinesSeedStream<R, F>
+where
+    F: FnMut() -> S,
+    S: for<'de> DeserializeSeed<'de, Value = V>,
+{
+    type Item = Result<V>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let mut reader = this.reader;
+        match ready!(reader.as_mut().poll_read_line(cx)) {
+            Ok(true) => {
+                let seed = (this.factory)();
+                let reader = reader.project();
+                let r = reader.lines.decode_seed(reader.buf, seed);
+                reader.buf.clear();
+                Some(r).into()
+            }
+            Ok(false) => None.into(),
             Err(e) => Some(Err(e)).into(),
         }
     }
@@ -260,6 +537,7 @@ pin_project! {
     pub struct AsyncJsonLinesWriter<W> {
         #[pin]
         inner: W,
+        terminator: LineTerminator,
     }
 }
 
@@ -267,7 +545,17 @@ impl<W> AsyncJsonLinesWriter<W> {
     /// Construct a new `AsyncJsonLinesWriter` from a
     /// [`tokio::io::AsyncWrite`] instance
     pub fn new(writer: W) -> Self {
-        AsyncJsonLinesWriter { inner: writer }
+        AsyncJsonLinesWriter {
+            inner: writer,
+            terminator: LineTerminator::default(),
+        }
+    }
+
+    /// Set the line terminator written after each value.  The default is
+    /// [`LineTerminator::Lf`].
+    pub fn line_terminator(mut self, terminator: LineTerminator) -> Self {
+        self.terminator = terminator;
+        self
     }
 
     /// Consume the `AsyncJsonLinesWriter` and return the underlying writer
@@ -302,13 +590,38 @@ impl<W> AsyncJsonLinesWriter<W> {
     /// wish to write values of varying types, use the
     /// [`write()`][AsyncJsonLinesWriter::write] method.
     pub fn into_sink<T>(self) -> JsonLinesSink<W, T> {
-        JsonLinesSink::new(self.inner)
+        JsonLinesSink::new(self.inner, self.terminator)
     }
 }
 
 impl<W: AsyncWrite> AsyncJsonLinesWriter<W> {
+    /// Serialize a header value as a line of JSON and write it asynchronously
+    /// to the underlying writer, returning the writer for writing the records
+    /// that follow the header.
+    ///
+    /// This is intended to be called immediately after constructing the
+    /// writer (and setting its [line
+    /// terminator][AsyncJsonLinesWriter::line_terminator], if desired), so
+    /// that the header is written exactly once, before any records.  The
+    /// header can be read back with
+    /// [`AsyncJsonLinesReader::read_with_header()`].
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`write()`][AsyncJsonLinesWriter::write].
+    #[allow(clippy::future_not_send)] // The Future is Send if W is Send
+    pub async fn with_header<H>(mut self, header: &H) -> Result<Self>
+    where
+        H: ?Sized + Serialize,
+        W: Unpin,
+    {
+        self.write(header).await?;
+        Ok(self)
+    }
+
     /// Serialize a value as a line of JSON and write it asynchronously to the
-    /// underlying writer, followed by a newline.
+    /// underlying writer, followed by the line terminator.
     ///
     /// Note that separate calls to this method may write different types of
     /// values.
@@ -324,7 +637,7 @@ impl<W: AsyncWrite> AsyncJsonLinesWriter<W> {
         W: Unpin,
     {
         let mut buf = serde_json::to_vec(value)?;
-        buf.push(b'\n');
+        buf.extend_from_slice(self.terminator.as_bytes());
         self.inner.write_all(&buf).await?;
         Ok(())
     }
@@ -360,16 +673,18 @@ pin_project! {
         inner: W,
         buffer: Option<Vec<u8>>,
         offset: usize,
+        terminator: LineTerminator,
         _input: PhantomData<T>,
     }
 }
 
 impl<W, T> JsonLinesSink<W, T> {
-    fn new(writer: W) -> Self {
+    fn new(writer: W, terminator: LineTerminator) -> Self {
         JsonLinesSink {
             inner: writer,
             buffer: None,
             offset: 0,
+            terminator,
             _input: PhantomData,
         }
     }
@@ -398,7 +713,7 @@ impl<W: AsyncWrite, T> Sink<T> for JsonLinesSink<W, T>
 where
     T: Serialize,
 {
-    type Error = std::io::Error;
+    type Error = Error;
 
     fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
         self.poll_flush_buffer(cx)
@@ -411,7 +726,7 @@ where
         );
         let this = self.project();
         let mut buf = serde_json::to_vec(&item)?;
-        buf.push(b'\n');
+        buf.extend_from_slice(this.terminator.as_bytes());
         *this.buffer = Some(buf);
         *this.offset = 0;
         Ok(())
@@ -501,10 +816,7 @@ pub trait AsyncBufReadJsonLines: AsyncBufRead {
     where
         Self: Sized,
     {
-        JsonLinesStream {
-            inner: self.lines(),
-            _output: PhantomData,
-        }
+        AsyncJsonLinesReader::new(self).read_all()
     }
 }
 
@@ -577,7 +889,7 @@ pub trait AsyncWriteJsonLines: AsyncWrite {
     where
         Self: Sized,
     {
-        JsonLinesSink::new(self)
+        JsonLinesSink::new(self, LineTerminator::default())
     }
 }
 
@@ -596,6 +908,36 @@ mod tests {
         require_send(fut);
     }
 
+    #[test]
+    fn test_read_seed_is_send_if_r_is_send() {
+        let mut ajreader = AsyncJsonLinesReader::new(tokio::io::empty());
+        let fut = ajreader.read_seed(PhantomData::<String>);
+        require_send(fut);
+    }
+
+    #[test]
+    fn test_read_into_is_send_if_r_is_send() {
+        let mut ajreader = AsyncJsonLinesReader::new(tokio::io::empty());
+        let mut s = String::new();
+        let fut = ajreader.read_into(&mut s);
+        require_send(fut);
+    }
+
+    #[test]
+    fn test_read_with_header_is_send_if_r_is_send() {
+        let ajreader = AsyncJsonLinesReader::new(tokio::io::empty());
+        let fut = ajreader.read_with_header::<String, String>();
+        require_send(fut);
+    }
+
+    #[test]
+    fn test_with_header_is_send_if_w_is_send() {
+        let ajwriter = AsyncJsonLinesWriter::new(tokio::io::sink());
+        let s = String::from("This is a header.");
+        let fut = ajwriter.with_header(&s);
+        require_send(fut);
+    }
+
     #[test]
     fn test_write_is_send_if_w_is_send() {
         let mut ajwriter = AsyncJsonLinesWriter::new(tokio::io::sink());
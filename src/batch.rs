@@ -0,0 +1,433 @@
+#![cfg_attr(docsrs, doc(cfg(feature = "arrow")))]
+use crate::migrate::create_temp_file;
+use crate::{JsonLinesReader, JsonLinesWriter};
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::json::reader::{Decoder, ReaderBuilder, infer_json_schema_from_iterator};
+use arrow::json::writer::LineDelimitedWriter;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{File, remove_file, rename};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Result, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Options for converting JSON Lines to Arrow [`RecordBatch`]es and Parquet.
+///
+/// By default, batches contain up to 1024 rows each, and the Arrow schema is
+/// inferred from the first 1000 lines of input.  Fields that do not occur in
+/// those lines are ignored, and later values that do not fit the inferred
+/// schema result in errors, so if the input is not uniform, either increase
+/// the number of lines used for inference with
+/// [`infer_limit()`][BatchOptions::infer_limit] or supply a schema with
+/// [`schema()`][BatchOptions::schema].
+#[derive(Clone, Debug)]
+pub struct BatchOptions {
+    schema: Option<SchemaRef>,
+    batch_size: usize,
+    infer_limit: usize,
+    properties: Option<WriterProperties>,
+}
+
+impl BatchOptions {
+    /// Construct a new `BatchOptions` with the default settings
+    pub fn new() -> Self {
+        BatchOptions {
+            schema: None,
+            batch_size: 1024,
+            infer_limit: 1000,
+            properties: None,
+        }
+    }
+
+    /// Use the given Arrow schema instead of inferring one from the input.
+    /// Fields of the input that are not in the schema are ignored.
+    pub fn schema(mut self, schema: SchemaRef) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Set the maximum number of rows in each batch.  The default is 1024.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the number of lines at the start of the input from which to infer
+    /// the schema when none is supplied.  The default is 1000.  Reading
+    /// fails if this is zero and no schema is supplied.
+    ///
+    /// The lines are held in memory until they have been converted.
+    pub fn infer_limit(mut self, infer_limit: usize) -> Self {
+        self.infer_limit = infer_limit;
+        self
+    }
+
+    /// Set the properties (compression, row group size, etc.) with which
+    /// Parquet files are written.  By default, the `parquet` crate's default
+    /// properties are used.
+    pub fn writer_properties(mut self, properties: WriterProperties) -> Self {
+        self.properties = Some(properties);
+        self
+    }
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions::new()
+    }
+}
+
+impl<R> JsonLinesReader<R> {
+    /// Consume the `JsonLinesReader` and return an iterator over Arrow
+    /// [`RecordBatch`]es of the JSON objects on each line.
+    ///
+    /// The returned iterator has an `Item` type of
+    /// `std::io::Result<RecordBatch>`.  Lines are read and converted as each
+    /// batch is requested, so arbitrarily large inputs can be converted in
+    /// bounded memory.
+    ///
+    /// Errors occur if a line cannot be read, if a line is not a single
+    /// JSON object, or if a value does not fit the schema.  An error of kind
+    /// [`std::io::ErrorKind::InvalidInput`] occurs if the batch size is zero
+    /// or if no schema is supplied and the infer limit is zero.  After an
+    /// error, the iterator does not yield any further items.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arrow::array::{AsArray, RecordBatch};
+    /// use arrow::datatypes::Int64Type;
+    /// use serde_jsonlines::{BatchOptions, JsonLinesReader};
+    ///
+    /// let input = "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2}\n{\"id\": 3, \"name\": \"c\"}\n";
+    /// let batches = JsonLinesReader::new(input.as_bytes())
+    ///     .read_batches(BatchOptions::new().batch_size(2))
+    ///     .collect::<std::io::Result<Vec<RecordBatch>>>()
+    ///     .unwrap();
+    /// assert_eq!(batches.len(), 2);
+    /// assert_eq!(batches[0].num_rows(), 2);
+    /// let ids = batches[1].column_by_name("id").unwrap().as_primitive::<Int64Type>();
+    /// assert_eq!(ids.value(0), 3);
+    /// ```
+    pub fn read_batches(self, options: BatchOptions) -> JsonLinesBatchIter<R> {
+        JsonLinesBatchIter {
+            reader: self,
+            options,
+            decoder: None,
+            pending: VecDeque::new(),
+            done: false,
+        }
+    }
+}
+
+/// An iterator over Arrow [`RecordBatch`]es of the JSON objects on the lines
+/// of a [`BufRead`] value `R`.
+///
+/// Iterators of this type are returned by
+/// [`JsonLinesReader::read_batches()`].
+pub struct JsonLinesBatchIter<R> {
+    reader: JsonLinesReader<R>,
+    options: BatchOptions,
+    /// The decoder for the schema; `None` until the schema is known
+    decoder: Option<Decoder>,
+    /// Lines read in order to infer the schema that have not yet been
+    /// decoded, along with their line numbers
+    pending: VecDeque<(u64, String)>,
+    done: bool,
+}
+
+impl<R> JsonLinesBatchIter<R> {
+    /// Consume the `JsonLinesBatchIter` and return the underlying reader.
+    ///
+    /// Any lines that have been read but not yet yielded as part of a batch
+    /// are lost.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+
+    /// Return the Arrow schema of the batches.  If the schema is being
+    /// inferred, this is `None` until the first batch has been requested.
+    pub fn schema(&self) -> Option<&SchemaRef> {
+        self.options.schema.as_ref()
+    }
+}
+
+impl<R: BufRead> JsonLinesBatchIter<R> {
+    /// Write all remaining batches to a Parquet file via `writer`, returning
+    /// the number of rows written.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as iterating over the
+    /// `JsonLinesBatchIter`.  Errors from writing the Parquet file also
+    /// occur; these are of kind [`std::io::ErrorKind::InvalidData`] unless
+    /// they are I/O errors.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use parquet::basic::Compression;
+    /// use parquet::file::properties::WriterProperties;
+    /// use serde_jsonlines::{BatchOptions, JsonLinesReader};
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let options = BatchOptions::new().writer_properties(
+    ///         WriterProperties::builder()
+    ///             .set_compression(Compression::SNAPPY)
+    ///             .build(),
+    ///     );
+    ///     let reader = JsonLinesReader::new(BufReader::new(File::open("events.jsonl")?));
+    ///     let rows = reader
+    ///         .read_batches(options)
+    ///         .write_parquet(File::create("events.parquet")?)?;
+    ///     println!("Wrote {rows} rows");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn write_parquet<W: Write + Send>(mut self, writer: W) -> Result<u64> {
+        let schema = self.start()?;
+        let properties = self.options.properties.take();
+        let mut writer = ArrowWriter::try_new(writer, schema, properties).map_err(parquet_error)?;
+        let mut rows = 0;
+        for batch in self {
+            let batch = batch?;
+            rows += batch.num_rows() as u64;
+            writer.write(&batch).map_err(parquet_error)?;
+        }
+        writer.close().map_err(parquet_error)?;
+        Ok(rows)
+    }
+
+    /// Determine the schema (reading lines into `pending` if it needs to be
+    /// inferred) and create the decoder if this has not been done yet,
+    /// returning the schema
+    fn start(&mut self) -> Result<SchemaRef> {
+        if self.options.batch_size == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "batch size must be nonzero",
+            ));
+        }
+        if self.options.infer_limit == 0 && self.options.schema.is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "infer limit must be nonzero when no schema is supplied",
+            ));
+        }
+        if let (Some(_), Some(schema)) = (&self.decoder, &self.options.schema) {
+            return Ok(Arc::clone(schema));
+        }
+        let schema = if let Some(schema) = self.options.schema.clone() {
+            schema
+        } else {
+            let mut values = Vec::new();
+            while self.pending.len() < self.options.infer_limit && self.reader.read_line()? {
+                let line = std::mem::take(&mut self.reader.buf);
+                let lineno = self.reader.lines.lineno;
+                values.push(self.reader.lines.decode_seed(&line, PhantomData::<Value>)?);
+                self.pending.push_back((lineno, line));
+            }
+            let schema = infer_json_schema_from_iterator(values.iter().map(Ok))
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            Arc::new(schema)
+        };
+        let decoder = ReaderBuilder::new(Arc::clone(&schema))
+            .with_batch_size(self.options.batch_size)
+            .build_decoder()
+            .map_err(arrow_error)?;
+        self.options.schema = Some(Arc::clone(&schema));
+        self.decoder = Some(decoder);
+        Ok(schema)
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        self.start()?;
+        let Some(decoder) = self.decoder.as_mut() else {
+            return Ok(None);
+        };
+        let mut first = None;
+        let mut last = 0;
+        while decoder.len() < self.options.batch_size {
+            let (lineno, line) = if let Some(pending) = self.pending.pop_front() {
+                pending
+            } else if self.reader.read_line()? {
+                (
+                    self.reader.lines.lineno,
+                    std::mem::take(&mut self.reader.buf),
+                )
+            } else {
+                break;
+            };
+            let before = decoder.len();
+            let consumed = decoder
+                .decode(line.as_bytes())
+                .map_err(|e| Error::new(ErrorKind::InvalidData, format!("line {lineno}: {e}")))?;
+            if consumed < line.len() || decoder.len() != before + 1 || decoder.has_partial_record()
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("line {lineno}: expected a single JSON object"),
+                ));
+            }
+            first.get_or_insert(lineno);
+            last = lineno;
+        }
+        decoder.flush().map_err(|e| match first {
+            Some(first) => Error::new(ErrorKind::InvalidData, format!("lines {first}-{last}: {e}")),
+            None => arrow_error(e),
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLinesBatchIter<R> {
+    type Item = Result<RecordBatch>;
+
+    fn next(&mut self) -> Option<Result<RecordBatch>> {
+        if self.done {
+            return None;
+        }
+        let r = self.next_batch().transpose();
+        if !matches!(r, Some(Ok(_))) {
+            self.done = true;
+        }
+        r
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for JsonLinesBatchIter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLinesBatchIter")
+            .field("reader", &self.reader)
+            .field("options", &self.options)
+            .field("pending", &self.pending)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    /// Write each row of an Arrow [`RecordBatch`] as a line of JSON, followed
+    /// by the line terminator.
+    ///
+    /// Each row is written as a JSON object with a field for each of the
+    /// batch's columns, except that null values are omitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if the
+    /// batch contains a column of a type that cannot be converted to JSON.
+    /// I/O errors from writing are passed through.
+    pub fn write_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        let mut buf = Vec::new();
+        let mut writer = LineDelimitedWriter::new(&mut buf);
+        writer.write(batch).map_err(arrow_error)?;
+        writer.finish().map_err(arrow_error)?;
+        for line in buf.split(|&b| b == b'\n').filter(|line| !line.is_empty()) {
+            self.inner.write_all(line)?;
+            self.inner.write_all(self.terminator.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert the JSON Lines file at `src` to a Parquet file at `dest`,
+/// returning the number of rows written.
+///
+/// The output is written to a temporary file in the same directory as
+/// `dest`, which then replaces `dest` (if it exists) via a rename, so a
+/// failed conversion never leaves a truncated Parquet file at `dest`.
+///
+/// # Errors
+///
+/// Has the same error conditions as [`File::open()`],
+/// [`std::fs::OpenOptions::open()`], [`std::fs::rename()`], and
+/// [`JsonLinesBatchIter::write_parquet()`].
+pub fn json_lines_to_parquet<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dest: Q,
+    options: BatchOptions,
+) -> Result<u64> {
+    let dest = dest.as_ref();
+    let reader = JsonLinesReader::new(BufReader::new(File::open(src)?));
+    let (tmp_path, output) = create_temp_file(dest, "parquet")?;
+    let r = reader
+        .read_batches(options)
+        .write_parquet(output)
+        .and_then(|rows| {
+            rename(&tmp_path, dest)?;
+            Ok(rows)
+        });
+    if r.is_err() {
+        let _ = remove_file(&tmp_path);
+    }
+    r
+}
+
+/// Convert the Parquet file at `src` to a JSON Lines file at `dest` as done
+/// by [`JsonLinesWriter::write_batch()`], returning the number of rows
+/// written.  If `dest` already exists, it is overwritten.
+///
+/// # Errors
+///
+/// Has the same error conditions as [`File::open()`], [`File::create()`],
+/// and [`JsonLinesWriter::write_batch()`].  Errors from reading the Parquet
+/// file also occur; these are of kind [`std::io::ErrorKind::InvalidData`]
+/// unless they are I/O errors.
+pub fn parquet_to_json_lines<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dest: Q) -> Result<u64> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(src)?)
+        .and_then(ParquetRecordBatchReaderBuilder::build)
+        .map_err(parquet_error)?;
+    let mut writer = JsonLinesWriter::new(BufWriter::new(File::create(dest)?));
+    let mut rows = 0;
+    for batch in reader {
+        let batch = batch.map_err(arrow_error)?;
+        rows += batch.num_rows() as u64;
+        writer.write_batch(&batch)?;
+    }
+    writer.flush()?;
+    Ok(rows)
+}
+
+/// Convert an [`ArrowError`] to an I/O error, passing through I/O errors and
+/// treating all other errors as invalid data
+fn arrow_error(e: ArrowError) -> Error {
+    match e {
+        ArrowError::IoError(_, e) => e,
+        e => Error::new(ErrorKind::InvalidData, e),
+    }
+}
+
+/// Convert a [`ParquetError`] to an I/O error, passing through I/O errors and
+/// treating all other errors as invalid data
+fn parquet_error(e: ParquetError) -> Error {
+    match e {
+        ParquetError::External(e) => match e.downcast::<Error>() {
+            Ok(e) => *e,
+            Err(e) => match e.downcast::<ArrowError>() {
+                Ok(e) => arrow_error(*e),
+                Err(e) => Error::new(ErrorKind::InvalidData, e),
+            },
+        },
+        e => Error::new(ErrorKind::InvalidData, e),
+    }
+}
@@ -0,0 +1,352 @@
+//! `jsonl` — a command-line tool for working with JSON Lines files
+//!
+//! Each subcommand reads the files given on the command line in order (or
+//! standard input if no files or `-` are given), decompressing any files
+//! whose names end in `.gz`.  Errors are reported with the file name & line
+//! number at which they occurred, along with the offending line and a caret
+//! pointing at the error position.
+use clap::{Parser, Subcommand};
+use flate2::read::MultiGzDecoder;
+use serde_json::value::RawValue;
+use serde_jsonlines::{Diagnostic, JsonLinesReader, JsonLinesWriter};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Work with JSON Lines files
+#[derive(Clone, Debug, Eq, Parser, PartialEq)]
+#[command(version)]
+struct Arguments {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
+enum Command {
+    /// Check that every line of the input is a single JSON value.
+    ///
+    /// All invalid lines are reported, and the command exits nonzero if there
+    /// were any.
+    Validate {
+        /// Files to read [default: standard input]
+        files: Vec<PathBuf>,
+    },
+    /// Output the total number of JSON values in the input
+    Count {
+        /// Files to read [default: standard input]
+        files: Vec<PathBuf>,
+    },
+    /// Output the first N values of the input
+    Head {
+        /// Number of values to output
+        #[arg(short = 'n', long, default_value_t = 10)]
+        lines: usize,
+
+        /// Files to read [default: standard input]
+        files: Vec<PathBuf>,
+    },
+    /// Output the last N values of the input
+    Tail {
+        /// Number of values to output
+        #[arg(short = 'n', long, default_value_t = 10)]
+        lines: usize,
+
+        /// Files to read [default: standard input]
+        files: Vec<PathBuf>,
+    },
+    /// Concatenate the inputs, decompressing gzipped files
+    Cat {
+        /// Files to read [default: standard input]
+        files: Vec<PathBuf>,
+    },
+    /// Re-serialize each value compactly, without insignificant whitespace
+    Fmt {
+        /// Files to read [default: standard input]
+        files: Vec<PathBuf>,
+    },
+}
+
+impl Command {
+    fn run(self) -> Result<bool, CliError> {
+        match self {
+            Command::Validate { files } => Ok(validate(&files)),
+            Command::Count { files } => {
+                let mut count = 0u64;
+                for_each_value(&files, |_| {
+                    count += 1;
+                    Ok(true)
+                })?;
+                let mut stdout = io::stdout().lock();
+                writeln!(stdout, "{count}").map_err(CliError::Write)?;
+                stdout.flush().map_err(CliError::Write)?;
+                Ok(true)
+            }
+            Command::Head { lines, files } => {
+                let mut out = Output::new();
+                let mut remaining = lines;
+                if remaining > 0 {
+                    for_each_value(&files, |value| {
+                        out.write(&value)?;
+                        remaining -= 1;
+                        Ok(remaining > 0)
+                    })?;
+                }
+                out.finish()
+            }
+            Command::Tail { lines, files } => {
+                let mut last = VecDeque::with_capacity(lines.min(1024));
+                if lines > 0 {
+                    for_each_value(&files, |value| {
+                        if last.len() == lines {
+                            last.pop_front();
+                        }
+                        last.push_back(value);
+                        Ok(true)
+                    })?;
+                }
+                let mut out = Output::new();
+                for value in last {
+                    out.write(&value)?;
+                }
+                out.finish()
+            }
+            Command::Cat { files } => {
+                let mut out = Output::new();
+                for_each_value(&files, |value| {
+                    out.write(&value)?;
+                    Ok(true)
+                })?;
+                out.finish()
+            }
+            Command::Fmt { files } => {
+                let mut out = Output::new();
+                for_each_value(&files, |value| {
+                    out.write(&minify(&value))?;
+                    Ok(true)
+                })?;
+                out.finish()
+            }
+        }
+    }
+}
+
+/// Check every line of every file, reporting each invalid line.  Returns
+/// `false` if any problems were found.
+fn validate(files: &[PathBuf]) -> bool {
+    let mut ok = true;
+    for input in inputs(files) {
+        let mut input = match input {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("jsonl: {e}");
+                ok = false;
+                continue;
+            }
+        };
+        loop {
+            match input.next() {
+                Ok(Some(_)) => (),
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("jsonl: {e}");
+                    ok = false;
+                    if !e.is_line_error() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    ok
+}
+
+/// Call `f` on each value in each file in turn until it returns `Ok(false)`
+/// or an error occurs
+fn for_each_value<F>(files: &[PathBuf], mut f: F) -> Result<(), CliError>
+where
+    F: FnMut(Box<RawValue>) -> Result<bool, CliError>,
+{
+    for input in inputs(files) {
+        let mut input = input?;
+        while let Some(value) = input.next()? {
+            if !f(value)? {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Return an iterator that opens each of the given files in turn
+fn inputs(files: &[PathBuf]) -> impl Iterator<Item = Result<Input, CliError>> + '_ {
+    let paths = if files.is_empty() {
+        vec![Path::new("-")]
+    } else {
+        files.iter().map(PathBuf::as_path).collect()
+    };
+    paths.into_iter().map(Input::open)
+}
+
+/// An input file being read value by value
+struct Input {
+    name: String,
+    reader: JsonLinesReader<Box<dyn BufRead>>,
+    line: u64,
+}
+
+impl Input {
+    fn open(path: &Path) -> Result<Input, CliError> {
+        if path == Path::new("-") {
+            return Ok(Input::new(
+                String::from("<stdin>"),
+                Box::new(io::stdin().lock()),
+            ));
+        }
+        let name = path.display().to_string();
+        let fp = match File::open(path) {
+            Ok(fp) => fp,
+            Err(source) => return Err(CliError::Open { name, source }),
+        };
+        let reader: Box<dyn BufRead> = if path.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(BufReader::new(MultiGzDecoder::new(BufReader::new(fp))))
+        } else {
+            Box::new(BufReader::new(fp))
+        };
+        Ok(Input::new(name, reader))
+    }
+
+    fn new(name: String, reader: Box<dyn BufRead>) -> Input {
+        Input {
+            name,
+            reader: JsonLinesReader::new(reader).diagnostics(true),
+            line: 0,
+        }
+    }
+
+    /// Read the next value, returning `Ok(None)` at end of file
+    fn next(&mut self) -> Result<Option<Box<RawValue>>, CliError> {
+        self.line += 1;
+        self.reader
+            .read::<Box<RawValue>>()
+            .map_err(|source| CliError::Read {
+                name: self.name.clone(),
+                line: self.line,
+                source,
+            })
+    }
+}
+
+/// Buffered standard output that values are written to as JSON Lines
+struct Output {
+    writer: JsonLinesWriter<BufWriter<io::StdoutLock<'static>>>,
+}
+
+impl Output {
+    fn new() -> Output {
+        Output {
+            writer: JsonLinesWriter::new(BufWriter::new(io::stdout().lock())),
+        }
+    }
+
+    fn write(&mut self, value: &RawValue) -> Result<(), CliError> {
+        self.writer.write(value).map_err(CliError::Write)
+    }
+
+    fn finish(mut self) -> Result<bool, CliError> {
+        self.writer.flush().map_err(CliError::Write)?;
+        Ok(true)
+    }
+}
+
+#[derive(Debug)]
+enum CliError {
+    Open {
+        name: String,
+        source: io::Error,
+    },
+    Read {
+        name: String,
+        line: u64,
+        source: io::Error,
+    },
+    Write(io::Error),
+}
+
+impl CliError {
+    /// Returns true if the error only affects a single line, so that reading
+    /// can continue with the next line
+    fn is_line_error(&self) -> bool {
+        match self {
+            CliError::Read { source, .. } => {
+                source.kind() == ErrorKind::InvalidData
+                    || source
+                        .get_ref()
+                        .is_some_and(|e| e.is::<Diagnostic>() || e.is::<serde_json::Error>())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Open { name, source } => write!(f, "{name}: {source}"),
+            CliError::Read { name, line, source } => {
+                match source
+                    .get_ref()
+                    .and_then(|e| e.downcast_ref::<Diagnostic>())
+                {
+                    Some(diag) => write!(f, "{}", diag.clone().file(name)),
+                    None => write!(f, "{name}:{line}: {source}"),
+                }
+            }
+            CliError::Write(source) => write!(f, "error writing output: {source}"),
+        }
+    }
+}
+
+/// Remove all insignificant whitespace from a JSON value
+fn minify(value: &RawValue) -> Box<RawValue> {
+    let json = value.get();
+    let mut out = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in json.chars() {
+        if in_string {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if !matches!(c, ' ' | '\t' | '\n' | '\r') {
+            if c == '"' {
+                in_string = true;
+            }
+            out.push(c);
+        }
+    }
+    // Removing whitespace outside of strings cannot make valid JSON invalid,
+    // but fall back to the original just in case.
+    RawValue::from_string(out).unwrap_or_else(|_| value.to_owned())
+}
+
+fn main() -> ExitCode {
+    match Arguments::parse().command.run() {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        // Exit quietly when output is piped to a command like `head` that
+        // stops reading early
+        Err(CliError::Write(e)) if e.kind() == ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("jsonl: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
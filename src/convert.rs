@@ -0,0 +1,578 @@
+#![cfg_attr(docsrs, doc(cfg(feature = "csv")))]
+use crate::{JsonLinesReader, JsonLinesWriter};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{Map, Number, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{
+    BufRead, BufReader, BufWriter, Error, ErrorKind, Read, Result, Seek, SeekFrom, Write,
+};
+use std::path::Path;
+
+/// How the cells of a CSV column are converted to JSON values by
+/// [`CsvToJsonLines`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ColumnType {
+    /// Convert cells that look like JSON numbers to numbers and cells that
+    /// are `true` or `false` (ignoring case) to booleans, leaving all other
+    /// cells as strings.  Empty cells become `null`.
+    #[default]
+    Infer,
+    /// Leave cells as strings, including empty cells
+    String,
+    /// Convert cells to integers.  Empty cells become `null`.
+    Integer,
+    /// Convert cells to (finite) numbers.  Empty cells become `null`.
+    Number,
+    /// Convert cells that are `true` or `false` (ignoring case) to booleans.
+    /// Empty cells become `null`.
+    Boolean,
+    /// Parse cells as JSON.  Empty cells become `null`.
+    Json,
+}
+
+impl ColumnType {
+    /// Convert a single cell to a JSON value, returning `None` if the cell is
+    /// not valid for the type
+    fn convert(self, cell: &str) -> Option<Value> {
+        if cell.is_empty() && self != ColumnType::String {
+            return Some(Value::Null);
+        }
+        match self {
+            ColumnType::Infer => Some(
+                parse_bool(cell)
+                    .map(Value::Bool)
+                    .or_else(|| cell.parse::<Number>().ok().map(Value::Number))
+                    .unwrap_or_else(|| Value::String(cell.to_owned())),
+            ),
+            ColumnType::String => Some(Value::String(cell.to_owned())),
+            ColumnType::Integer => cell
+                .parse::<i64>()
+                .map(Number::from)
+                .or_else(|_| cell.parse::<u64>().map(Number::from))
+                .ok()
+                .map(Value::Number),
+            ColumnType::Number => cell
+                .parse::<Number>()
+                .ok()
+                .or_else(|| cell.parse::<f64>().ok().and_then(Number::from_f64))
+                .map(Value::Number),
+            ColumnType::Boolean => parse_bool(cell).map(Value::Bool),
+            ColumnType::Json => serde_json::from_str(cell).ok(),
+        }
+    }
+
+    /// Return a description of the values accepted by the type, for use in
+    /// error messages
+    fn expected(self) -> &'static str {
+        match self {
+            ColumnType::Infer | ColumnType::String => "a string",
+            ColumnType::Integer => "an integer",
+            ColumnType::Number => "a number",
+            ColumnType::Boolean => "a boolean",
+            ColumnType::Json => "a JSON value",
+        }
+    }
+}
+
+fn parse_bool(cell: &str) -> Option<bool> {
+    if cell.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if cell.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// A converter from CSV with a header row to JSON Lines, in which each row
+/// becomes a JSON object whose keys are the column names in header order.
+///
+/// By default, the type of each cell is inferred: cells that look like JSON
+/// numbers become numbers, cells that are `true` or `false` (ignoring case)
+/// become booleans, empty cells become `null`, and all other cells are left
+/// as strings.  Specific columns can be given other types with
+/// [`column_type()`][CsvToJsonLines::column_type] (e.g., in order to keep ZIP
+/// codes or ID numbers as strings), and inference for the remaining columns
+/// can be disabled with [`infer_types()`][CsvToJsonLines::infer_types].
+///
+/// Input is converted one row at a time, so arbitrarily large inputs can be
+/// converted in constant memory.
+///
+/// # Example
+///
+/// ```
+/// use serde_jsonlines::{ColumnType, CsvToJsonLines};
+///
+/// let csv = "id,name,price,in_stock\n007,Widget,2.50,TRUE\n008,Gadget,,false\n";
+/// let mut jsonl = Vec::new();
+/// let n = CsvToJsonLines::new()
+///     .column_type("id", ColumnType::String)
+///     .convert(csv.as_bytes(), &mut jsonl)
+///     .unwrap();
+/// assert_eq!(n, 2);
+/// assert_eq!(
+///     String::from_utf8(jsonl).unwrap(),
+///     concat!(
+///         "{\"id\":\"007\",\"name\":\"Widget\",\"price\":2.5,\"in_stock\":true}\n",
+///         "{\"id\":\"008\",\"name\":\"Gadget\",\"price\":null,\"in_stock\":false}\n",
+///     )
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CsvToJsonLines {
+    delimiter: u8,
+    infer: bool,
+    columns: BTreeMap<String, ColumnType>,
+}
+
+impl CsvToJsonLines {
+    /// Construct a new `CsvToJsonLines` that reads comma-separated input and
+    /// infers the type of every column
+    pub fn new() -> Self {
+        CsvToJsonLines {
+            delimiter: b',',
+            infer: true,
+            columns: BTreeMap::new(),
+        }
+    }
+
+    /// Set the field delimiter of the CSV input.  The default is `b','`.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set whether to infer the types of columns that have not been given a
+    /// type with [`column_type()`][CsvToJsonLines::column_type].  When
+    /// disabled, such columns are treated as [`ColumnType::String`].  The
+    /// default is `true`.
+    pub fn infer_types(mut self, infer: bool) -> Self {
+        self.infer = infer;
+        self
+    }
+
+    /// Set how the cells of the column with the given name are converted
+    pub fn column_type<S: Into<String>>(mut self, column: S, ty: ColumnType) -> Self {
+        self.columns.insert(column.into(), ty);
+        self
+    }
+
+    /// Convert CSV read from `reader` to JSON Lines written to `writer`,
+    /// returning the number of rows converted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if a
+    /// column given a type with
+    /// [`column_type()`][CsvToJsonLines::column_type] does not occur in the
+    /// header row.  Returns an error of kind
+    /// [`std::io::ErrorKind::InvalidData`] if the header row contains
+    /// duplicate column names, if the CSV is malformed (e.g., if a row has a
+    /// different number of fields than the header), or if a cell is not
+    /// valid for its column's type.  I/O errors from reading or writing are
+    /// passed through.
+    pub fn convert<R: Read, W: Write>(&self, reader: R, writer: W) -> Result<u64> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .from_reader(reader);
+        let headers = reader
+            .headers()
+            .map_err(csv_error)?
+            .iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let mut seen = HashSet::new();
+        for name in &headers {
+            if !seen.insert(name.as_str()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("duplicate column name {name:?} in CSV header"),
+                ));
+            }
+        }
+        if let Some(name) = self.columns.keys().find(|&k| !seen.contains(k.as_str())) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("column {name:?} does not occur in CSV header"),
+            ));
+        }
+        let default = if self.infer {
+            ColumnType::Infer
+        } else {
+            ColumnType::String
+        };
+        let types = headers
+            .iter()
+            .map(|name| self.columns.get(name).copied().unwrap_or(default))
+            .collect::<Vec<_>>();
+        let mut writer = JsonLinesWriter::new(writer);
+        let mut record = csv::StringRecord::new();
+        let mut values = Vec::with_capacity(headers.len());
+        let mut rows = 0;
+        while reader.read_record(&mut record).map_err(csv_error)? {
+            values.clear();
+            for ((cell, &ty), name) in record.iter().zip(&types).zip(&headers) {
+                let Some(value) = ty.convert(cell) else {
+                    let line = record.position().map_or(0, csv::Position::line);
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "line {line}, column {name:?}: expected {}, got {cell:?}",
+                            ty.expected()
+                        ),
+                    ));
+                };
+                values.push(value);
+            }
+            writer.write(&Row {
+                headers: &headers,
+                values: &values,
+            })?;
+            rows += 1;
+        }
+        writer.flush()?;
+        Ok(rows)
+    }
+
+    /// Convert the CSV file at `src` to a JSON Lines file at `dest`,
+    /// returning the number of rows converted.  If `dest` already exists, it
+    /// is overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`File::open()`], [`File::create()`],
+    /// and [`convert()`][CsvToJsonLines::convert].
+    pub fn convert_file<P: AsRef<Path>, Q: AsRef<Path>>(&self, src: P, dest: Q) -> Result<u64> {
+        let reader = File::open(src)?;
+        let writer = BufWriter::new(File::create(dest)?);
+        self.convert(reader, writer)
+    }
+}
+
+impl Default for CsvToJsonLines {
+    fn default() -> Self {
+        CsvToJsonLines::new()
+    }
+}
+
+/// A row of CSV converted to JSON values, serialized as a JSON object with
+/// its keys in column order
+struct Row<'a> {
+    headers: &'a [String],
+    values: &'a [Value],
+}
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.values.len()))?;
+        for (key, value) in self.headers.iter().zip(self.values) {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// A converter from JSON Lines to CSV, in which each line must be a JSON
+/// object and becomes a row of the CSV output.
+///
+/// Nested objects are flattened into columns with dotted names, so that
+/// `{"user": {"name": "Alice"}}` has a `user.name` column.  Arrays are
+/// written as a single cell with their elements joined by a separator
+/// (`";"` by default; see
+/// [`array_separator()`][JsonLinesToCsv::array_separator]), with any objects
+/// or arrays inside them written as JSON.  `null`s and missing fields are
+/// written as empty cells.  If two fields of a line flatten to the same
+/// column name (e.g., a literal `"a.b"` key alongside an `"a"` object with a
+/// `"b"` key), conversion fails rather than letting one overwrite the other.
+///
+/// The columns of the output are either given explicitly with
+/// [`columns()`][JsonLinesToCsv::columns] (in which case any other fields are
+/// ignored) or are determined by a first pass over the input that collects
+/// every column, ordered by the first line in which each occurs.  Within a
+/// line, fields are visited in the order of [`serde_json::Map`], which is
+/// alphabetical by key unless `serde_json`'s `preserve_order` feature is
+/// enabled.  Either way, input is converted one line at a time, so
+/// arbitrarily large inputs can be converted in constant memory.
+///
+/// # Example
+///
+/// ```
+/// use serde_jsonlines::JsonLinesToCsv;
+/// use std::io::Cursor;
+///
+/// let jsonl = concat!(
+///     "{\"id\": 1, \"user\": {\"name\": \"Alice\"}, \"tags\": [\"a\", \"b\"]}\n",
+///     "{\"id\": 2, \"user\": {\"name\": \"Bob\", \"admin\": true}, \"tags\": []}\n",
+/// );
+/// let mut csv = Vec::new();
+/// let n = JsonLinesToCsv::new()
+///     .array_separator("|")
+///     .convert(Cursor::new(jsonl), &mut csv)
+///     .unwrap();
+/// assert_eq!(n, 2);
+/// assert_eq!(
+///     String::from_utf8(csv).unwrap(),
+///     concat!(
+///         "id,tags,user.name,user.admin\n",
+///         "1,a|b,Alice,\n",
+///         "2,,Bob,true\n",
+///     )
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JsonLinesToCsv {
+    delimiter: u8,
+    array_separator: String,
+    columns: Option<Vec<String>>,
+}
+
+impl JsonLinesToCsv {
+    /// Construct a new `JsonLinesToCsv` that writes comma-separated output
+    /// and determines its columns with a first pass over the input
+    pub fn new() -> Self {
+        JsonLinesToCsv {
+            delimiter: b',',
+            array_separator: String::from(";"),
+            columns: None,
+        }
+    }
+
+    /// Set the field delimiter of the CSV output.  The default is `b','`.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set the separator placed between the elements of an array when
+    /// writing it as a single cell.  The default is `";"`.
+    pub fn array_separator<S: Into<String>>(mut self, separator: S) -> Self {
+        self.array_separator = separator.into();
+        self
+    }
+
+    /// Set the (dotted) names of the columns to write, in order, instead of
+    /// determining them with a first pass over the input
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Return the (dotted) names of all columns that occur in the JSON Lines
+    /// read from `reader`, ordered by the first line in which each occurs
+    /// (and, within a line, as described in the [type-level
+    /// documentation][JsonLinesToCsv]).  This is the first pass performed by
+    /// [`convert()`][JsonLinesToCsv::convert] when no columns have been set.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`convert_stream()`][JsonLinesToCsv::convert_stream].
+    pub fn scan_columns<R: BufRead>(&self, reader: R) -> Result<Vec<String>> {
+        let mut columns = Vec::new();
+        let mut seen = HashSet::new();
+        let mut cells = HashMap::new();
+        let mut reader = JsonLinesReader::new(reader);
+        let mut lineno = 0;
+        while let Some(value) = reader.read::<Value>()? {
+            lineno += 1;
+            cells.clear();
+            let mut names = Vec::new();
+            self.flatten(
+                &mut cells,
+                Some(&mut names),
+                expect_object(value, lineno)?,
+                lineno,
+            )?;
+            for name in names {
+                if seen.insert(name.clone()) {
+                    columns.push(name);
+                }
+            }
+        }
+        Ok(columns)
+    }
+
+    /// Convert JSON Lines read from `reader` to CSV written to `writer`,
+    /// returning the number of rows written (not counting the header row).
+    ///
+    /// If no columns have been set with
+    /// [`columns()`][JsonLinesToCsv::columns], the input is read twice: once
+    /// to determine the columns, after which `reader` is rewound to its
+    /// initial position, and again to write the rows.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`convert_stream()`][JsonLinesToCsv::convert_stream], along with any
+    /// errors from seeking `reader`.
+    pub fn convert<R: BufRead + Seek, W: Write>(&self, mut reader: R, writer: W) -> Result<u64> {
+        if let Some(columns) = &self.columns {
+            return self.write_rows(reader, writer, columns);
+        }
+        let start = reader.stream_position()?;
+        let columns = self.scan_columns(&mut reader)?;
+        reader.seek(SeekFrom::Start(start))?;
+        self.write_rows(reader, writer, &columns)
+    }
+
+    /// Convert JSON Lines read from `reader` to CSV written to `writer` in a
+    /// single pass, returning the number of rows written (not counting the
+    /// header row).  Unlike [`convert()`][JsonLinesToCsv::convert], this does
+    /// not require `reader` to be seekable, but the columns must have been
+    /// set with [`columns()`][JsonLinesToCsv::columns].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if no
+    /// columns have been set.  Returns an error of kind
+    /// [`std::io::ErrorKind::InvalidData`] if a line of input is not valid
+    /// JSON or is not a JSON object.  I/O errors from reading or writing are
+    /// passed through.
+    pub fn convert_stream<R: BufRead, W: Write>(&self, reader: R, writer: W) -> Result<u64> {
+        match &self.columns {
+            Some(columns) => self.write_rows(reader, writer, columns),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "columns must be set in order to convert input in a single pass",
+            )),
+        }
+    }
+
+    /// Convert the JSON Lines file at `src` to a CSV file at `dest`,
+    /// returning the number of rows written (not counting the header row).
+    /// If `dest` already exists, it is overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`File::open()`], [`File::create()`],
+    /// and [`convert()`][JsonLinesToCsv::convert].
+    pub fn convert_file<P: AsRef<Path>, Q: AsRef<Path>>(&self, src: P, dest: Q) -> Result<u64> {
+        let reader = BufReader::new(File::open(src)?);
+        let writer = BufWriter::new(File::create(dest)?);
+        self.convert(reader, writer)
+    }
+
+    fn write_rows<R: BufRead, W: Write>(
+        &self,
+        reader: R,
+        writer: W,
+        columns: &[String],
+    ) -> Result<u64> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .from_writer(writer);
+        writer.write_record(columns).map_err(csv_error)?;
+        let mut cells = HashMap::new();
+        let mut reader = JsonLinesReader::new(reader);
+        let mut rows = 0;
+        while let Some(value) = reader.read::<Value>()? {
+            rows += 1;
+            cells.clear();
+            self.flatten(&mut cells, None, expect_object(value, rows)?, rows)?;
+            writer
+                .write_record(
+                    columns
+                        .iter()
+                        .map(|name| cells.get(name).map_or("", String::as_str)),
+                )
+                .map_err(csv_error)?;
+        }
+        writer.flush()?;
+        Ok(rows)
+    }
+
+    /// Flatten a JSON object into a map from dotted column names to cell
+    /// values, also appending each column name to `names` (if given) in order
+    /// to preserve the order in which they occur.  Fails if two fields
+    /// flatten to the same column name.
+    fn flatten(
+        &self,
+        cells: &mut HashMap<String, String>,
+        mut names: Option<&mut Vec<String>>,
+        obj: Map<String, Value>,
+        lineno: u64,
+    ) -> Result<()> {
+        let mut stack = vec![(String::new(), obj.into_iter())];
+        while let Some((prefix, mut fields)) = stack.pop() {
+            let Some((key, value)) = fields.next() else {
+                continue;
+            };
+            let name = if prefix.is_empty() {
+                key
+            } else {
+                format!("{prefix}.{key}")
+            };
+            stack.push((prefix, fields));
+            match value {
+                Value::Object(inner) => stack.push((name, inner.into_iter())),
+                value => {
+                    let cell = match value {
+                        Value::Array(items) => items
+                            .into_iter()
+                            .map(|v| match v {
+                                Value::Object(_) | Value::Array(_) => v.to_string(),
+                                v => scalar_cell(v),
+                            })
+                            .collect::<Vec<_>>()
+                            .join(&self.array_separator),
+                        v => scalar_cell(v),
+                    };
+                    if cells.contains_key(&name) {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!("line {lineno}: multiple fields flatten to column {name:?}"),
+                        ));
+                    }
+                    if let Some(names) = names.as_deref_mut() {
+                        names.push(name.clone());
+                    }
+                    cells.insert(name, cell);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for JsonLinesToCsv {
+    fn default() -> Self {
+        JsonLinesToCsv::new()
+    }
+}
+
+/// Return the text of a CSV cell for a JSON value that is not an object or
+/// array
+fn scalar_cell(value: Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s,
+        v => v.to_string(),
+    }
+}
+
+/// Convert a [`csv::Error`] to an I/O error, passing through I/O errors and
+/// treating all other errors as invalid data
+fn csv_error(e: csv::Error) -> Error {
+    if !e.is_io_error() {
+        return Error::new(ErrorKind::InvalidData, e);
+    }
+    match e.into_kind() {
+        csv::ErrorKind::Io(e) => e,
+        kind => Error::new(ErrorKind::InvalidData, format!("{kind:?}")),
+    }
+}
+
+fn expect_object(value: Value, lineno: u64) -> Result<Map<String, Value>> {
+    match value {
+        Value::Object(obj) => Ok(obj),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("line {lineno}: expected a JSON object"),
+        )),
+    }
+}
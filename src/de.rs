@@ -0,0 +1,197 @@
+use serde::de::{self, DeserializeOwned, DeserializeSeed, SeqAccess, Visitor};
+use std::io::{BufRead, BufReader, Read};
+
+/// A [`serde::Deserializer`] that deserializes a complete JSON Lines document
+/// as a sequence, with each line deserialized as one element.
+///
+/// This allows a JSON Lines document to be deserialized into any type that
+/// can be deserialized from a sequence, such as `Vec<T>`, `VecDeque<T>`,
+/// `BTreeSet<T>`, or a tuple, including via generic code that is
+/// parameterized over serde formats.  Deserializing into a tuple or other
+/// fixed-size sequence fails if the document has more lines than the
+/// sequence has elements.
+///
+/// The elements are deserialized from lines that have been copied out of the
+/// underlying reader, and so they cannot borrow from the input.
+///
+/// Unlike [`JsonLinesReader`][crate::JsonLinesReader], this type's
+/// [`serde::Deserializer`] implementation uses [`serde_json::Error`] as its
+/// error type; I/O errors are wrapped with [`serde_json::Error::io()`].
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_jsonlines::Deserializer;
+/// use std::collections::VecDeque;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// pub struct Point {
+///     pub x: i32,
+///     pub y: i32,
+/// }
+///
+/// fn main() -> Result<(), serde_json::Error> {
+///     let mut de = Deserializer::from_str("{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n");
+///     let points = VecDeque::<Point>::deserialize(&mut de)?;
+///     assert_eq!(points, [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Deserializer<R> {
+    inner: R,
+    line: Vec<u8>,
+}
+
+impl<R> Deserializer<R> {
+    /// Construct a new `Deserializer` from a [`std::io::BufRead`] instance
+    pub fn new(reader: R) -> Self {
+        Deserializer {
+            inner: reader,
+            line: Vec::new(),
+        }
+    }
+
+    /// Consume the `Deserializer` and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<'a> Deserializer<&'a [u8]> {
+    /// Construct a new `Deserializer` that reads from a string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &'a str) -> Self {
+        Deserializer::new(s.as_bytes())
+    }
+
+    /// Construct a new `Deserializer` that reads from a byte slice
+    pub fn from_slice(bytes: &'a [u8]) -> Self {
+        Deserializer::new(bytes)
+    }
+}
+
+impl<R: BufRead> Deserializer<R> {
+    /// Read the next line into `self.line`, returning `false` at end of input
+    fn next_line(&mut self) -> Result<bool, serde_json::Error> {
+        self.line.clear();
+        let r = self
+            .inner
+            .read_until(b'\n', &mut self.line)
+            .map_err(serde_json::Error::io)?;
+        Ok(r > 0)
+    }
+}
+
+impl<'de, R: BufRead> de::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = visitor.visit_seq(Lines { de: &mut *self })?;
+        if self.next_line()? {
+            return Err(de::Error::custom("trailing lines in JSON Lines document"));
+        }
+        Ok(value)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct Lines<'a, R> {
+    de: &'a mut Deserializer<R>,
+}
+
+impl<'de, R: BufRead> SeqAccess<'de> for Lines<'_, R> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if !self.de.next_line()? {
+            return Ok(None);
+        }
+        // `from_reader()` is used instead of `from_slice()` because the line
+        // does not live for `'de`.
+        let mut jde = serde_json::Deserializer::from_reader(self.de.line.as_slice());
+        let value = seed.deserialize(&mut jde)?;
+        jde.end()?;
+        Ok(Some(value))
+    }
+}
+
+/// Deserialize a JSON Lines document in a string as a sequence, with each
+/// line deserialized as one element.
+///
+/// See [`Deserializer`] for more information.
+///
+/// # Errors
+///
+/// Fails if any line is not valid JSON for the sequence's element type, if
+/// the document has more lines than a fixed-size `T` can hold, or if `T`
+/// cannot be deserialized from a sequence.  The [`std::io::Error`] returned
+/// in such cases has a [`serde_json::Error`] value as its payload.
+///
+/// # Example
+///
+/// ```
+/// use serde_jsonlines::from_str;
+///
+/// fn main() -> std::io::Result<()> {
+///     let (a, b) = from_str::<(i32, String)>("42\n\"foo\"\n")?;
+///     assert_eq!(a, 42);
+///     assert_eq!(b, "foo");
+///     Ok(())
+/// }
+/// ```
+pub fn from_str<T: DeserializeOwned>(s: &str) -> std::io::Result<T> {
+    Ok(T::deserialize(&mut Deserializer::from_str(s))?)
+}
+
+/// Deserialize a JSON Lines document in a byte slice as a sequence, with each
+/// line deserialized as one element.
+///
+/// See [`Deserializer`] for more information.
+///
+/// # Errors
+///
+/// Has the same error conditions as [`from_str()`].
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> std::io::Result<T> {
+    Ok(T::deserialize(&mut Deserializer::from_slice(bytes))?)
+}
+
+/// Deserialize a JSON Lines document read from a [`std::io::Read`] instance
+/// as a sequence, with each line deserialized as one element.
+///
+/// The reader is wrapped in a [`std::io::BufReader`] internally.
+///
+/// See [`Deserializer`] for more information.
+///
+/// # Errors
+///
+/// Has the same error conditions as [`from_str()`] and
+/// [`std::io::BufRead::read_until()`].
+pub fn from_reader<R: Read, T: DeserializeOwned>(reader: R) -> std::io::Result<T> {
+    Ok(T::deserialize(&mut Deserializer::new(BufReader::new(
+        reader,
+    )))?)
+}
@@ -0,0 +1,297 @@
+use std::fmt;
+use std::io::Error;
+use std::path::PathBuf;
+
+/// The maximum number of characters of the offending line shown by the
+/// [`Display`][fmt::Display] implementation for [`Diagnostic`]
+const DEFAULT_WIDTH: usize = 80;
+
+/// A human-readable report of an error that occurred while reading a line of
+/// JSON Lines input, pointing at the position in the line at which the error
+/// occurred.
+///
+/// Errors of this type are produced (as the payload of a [`std::io::Error`])
+/// by readers on which
+/// [`JsonLinesReader::diagnostics()`][crate::JsonLinesReader::diagnostics] or
+/// `AsyncJsonLinesReader::diagnostics()` has been enabled.  They can also be
+/// constructed from any error and the text of the line that caused it with
+/// [`Diagnostic::from_error()`].
+///
+/// The [`Display`][fmt::Display] implementation renders the diagnostic over
+/// several lines: first the location & the error message, then the offending
+/// line (truncated to at most 80 characters around the error position), then
+/// a caret under the position at which the error occurred.  Use
+/// [`Diagnostic::render()`] to truncate the line to a different width.
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_jsonlines::{Diagnostic, JsonLinesReader};
+///
+/// #[derive(Debug, Deserialize)]
+/// pub struct Item {
+///     pub sku: String,
+///     pub qty: u64,
+/// }
+///
+/// let input = "{\"sku\": \"A\", \"qty\": 1}\n{\"sku\": \"B\", \"qty\": \"2\"}\n";
+/// let mut reader = JsonLinesReader::new(input.as_bytes()).diagnostics(true);
+/// assert!(reader.read::<Item>().unwrap().is_some());
+/// let e = reader.read::<Item>().unwrap_err();
+/// let diag = e.get_ref().unwrap().downcast_ref::<Diagnostic>().unwrap();
+/// assert_eq!(diag.line, 2);
+/// assert_eq!(diag.column, Some(23));
+/// assert_eq!(
+///     diag.clone().file("items.jsonl").to_string(),
+///     concat!(
+///         "items.jsonl:2:23: invalid type: string \"2\", expected u64\n",
+///         "  |\n",
+///         "2 | {\"sku\": \"B\", \"qty\": \"2\"}\n",
+///         "  |                       ^\n",
+///     )
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// The path to the file being read, if known
+    pub file: Option<PathBuf>,
+    /// The (1-based) number of the line at which the error occurred
+    pub line: u64,
+    /// The (1-based) byte offset within the line at which the error occurred,
+    /// if known.  This may be one past the end of the line if the error
+    /// occurred at the end of the line.
+    pub column: Option<usize>,
+    /// The path to the field at which deserialization failed (as reported by
+    /// `FieldPathError`), if known
+    pub field: Option<String>,
+    /// A description of the error, without any position information
+    pub message: String,
+    /// The text of the offending line, without its line terminator
+    pub source_line: String,
+}
+
+impl Diagnostic {
+    /// Construct a `Diagnostic` for an error that occurred while reading the
+    /// given line (with or without its line terminator), which is line number
+    /// `line` of the input.
+    ///
+    /// If the error's payload is a [`serde_json::Error`] (or a
+    /// `FieldPathError`), the message and error position are taken from it;
+    /// otherwise, the error's message is used as-is and no position is known.
+    /// If the payload is already a `Diagnostic`, a copy of it is returned.
+    pub fn from_error(error: &Error, source_line: &str, line: u64) -> Diagnostic {
+        let source_line = source_line
+            .strip_suffix('\n')
+            .map_or(source_line, |s| s.strip_suffix('\r').unwrap_or(s));
+        let payload = error.get_ref();
+        if let Some(diag) = payload.and_then(|e| e.downcast_ref::<Diagnostic>()) {
+            return diag.clone();
+        }
+        let (field, json_error) = match field_path_error(error) {
+            Some((path, e)) => (Some(path.to_owned()), Some(e)),
+            None => (
+                None,
+                payload.and_then(|e| e.downcast_ref::<serde_json::Error>()),
+            ),
+        };
+        let (message, column) = match json_error {
+            Some(e) => {
+                let position = format!(" at line {} column {}", e.line(), e.column());
+                let msg = e.to_string();
+                let msg = msg.strip_suffix(&position).unwrap_or(&msg).to_owned();
+                let column = match e.line() {
+                    0 => None,
+                    1 => Some(e.column().max(1)),
+                    // The error occurred after the line terminator (e.g., at
+                    // EOF in the middle of a value), so point at the end of
+                    // the line.
+                    _ => Some(source_line.len() + 1),
+                };
+                (msg, column)
+            }
+            None => (error.to_string(), None),
+        };
+        Diagnostic {
+            file: None,
+            line,
+            column,
+            field,
+            message,
+            source_line: source_line.to_owned(),
+        }
+    }
+
+    /// Set the path to the file being read, which is shown in place of the
+    /// bare line number when rendering
+    pub fn file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// Render the diagnostic as done by the [`Display`][fmt::Display]
+    /// implementation, but showing at most `width` characters of the
+    /// offending line.  Longer lines are truncated around the error position,
+    /// with an ellipsis marking each end that was cut off.
+    pub fn render(&self, width: usize) -> String {
+        let mut s = String::new();
+        // Writing to a String never fails
+        let _ = self.write_to(&mut s, width);
+        s
+    }
+
+    fn write_to<W: fmt::Write>(&self, out: &mut W, width: usize) -> fmt::Result {
+        match (&self.file, self.column) {
+            (Some(file), Some(column)) => {
+                write!(out, "{}:{}:{column}: ", file.display(), self.line)?;
+            }
+            (Some(file), None) => write!(out, "{}:{}: ", file.display(), self.line)?,
+            (None, Some(column)) => write!(out, "line {}, column {column}: ", self.line)?,
+            (None, None) => write!(out, "line {}: ", self.line)?,
+        }
+        if let Some(field) = &self.field {
+            write!(out, "at {field}: ")?;
+        }
+        writeln!(out, "{}", self.message)?;
+        let lineno = self.line.to_string();
+        let gutter = " ".repeat(lineno.len());
+        let (snippet, caret) = self.snippet(width);
+        writeln!(out, "{gutter} |")?;
+        writeln!(out, "{lineno} | {snippet}")?;
+        if let Some(caret) = caret {
+            writeln!(out, "{gutter} | {}^", " ".repeat(caret))?;
+        }
+        Ok(())
+    }
+
+    /// Return the portion of the offending line to show, truncated to `width`
+    /// characters around the error position, along with the (0-based)
+    /// character offset of the error position within it
+    fn snippet(&self, width: usize) -> (String, Option<usize>) {
+        let chars = self
+            .source_line
+            .chars()
+            .map(|c| match c {
+                '\t' => ' ',
+                // Show control characters & BOMs in a way that doesn't mess
+                // up the terminal or the caret's alignment
+                c if c.is_control() || c == '\u{FEFF}' => '\u{FFFD}',
+                c => c,
+            })
+            .collect::<Vec<_>>();
+        let caret = self.column.map(|column| {
+            let mut i = column.saturating_sub(1).min(self.source_line.len());
+            while !self.source_line.is_char_boundary(i) {
+                i -= 1;
+            }
+            self.source_line[..i].chars().count()
+        });
+        let width = width.max(1);
+        let (start, end) = if chars.len() <= width {
+            (0, chars.len())
+        } else {
+            let start = caret.map_or(0, |c| c.saturating_sub(width / 2));
+            let end = (start + width).min(chars.len());
+            (end - width, end)
+        };
+        let mut snippet = String::new();
+        let mut offset = 0;
+        if start > 0 {
+            snippet.push('…');
+            offset = 1;
+        }
+        snippet.extend(&chars[start..end]);
+        if end < chars.len() {
+            snippet.push('…');
+        }
+        (snippet, caret.map(|c| c - start + offset))
+    }
+
+    /// Convert the diagnostic into a [`miette::Report`] that labels the error
+    /// position within the offending line
+    #[cfg(feature = "miette")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "miette")))]
+    pub fn into_report(self) -> miette::Report {
+        let mut diag = miette::MietteDiagnostic::new(self.message);
+        if let Some(column) = self.column {
+            let offset = column.saturating_sub(1).min(self.source_line.len());
+            let label = self
+                .field
+                .map_or_else(|| String::from("here"), |f| format!("at {f}"));
+            diag = diag.with_label(miette::LabeledSpan::at_offset(offset, label));
+        } else if let Some(field) = self.field {
+            diag = diag.with_help(format!("at {field}"));
+        }
+        miette::Report::new(diag).with_source_code(LineSource {
+            name: self.file.map(|p| p.display().to_string()),
+            text: self.source_line,
+            line: usize::try_from(self.line.saturating_sub(1)).unwrap_or(usize::MAX),
+        })
+    }
+}
+
+/// If the payload of `error` is a `FieldPathError`, return its path & the
+/// underlying [`serde_json::Error`]
+#[cfg(feature = "path-to-error")]
+fn field_path_error(error: &Error) -> Option<(&str, &serde_json::Error)> {
+    error
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<crate::FieldPathError>())
+        .map(|e| (e.path.as_str(), &e.error))
+}
+
+#[cfg(not(feature = "path-to-error"))]
+fn field_path_error(_error: &Error) -> Option<(&str, &serde_json::Error)> {
+    None
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f, DEFAULT_WIDTH)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// A single line of source code for a [`miette::Report`] that reports line
+/// numbers relative to the start of the file it came from
+#[cfg(feature = "miette")]
+#[derive(Clone, Debug)]
+struct LineSource {
+    name: Option<String>,
+    text: String,
+    /// The 0-based number of the line
+    line: usize,
+}
+
+#[cfg(feature = "miette")]
+impl miette::SourceCode for LineSource {
+    fn read_span<'a>(
+        &'a self,
+        span: &miette::SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn miette::SpanContents<'a> + 'a>, miette::MietteError> {
+        let contents = self
+            .text
+            .read_span(span, context_lines_before, context_lines_after)?;
+        let line = contents.line() + self.line;
+        Ok(Box::new(match &self.name {
+            Some(name) => miette::MietteSpanContents::new_named(
+                name.clone(),
+                contents.data(),
+                *contents.span(),
+                line,
+                contents.column(),
+                contents.line_count(),
+            ),
+            None => miette::MietteSpanContents::new(
+                contents.data(),
+                *contents.span(),
+                line,
+                contents.column(),
+                contents.line_count(),
+            ),
+        }))
+    }
+}
@@ -0,0 +1,282 @@
+use crate::{JsonLinesReader, LineHandling, Projection};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, Error, ErrorKind, Result};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+type Decoder<T> = Arc<dyn Fn(&str, &LineHandling) -> Result<T> + Send + Sync>;
+type Fallback<T> = Arc<dyn Fn(Value) -> T + Send + Sync>;
+
+/// A registry of decoders for JSON Lines input in which each line may be a
+/// different kind of record, as indicated by a string-valued discriminator
+/// field.
+///
+/// The discriminator is located by a [JSON
+/// Pointer](https://www.rfc-editor.org/rfc/rfc6901) and may appear anywhere
+/// in the line; as with a [`Projection`], only the discriminator's value is
+/// built when looking it up.  The line is then deserialized in full as the
+/// type registered for that discriminator value and converted to the common
+/// output type `T`.  Lines whose discriminator is not registered (or is
+/// absent or not a string) are passed to the fallback as a
+/// [`serde_json::Value`], or result in an error if no fallback was set.
+///
+/// Dispatchers are used with [`JsonLinesReader::read_dispatched()`] and
+/// [`JsonLinesReader::dispatch_all()`].
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_json::Value;
+/// use serde_jsonlines::{Dispatcher, JsonLinesReader};
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// pub struct Span {
+///     pub name: String,
+///     pub duration: u64,
+/// }
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// pub struct Metric {
+///     pub name: String,
+///     pub value: f64,
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// pub enum Record {
+///     Span(Span),
+///     Metric(Metric),
+///     Other(Value),
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let input = concat!(
+///         "{\"name\": \"fetch\", \"duration\": 12, \"type\": \"span\"}\n",
+///         "{\"type\": \"metric\", \"name\": \"cpu\", \"value\": 0.5}\n",
+///         "{\"type\": \"log\", \"message\": \"hello\"}\n",
+///     );
+///     let dispatcher = Dispatcher::new("/type")?
+///         .register("span", Record::Span)
+///         .register("metric", Record::Metric)
+///         .fallback(Record::Other);
+///     let records = JsonLinesReader::new(input.as_bytes())
+///         .dispatch_all(dispatcher)
+///         .collect::<std::io::Result<Vec<_>>>()?;
+///     assert_eq!(
+///         records,
+///         [
+///             Record::Span(Span {
+///                 name: "fetch".into(),
+///                 duration: 12
+///             }),
+///             Record::Metric(Metric {
+///                 name: "cpu".into(),
+///                 value: 0.5
+///             }),
+///             Record::Other(serde_json::json!({"type": "log", "message": "hello"})),
+///         ]
+///     );
+///     Ok(())
+/// }
+/// ```
+pub struct Dispatcher<T> {
+    projection: Projection,
+    decoders: HashMap<String, Decoder<T>>,
+    fallback: Option<Fallback<T>>,
+}
+
+impl<T> Dispatcher<T> {
+    /// Construct a new `Dispatcher` with no registered decoders that reads
+    /// the discriminator at the JSON Pointer `pointer`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if
+    /// `pointer` is not a valid JSON Pointer.
+    pub fn new<S: Into<String>>(pointer: S) -> Result<Self> {
+        Ok(Dispatcher {
+            projection: Projection::new([pointer])?,
+            decoders: HashMap::new(),
+            fallback: None,
+        })
+    }
+
+    /// Register a decoder for lines whose discriminator equals `kind`.  Such
+    /// lines are deserialized as values of type `U` and then passed to `f`.
+    ///
+    /// If a decoder was already registered for `kind`, it is replaced.
+    pub fn register<S, U, F>(mut self, kind: S, f: F) -> Self
+    where
+        S: Into<String>,
+        U: DeserializeOwned,
+        F: Fn(U) -> T + Send + Sync + 'static,
+    {
+        self.decoders.insert(
+            kind.into(),
+            Arc::new(move |line, lines| lines.decode_seed(line, PhantomData::<U>).map(&f)),
+        );
+        self
+    }
+
+    /// Set a function to call on lines whose discriminator is not registered,
+    /// absent, or not a string.  Such lines are deserialized as
+    /// [`serde_json::Value`]s and then passed to `f`.
+    pub fn fallback<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Value) -> T + Send + Sync + 'static,
+    {
+        self.fallback = Some(Arc::new(f));
+        self
+    }
+
+    /// Return the JSON Pointer at which the discriminator is read
+    pub fn pointer(&self) -> &str {
+        &self.projection.pointers()[0]
+    }
+
+    /// Decode a line of JSON using the decoder registered for its
+    /// discriminator or, failing that, the fallback.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if the
+    /// line is not valid JSON, if it cannot be deserialized as the type
+    /// registered for its discriminator, or if there is no decoder for its
+    /// discriminator and no fallback.
+    pub fn decode(&self, line: &str) -> Result<T> {
+        self.decode_with(line, &LineHandling::default())
+    }
+
+    /// Decode a line of JSON as with [`decode()`][Dispatcher::decode], using
+    /// the field path & diagnostic settings of the reader it came from
+    pub(crate) fn decode_with(&self, line: &str, lines: &LineHandling) -> Result<T> {
+        let kind = self
+            .projection
+            .extract_values(line)
+            .map_err(|e| lines.diagnose(e.into(), line))?
+            .pop()
+            .flatten();
+        if let Some(decoder) = kind
+            .as_ref()
+            .and_then(Value::as_str)
+            .and_then(|k| self.decoders.get(k))
+        {
+            return decoder(line, lines);
+        }
+        if let Some(fallback) = &self.fallback {
+            return lines
+                .decode_seed(line, PhantomData::<Value>)
+                .map(&**fallback);
+        }
+        let msg = match kind {
+            Some(kind) => format!("no decoder registered for {} = {kind}", self.pointer()),
+            None => format!("no discriminator found at {:?}", self.pointer()),
+        };
+        Err(lines.diagnose(Error::new(ErrorKind::InvalidData, msg), line))
+    }
+}
+
+impl<T> Clone for Dispatcher<T> {
+    fn clone(&self) -> Self {
+        Dispatcher {
+            projection: self.projection.clone(),
+            decoders: self.decoders.clone(),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Dispatcher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut kinds = self.decoders.keys().collect::<Vec<_>>();
+        kinds.sort();
+        f.debug_struct("Dispatcher")
+            .field("pointer", &self.pointer())
+            .field("kinds", &kinds)
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    /// Read a line of JSON from the underlying reader and decode it with
+    /// `dispatcher` according to its discriminator.
+    ///
+    /// If end-of-file is reached, this method returns `Ok(None)`.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::io::BufRead::read_line()`] and
+    /// [`Dispatcher::decode()`].  As with [`read()`][JsonLinesReader::read],
+    /// reading can continue with the next line after a decoding error.
+    pub fn read_dispatched<T>(&mut self, dispatcher: &Dispatcher<T>) -> Result<Option<T>> {
+        if !self.read_line()? {
+            return Ok(None);
+        }
+        let r = dispatcher.decode_with(&self.buf, &self.lines);
+        self.buf.clear();
+        r.map(Some)
+    }
+}
+
+impl<R> JsonLinesReader<R> {
+    /// Consume the `JsonLinesReader` and return an iterator over the values
+    /// decoded from each line with `dispatcher`.
+    ///
+    /// The returned iterator has an `Item` type of `std::io::Result<T>`.  Each
+    /// call to `next()` has the same error conditions as
+    /// [`read_dispatched()`][JsonLinesReader::read_dispatched].
+    pub fn dispatch_all<T>(self, dispatcher: Dispatcher<T>) -> JsonLinesDispatchIter<R, T> {
+        JsonLinesDispatchIter {
+            reader: self,
+            dispatcher,
+        }
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`] value `R` that decodes each
+/// line with a [`Dispatcher`] according to its discriminator.
+///
+/// This iterator yields items of type `Result<T, std::io::Error>`.  Errors
+/// occur under the same conditions as for
+/// [`JsonLinesReader::read_dispatched()`].
+///
+/// Iterators of this type are returned by [`JsonLinesReader::dispatch_all()`].
+#[derive(Clone, Debug)]
+pub struct JsonLinesDispatchIter<R, T> {
+    reader: JsonLinesReader<R>,
+    dispatcher: Dispatcher<T>,
+}
+
+impl<R, T> JsonLinesDispatchIter<R, T> {
+    /// Consume the `JsonLinesDispatchIter` and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+
+    /// Get a reference to the dispatcher
+    pub fn dispatcher(&self) -> &Dispatcher<T> {
+        &self.dispatcher
+    }
+}
+
+impl<R: BufRead, T> Iterator for JsonLinesDispatchIter<R, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.reader.read_dispatched(&self.dispatcher).transpose()
+    }
+}
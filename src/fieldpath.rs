@@ -0,0 +1,136 @@
+#![cfg_attr(docsrs, doc(cfg(feature = "path-to-error")))]
+use serde::de::{Deserialize, DeserializeOwned, DeserializeSeed};
+use serde_json::error::Category;
+use serde_path_to_error::{Deserializer, Track};
+use std::fmt;
+use std::io::{Error, ErrorKind};
+
+/// An error from deserializing a line of JSON that records the path to the
+/// field at which deserialization failed along with the line's number.
+///
+/// Errors of this type are produced (as the payload of a [`std::io::Error`])
+/// by readers on which
+/// [`JsonLinesReader::field_paths()`][crate::JsonLinesReader::field_paths]
+/// or `AsyncJsonLinesReader::field_paths()` has been enabled.
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_jsonlines::{FieldPathError, JsonLinesReader};
+///
+/// #[derive(Debug, Deserialize)]
+/// pub struct Order {
+///     pub items: Vec<Item>,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// pub struct Item {
+///     pub sku: String,
+///     pub qty: u64,
+/// }
+///
+/// let input = concat!(
+///     "{\"items\": [{\"sku\": \"A\", \"qty\": 1}]}\n",
+///     "{\"items\": [{\"sku\": \"B\", \"qty\": 2}, {\"sku\": \"C\", \"qty\": \"3\"}]}\n",
+/// );
+/// let mut reader = JsonLinesReader::new(input.as_bytes()).field_paths(true);
+/// assert!(reader.read::<Order>().unwrap().is_some());
+/// let e = reader.read::<Order>().unwrap_err();
+/// let e = e.get_ref().unwrap().downcast_ref::<FieldPathError>().unwrap();
+/// assert_eq!(e.line, 2);
+/// assert_eq!(e.path, "items[1].qty");
+/// assert_eq!(
+///     e.to_string(),
+///     "line 2, at items[1].qty: invalid type: string \"3\", expected u64 at line 1 column 58"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct FieldPathError {
+    /// The (1-based) number of the line that failed to deserialize, counting
+    /// from where the reader started
+    pub line: u64,
+    /// The path to the field at which deserialization failed, in the form
+    /// `orders[3].items[0].qty`.  This is `"."` if the error occurred at the
+    /// top level of the line.
+    pub path: String,
+    /// The underlying error
+    pub error: serde_json::Error,
+}
+
+impl fmt::Display for FieldPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, at {}: {}", self.line, self.path, self.error)
+    }
+}
+
+impl std::error::Error for FieldPathError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<FieldPathError> for Error {
+    fn from(e: FieldPathError) -> Error {
+        let kind = match e.error.classify() {
+            Category::Io => ErrorKind::Other,
+            Category::Syntax | Category::Data => ErrorKind::InvalidData,
+            Category::Eof => ErrorKind::UnexpectedEof,
+        };
+        Error::new(kind, e)
+    }
+}
+
+/// Deserialize a single line of JSON using a seed, rejecting any trailing
+/// non-whitespace and tracking the path to the current field
+pub(crate) fn from_str_seed<'de, S>(
+    s: &'de str,
+    seed: S,
+    line: u64,
+) -> Result<S::Value, FieldPathError>
+where
+    S: DeserializeSeed<'de>,
+{
+    let mut de = serde_json::Deserializer::from_str(s);
+    let mut track = Track::new();
+    let r = seed
+        .deserialize(Deserializer::new(&mut de, &mut track))
+        .and_then(|value| de.end().map(|()| value));
+    r.map_err(|error| FieldPathError {
+        line,
+        path: track.path().to_string(),
+        error,
+    })
+}
+
+/// Deserialize a single line of JSON into an existing value, rejecting any
+/// trailing non-whitespace and tracking the path to the current field
+pub(crate) fn from_str_in_place<T>(s: &str, place: &mut T, line: u64) -> Result<(), FieldPathError>
+where
+    T: DeserializeOwned,
+{
+    let mut de = serde_json::Deserializer::from_str(s);
+    let mut track = Track::new();
+    let r = T::deserialize_in_place(Deserializer::new(&mut de, &mut track), place)
+        .and_then(|()| de.end());
+    r.map_err(|error| FieldPathError {
+        line,
+        path: track.path().to_string(),
+        error,
+    })
+}
+
+/// Deserialize a value from an arbitrary JSON deserializer (such as a
+/// [`serde_json::Value`]), tracking the path to the current field
+pub(crate) fn from_deserializer<'de, D, T>(de: D, line: u64) -> Result<T, FieldPathError>
+where
+    D: serde::Deserializer<'de, Error = serde_json::Error>,
+    T: Deserialize<'de>,
+{
+    let mut track = Track::new();
+    T::deserialize(Deserializer::new(de, &mut track)).map_err(|error| FieldPathError {
+        line,
+        path: track.path().to_string(),
+        error,
+    })
+}
@@ -0,0 +1,279 @@
+use crate::{JsonLinesReader, Projection};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::io::{BufRead, Result};
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+/// A set of cheap conditions that a line of JSON must satisfy in order to be
+/// deserialized.
+///
+/// Each condition tests the value at a [JSON
+/// Pointer](https://www.rfc-editor.org/rfc/rfc6901) within the line.  When a
+/// line is checked against a `Filter`, only the values at the pointers used
+/// by the conditions are built (as for a [`Projection`]); the rest of the
+/// line is merely checked for well-formedness and skipped over.  A line
+/// matches the filter if it satisfies all of the filter's conditions; a
+/// filter with no conditions matches every line.
+///
+/// Filters are used with [`JsonLinesReader::read_filtered()`] and
+/// [`JsonLinesReader::filter_all()`] to skip lines that are not of interest
+/// before paying the cost of deserializing them in full.
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_jsonlines::{Filter, JsonLinesReader};
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// pub struct Click {
+///     pub user: u64,
+///     pub x: i32,
+///     pub y: i32,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let input = concat!(
+///         "{\"type\": \"click\", \"user\": 1, \"x\": 10, \"y\": 20}\n",
+///         "{\"type\": \"scroll\", \"user\": 1, \"delta\": 5}\n",
+///         "{\"type\": \"click\", \"user\": 2, \"x\": 30, \"y\": 40}\n",
+///     );
+///     let filter = Filter::new()
+///         .equals("/type", "click")?
+///         .range("/user", 2.0..)?;
+///     let clicks = JsonLinesReader::new(input.as_bytes())
+///         .filter_all::<Click>(filter)
+///         .collect::<std::io::Result<Vec<_>>>()?;
+///     assert_eq!(clicks, [Click { user: 2, x: 30, y: 40 }]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    projection: Projection,
+    conditions: Vec<Condition>,
+}
+
+impl Filter {
+    /// Construct a new `Filter` with no conditions
+    pub fn new() -> Self {
+        Filter {
+            projection: Projection::default(),
+            conditions: Vec::new(),
+        }
+    }
+
+    /// Add a condition that the value at `pointer` must equal `value`.
+    ///
+    /// Values are compared as [`serde_json::Value`]s, and so, e.g., the
+    /// integer `1` is not equal to the float `1.0`.  Use
+    /// [`range()`][Filter::range] to compare numbers numerically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if
+    /// `pointer` is not a valid JSON Pointer.
+    pub fn equals<S, V>(self, pointer: S, value: V) -> Result<Self>
+    where
+        S: Into<String>,
+        V: Into<Value>,
+    {
+        self.with_condition(pointer.into(), |i| Condition::Equals(i, value.into()))
+    }
+
+    /// Add a condition that `pointer` must resolve to a value (which may be
+    /// `null`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if
+    /// `pointer` is not a valid JSON Pointer.
+    pub fn exists<S: Into<String>>(self, pointer: S) -> Result<Self> {
+        self.with_condition(pointer.into(), Condition::Exists)
+    }
+
+    /// Add a condition that the value at `pointer` must be a number within
+    /// `range`.
+    ///
+    /// Numbers are compared as `f64`s.  Values that are not numbers never
+    /// satisfy this condition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if
+    /// `pointer` is not a valid JSON Pointer.
+    pub fn range<S, B>(self, pointer: S, range: B) -> Result<Self>
+    where
+        S: Into<String>,
+        B: RangeBounds<f64>,
+    {
+        let start = range.start_bound().cloned();
+        let end = range.end_bound().cloned();
+        self.with_condition(pointer.into(), |i| Condition::Range(i, start, end))
+    }
+
+    /// Test whether a line of JSON matches the filter.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `line` is not valid JSON.
+    pub fn matches(&self, line: &str) -> serde_json::Result<bool> {
+        if self.conditions.is_empty() {
+            // Still check that the line is well-formed, for consistency
+            serde_json::from_str::<serde::de::IgnoredAny>(line)?;
+            return Ok(true);
+        }
+        let values = self.projection.extract_values(line)?;
+        Ok(self
+            .conditions
+            .iter()
+            .all(|cond| cond.check(&values).unwrap_or(false)))
+    }
+
+    fn with_condition<F>(mut self, pointer: String, make: F) -> Result<Self>
+    where
+        F: FnOnce(usize) -> Condition,
+    {
+        let pointers = self.projection.pointers();
+        let i = if let Some(i) = pointers.iter().position(|p| *p == pointer) {
+            i
+        } else {
+            let i = pointers.len();
+            self.projection = Projection::new(pointers.iter().cloned().chain([pointer]))?;
+            i
+        };
+        self.conditions.push(make(i));
+        Ok(self)
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::new()
+    }
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    /// Read lines from the underlying reader until one matches `filter`, and
+    /// deserialize that line as a value of type `T`.
+    ///
+    /// Lines that do not match the filter are skipped without being
+    /// deserialized as `T`.  If end-of-file is reached, this method returns
+    /// `Ok(None)`.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`read()`][JsonLinesReader::read].
+    /// In particular, a line that is not valid JSON results in an error
+    /// regardless of whether it would have matched the filter.
+    pub fn read_filtered<T>(&mut self, filter: &Filter) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        loop {
+            if !self.read_line()? {
+                return Ok(None);
+            }
+            let r = filter
+                .matches(&self.buf)
+                .map_err(|e| self.lines.diagnose(e.into(), &self.buf))
+                .and_then(|matched| {
+                    matched
+                        .then(|| self.lines.decode_seed(&self.buf, PhantomData::<T>))
+                        .transpose()
+                });
+            self.buf.clear();
+            if let Some(value) = r? {
+                return Ok(Some(value));
+            }
+        }
+    }
+}
+
+impl<R> JsonLinesReader<R> {
+    /// Consume the `JsonLinesReader` and return an iterator over the
+    /// deserialized JSON values from each line that matches `filter`.
+    ///
+    /// The returned iterator has an `Item` type of `std::io::Result<T>`.  Each
+    /// call to `next()` has the same error conditions as
+    /// [`read_filtered()`][JsonLinesReader::read_filtered].
+    pub fn filter_all<T>(self, filter: Filter) -> JsonLinesFilterIter<R, T> {
+        JsonLinesFilterIter {
+            reader: self,
+            filter,
+            _output: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`] value `R` that match a
+/// [`Filter`], decoding each matching line as JSON of type `T`.
+///
+/// This iterator yields items of type `Result<T, std::io::Error>`.  Errors
+/// occur under the same conditions as for
+/// [`JsonLinesReader::read_filtered()`].
+///
+/// Iterators of this type are returned by [`JsonLinesReader::filter_all()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonLinesFilterIter<R, T> {
+    reader: JsonLinesReader<R>,
+    filter: Filter,
+    _output: PhantomData<T>,
+}
+
+impl<R, T> JsonLinesFilterIter<R, T> {
+    /// Consume the `JsonLinesFilterIter` and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+
+    /// Get a reference to the filter
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+}
+
+impl<R, T> Iterator for JsonLinesFilterIter<R, T>
+where
+    R: BufRead,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.reader.read_filtered(&self.filter).transpose()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Condition {
+    Equals(usize, Value),
+    Exists(usize),
+    Range(usize, Bound<f64>, Bound<f64>),
+}
+
+impl Condition {
+    /// Test the condition against the extracted values, returning `None` if
+    /// the pointer did not resolve
+    fn check(&self, values: &[Option<Value>]) -> Option<bool> {
+        match self {
+            Condition::Equals(i, expected) => Some(values.get(*i)?.as_ref()? == expected),
+            Condition::Exists(i) => Some(values.get(*i)?.is_some()),
+            Condition::Range(i, start, end) => {
+                let x = values.get(*i)?.as_ref()?.as_f64()?;
+                Some((*start, *end).contains(&x))
+            }
+        }
+    }
+}
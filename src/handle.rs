@@ -0,0 +1,293 @@
+use serde::Serialize;
+use std::fmt;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::marker::PhantomData;
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{Builder, JoinHandle};
+
+/// A cloneable handle for writing JSON Lines to a [`std::io::Write`] instance
+/// owned by a dedicated background thread.
+///
+/// Values passed to [`write()`][JsonLinesWriterHandle::write] are serialized
+/// on the calling thread, and the resulting line is then sent over a bounded
+/// channel to the writer thread, which writes the lines to the underlying
+/// writer in the order that they were received.  This allows many threads to
+/// write to a single JSON Lines output without contending on a lock during
+/// serialization.
+///
+/// Because the actual I/O happens on the writer thread, errors from writing
+/// to the underlying writer are not returned by `write()`.  Instead, the
+/// writer thread remembers the error and reports it on the next call to
+/// [`flush()`][JsonLinesWriterHandle::flush] or
+/// [`shutdown()`][JsonLinesWriterHandle::shutdown].  As a failed write may
+/// have left part of a line in the output, the writer thread discards all
+/// lines that it receives after an error until the error has been reported,
+/// and it then terminates any such partial line before writing the next
+/// line, so that later lines are never run together with a partial one.
+///
+/// If all handles are dropped without calling `shutdown()`, the writer thread
+/// flushes the underlying writer and exits, discarding any error.
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Serialize;
+/// use serde_jsonlines::JsonLinesWriterHandle;
+/// use std::fs::File;
+/// use std::io::BufWriter;
+/// use std::thread;
+///
+/// #[derive(Serialize)]
+/// pub struct Event {
+///     pub worker: usize,
+///     pub seq: usize,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let fp = BufWriter::new(File::create("events.jsonl")?);
+///     let handle = JsonLinesWriterHandle::<Event>::spawn(fp, 1024)?;
+///     thread::scope(|s| {
+///         for worker in 0..4 {
+///             let handle = handle.clone();
+///             s.spawn(move || {
+///                 for seq in 0..100 {
+///                     handle.write(&Event { worker, seq })?;
+///                 }
+///                 Ok::<(), std::io::Error>(())
+///             });
+///         }
+///     });
+///     handle.shutdown()
+/// }
+/// ```
+pub struct JsonLinesWriterHandle<T> {
+    sender: SyncSender<Message>,
+    thread: Arc<Mutex<Option<JoinHandle<()>>>>,
+    // `fn(&T)` makes the handle `Send` & `Sync` regardless of `T`
+    _input: PhantomData<fn(&T)>,
+}
+
+impl<T> JsonLinesWriterHandle<T> {
+    /// Spawn a writer thread that takes ownership of `writer` and return a
+    /// handle for sending values to it.
+    ///
+    /// `capacity` is the number of serialized lines that may be queued for
+    /// the writer thread at once; if the queue is full, calls to
+    /// [`write()`][JsonLinesWriterHandle::write] block until there is room.
+    /// A `capacity` of zero means that each call to `write()` blocks until
+    /// the writer thread receives the line.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::thread::Builder::spawn()`].
+    pub fn spawn<W>(writer: W, capacity: usize) -> Result<Self>
+    where
+        W: Write + Send + 'static,
+    {
+        let (sender, receiver) = sync_channel(capacity);
+        let thread = Builder::new()
+            .name(String::from("jsonlines-writer"))
+            .spawn(move || run_writer(writer, &receiver))?;
+        Ok(JsonLinesWriterHandle {
+            sender,
+            thread: Arc::new(Mutex::new(Some(thread))),
+            _input: PhantomData,
+        })
+    }
+
+    /// Wait until every line sent by any handle before this call has been
+    /// written, and then flush the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer thread has been shut down, if flushing
+    /// the underlying writer fails, or if writing any line to the underlying
+    /// writer has failed since the previous call to `flush()`.  In the last
+    /// case, the error from the failed write is returned, and any lines sent
+    /// after that write were discarded.
+    pub fn flush(&self) -> Result<()> {
+        let (reply, response) = sync_channel(1);
+        self.send(Message::Flush(reply))?;
+        response.recv().map_err(|_| shut_down())?
+    }
+
+    /// Shut down the writer thread.
+    ///
+    /// The writer thread writes out all lines sent by any handle before this
+    /// call, flushes the underlying writer, and then exits, dropping the
+    /// underlying writer.  This method returns once the thread has exited.
+    ///
+    /// After the writer thread has been shut down, calls to
+    /// [`write()`][JsonLinesWriterHandle::write] and
+    /// [`flush()`][JsonLinesWriterHandle::flush] on any of its handles will
+    /// fail.  Calling `shutdown()` again has no effect.  If several handles
+    /// call `shutdown()` concurrently, every call waits for the thread to
+    /// exit, but only the first call reports the thread's errors; the others
+    /// return `Ok(())`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if flushing the underlying writer fails or if writing
+    /// any line to the underlying writer has failed since the last call to
+    /// [`flush()`][JsonLinesWriterHandle::flush].  In the latter case, the
+    /// error from the failed write is returned, and any lines sent after that
+    /// write were discarded.
+    ///
+    /// An error is also returned if the writer thread panicked.
+    pub fn shutdown(&self) -> Result<()> {
+        // Hold the lock until the thread has been joined so that concurrent
+        // callers do not return before the shutdown has finished
+        let mut thread = self.thread.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(handle) = thread.take() else {
+            return Ok(());
+        };
+        let (reply, response) = sync_channel(1);
+        let r = match self.send(Message::Shutdown(reply)) {
+            Ok(()) => response.recv().unwrap_or(Ok(())),
+            Err(_) => Ok(()),
+        };
+        if handle.join().is_err() {
+            return Err(Error::other("JSON Lines writer thread panicked"));
+        }
+        r
+    }
+
+    fn send(&self, msg: Message) -> Result<()> {
+        self.sender.send(msg).map_err(|_| shut_down())
+    }
+}
+
+impl<T: Serialize> JsonLinesWriterHandle<T> {
+    /// Serialize a value as a line of JSON and send it to the writer thread.
+    ///
+    /// This method blocks if the writer thread's queue is full.  It does not
+    /// wait for the line to actually be written; use
+    /// [`flush()`][JsonLinesWriterHandle::flush] for that.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`serde_json::to_writer()`].  An
+    /// error is also returned if the writer thread has been shut down.
+    pub fn write(&self, value: &T) -> Result<()> {
+        let mut buf = serde_json::to_vec(value)?;
+        buf.push(b'\n');
+        self.send(Message::Line(buf))
+    }
+
+    /// Serialize each item in an iterator as a line of JSON and send them to
+    /// the writer thread.
+    ///
+    /// The lines are sent one at a time, and so lines from other handles may
+    /// be written in between them.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`write()`][JsonLinesWriterHandle::write].
+    pub fn write_all<I>(&self, items: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for value in items {
+            self.write(&value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Clone for JsonLinesWriterHandle<T> {
+    fn clone(&self) -> Self {
+        JsonLinesWriterHandle {
+            sender: self.sender.clone(),
+            thread: Arc::clone(&self.thread),
+            _input: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for JsonLinesWriterHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLinesWriterHandle")
+            .field("sender", &self.sender)
+            .field("thread", &self.thread)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+enum Message {
+    Line(Vec<u8>),
+    Flush(SyncSender<Result<()>>),
+    Shutdown(SyncSender<Result<()>>),
+}
+
+fn run_writer<W: Write>(mut writer: W, receiver: &Receiver<Message>) {
+    let mut error = None;
+    // Whether a failed write left part of a line in the output
+    let mut partial = false;
+    // `recv()` only fails once all handles have been dropped
+    while let Ok(msg) = receiver.recv() {
+        match msg {
+            // After a failed write, discard lines until the error has been
+            // reported so that they aren't appended to a partial line
+            Message::Line(buf) if error.is_none() => {
+                // Terminate the partial line before writing a new one
+                if partial {
+                    match write_bytes(&mut writer, b"\n") {
+                        Ok(()) => partial = false,
+                        Err((e, _)) => {
+                            error = Some(e);
+                            continue;
+                        }
+                    }
+                }
+                if let Err((e, written)) = write_bytes(&mut writer, &buf) {
+                    partial = written > 0;
+                    error = Some(e);
+                }
+            }
+            Message::Line(_) => (),
+            Message::Flush(reply) => {
+                let r = finish(&mut writer, error.take());
+                let _ = reply.send(r);
+            }
+            Message::Shutdown(reply) => {
+                let r = finish(&mut writer, error.take());
+                let _ = reply.send(r);
+                return;
+            }
+        }
+    }
+    let _ = writer.flush();
+}
+
+/// Write all of `buf` to `writer`.  On failure, the error is returned along
+/// with the number of bytes that were written before it occurred.
+fn write_bytes<W: Write>(writer: &mut W, buf: &[u8]) -> std::result::Result<(), (Error, usize)> {
+    let mut written = 0;
+    while written < buf.len() {
+        match writer.write(&buf[written..]) {
+            Ok(0) => return Err((Error::from(ErrorKind::WriteZero), written)),
+            Ok(n) => written += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => (),
+            Err(e) => return Err((e, written)),
+        }
+    }
+    Ok(())
+}
+
+fn finish<W: Write>(writer: &mut W, error: Option<Error>) -> Result<()> {
+    let r = writer.flush();
+    match error {
+        Some(e) => Err(e),
+        None => r,
+    }
+}
+
+fn shut_down() -> Error {
+    Error::new(
+        ErrorKind::BrokenPipe,
+        "JSON Lines writer thread has shut down",
+    )
+}
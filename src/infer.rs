@@ -0,0 +1,610 @@
+use crate::JsonLinesReader;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Result};
+use std::path::Path;
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Read up to `sample_limit` values (or all values, if `sample_limit` is
+/// `None`) from a JSON Lines reader and merge their shapes into a single
+/// [`Shape`] describing them all.
+///
+/// The resulting shape records which JSON types were seen at each location
+/// (and how often), the ranges of numbers, the lengths of arrays, and how
+/// often each object field was present.  It can then be rendered as a JSON
+/// Schema with [`Shape::to_json_schema()`] or as Rust type definitions with
+/// [`Shape::to_rust()`].
+///
+/// # Errors
+///
+/// Has the same error conditions as [`JsonLinesReader::read()`].
+///
+/// # Example
+///
+/// ```
+/// use serde_json::json;
+/// use serde_jsonlines::infer_schema;
+///
+/// fn main() -> std::io::Result<()> {
+///     let input = concat!(
+///         "{\"id\": 1, \"name\": \"Foo\", \"tags\": [\"a\", \"b\"]}\n",
+///         "{\"id\": 2, \"name\": null, \"tags\": [], \"score\": 0.5}\n",
+///     );
+///     let shape = infer_schema(input.as_bytes(), Some(100))?;
+///     assert_eq!(shape.count, 2);
+///     let object = shape.objects.as_ref().unwrap();
+///     assert_eq!(object.presence("score"), 0.5);
+///     assert_eq!(
+///         shape.to_json_schema(),
+///         json!({
+///             "$schema": "https://json-schema.org/draft/2020-12/schema",
+///             "type": "object",
+///             "properties": {
+///                 "id": {"type": "integer", "minimum": 1, "maximum": 2},
+///                 "name": {"type": ["null", "string"]},
+///                 "score": {"type": "number", "minimum": 0.5, "maximum": 0.5},
+///                 "tags": {
+///                     "type": "array",
+///                     "items": {"type": "string"},
+///                     "minItems": 0,
+///                     "maxItems": 2,
+///                 },
+///             },
+///             "required": ["id", "name", "tags"],
+///         })
+///     );
+///     assert_eq!(
+///         shape.to_rust("Record"),
+///         concat!(
+///             "use serde::{Deserialize, Serialize};\n",
+///             "\n",
+///             "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+///             "pub struct Record {\n",
+///             "    pub id: i64,\n",
+///             "    pub name: Option<String>,\n",
+///             "    #[serde(skip_serializing_if = \"Option::is_none\")]\n",
+///             "    pub score: Option<f64>,\n",
+///             "    pub tags: Vec<String>,\n",
+///             "}\n",
+///         )
+///     );
+///     Ok(())
+/// }
+/// ```
+pub fn infer_schema<R: BufRead>(reader: R, sample_limit: Option<usize>) -> Result<Shape> {
+    let mut reader = JsonLinesReader::new(reader);
+    let mut shape = Shape::default();
+    let mut sampled = 0;
+    while sample_limit.is_none_or(|limit| sampled < limit) {
+        match reader.read::<Value>()? {
+            Some(value) => shape.observe(&value),
+            None => break,
+        }
+        sampled += 1;
+    }
+    Ok(shape)
+}
+
+/// Read up to `sample_limit` values (or all values, if `sample_limit` is
+/// `None`) from the JSON Lines file at the given path and merge their shapes
+/// into a single [`Shape`].
+///
+/// See [`infer_schema()`] for more information.
+///
+/// # Errors
+///
+/// Has the same error conditions as [`File::open()`] and [`infer_schema()`].
+pub fn infer_schema_file<P: AsRef<Path>>(path: P, sample_limit: Option<usize>) -> Result<Shape> {
+    infer_schema(BufReader::new(File::open(path)?), sample_limit)
+}
+
+/// A summary of the JSON values observed at a single location (the top level
+/// of each line, an object field, or the elements of arrays) in a sample of
+/// JSON Lines input.
+///
+/// Shapes are normally produced by [`infer_schema()`], but they can also be
+/// built up manually by calling [`observe()`][Shape::observe] on each value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Shape {
+    /// The total number of values observed
+    pub count: u64,
+    /// The number of `null` values observed
+    pub nulls: u64,
+    /// The number of booleans observed
+    pub booleans: u64,
+    /// Statistics on the integers observed, if any
+    pub integers: Option<NumberStats<i128>>,
+    /// Statistics on all of the numbers observed (both integers and
+    /// non-integers), if any
+    pub numbers: Option<NumberStats<f64>>,
+    /// The number of strings observed
+    pub strings: u64,
+    /// A summary of the arrays observed, if any
+    pub arrays: Option<ArrayShape>,
+    /// A summary of the objects observed, if any
+    pub objects: Option<ObjectShape>,
+}
+
+impl Shape {
+    /// Merge a value into the shape
+    pub fn observe(&mut self, value: &Value) {
+        self.count += 1;
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::Bool(_) => self.booleans += 1,
+            Value::Number(n) => {
+                if let Some(i) = n
+                    .as_i64()
+                    .map(i128::from)
+                    .or_else(|| n.as_u64().map(i128::from))
+                {
+                    NumberStats::update(&mut self.integers, i);
+                }
+                if let Some(f) = n.as_f64() {
+                    NumberStats::update(&mut self.numbers, f);
+                }
+            }
+            Value::String(_) => self.strings += 1,
+            Value::Array(items) => {
+                let arrays = self.arrays.get_or_insert_with(|| ArrayShape {
+                    count: 0,
+                    min_len: items.len(),
+                    max_len: items.len(),
+                    items: Box::default(),
+                });
+                arrays.count += 1;
+                arrays.min_len = arrays.min_len.min(items.len());
+                arrays.max_len = arrays.max_len.max(items.len());
+                for item in items {
+                    arrays.items.observe(item);
+                }
+            }
+            Value::Object(map) => {
+                let objects = self.objects.get_or_insert_with(ObjectShape::default);
+                objects.count += 1;
+                for (key, value) in map {
+                    objects
+                        .fields
+                        .entry(key.clone())
+                        .or_default()
+                        .observe(value);
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if any `null` values were observed
+    pub fn is_nullable(&self) -> bool {
+        self.nulls > 0
+    }
+
+    /// Return the number of non-integer numbers observed
+    pub fn floats(&self) -> u64 {
+        let numbers = self.numbers.as_ref().map_or(0, |n| n.count);
+        let integers = self.integers.as_ref().map_or(0, |n| n.count);
+        numbers - integers
+    }
+
+    /// Return the names of the JSON Schema types of the values observed, in
+    /// the order `"null"`, `"boolean"`, `"integer"` or `"number"`,
+    /// `"string"`, `"array"`, `"object"`.
+    ///
+    /// Numbers are reported as `"integer"` if every number observed was an
+    /// integer and as `"number"` otherwise.
+    pub fn types(&self) -> Vec<&'static str> {
+        let mut types = Vec::new();
+        if self.nulls > 0 {
+            types.push("null");
+        }
+        if self.booleans > 0 {
+            types.push("boolean");
+        }
+        if self.floats() > 0 {
+            types.push("number");
+        } else if self.integers.is_some() {
+            types.push("integer");
+        }
+        if self.strings > 0 {
+            types.push("string");
+        }
+        if self.arrays.is_some() {
+            types.push("array");
+        }
+        if self.objects.is_some() {
+            types.push("object");
+        }
+        types
+    }
+
+    /// Render the shape as a [JSON Schema](https://json-schema.org) (draft
+    /// 2020-12) that accepts all of the values observed.
+    ///
+    /// Object fields that were present in every object observed are listed
+    /// as `"required"`, and number ranges and array lengths are given as
+    /// `"minimum"`/`"maximum"` and `"minItems"`/`"maxItems"`.
+    pub fn to_json_schema(&self) -> Value {
+        let mut schema = Map::new();
+        schema.insert("$schema".into(), JSON_SCHEMA_DIALECT.into());
+        schema.extend(self.schema());
+        Value::Object(schema)
+    }
+
+    fn schema(&self) -> Map<String, Value> {
+        let mut schema = Map::new();
+        let types = self.types();
+        match types.as_slice() {
+            [] => return schema,
+            [ty] => schema.insert("type".into(), (*ty).into()),
+            _ => schema.insert("type".into(), types.clone().into()),
+        };
+        if self.floats() > 0 {
+            if let Some(numbers) = &self.numbers {
+                schema.insert("minimum".into(), numbers.min.into());
+                schema.insert("maximum".into(), numbers.max.into());
+            }
+        } else if let Some(integers) = &self.integers {
+            schema.insert("minimum".into(), integer_value(integers.min));
+            schema.insert("maximum".into(), integer_value(integers.max));
+        }
+        if let Some(arrays) = &self.arrays {
+            if arrays.items.count > 0 {
+                schema.insert("items".into(), Value::Object(arrays.items.schema()));
+            }
+            schema.insert("minItems".into(), arrays.min_len.into());
+            schema.insert("maxItems".into(), arrays.max_len.into());
+        }
+        if let Some(objects) = &self.objects {
+            let properties = objects
+                .fields
+                .iter()
+                .map(|(name, field)| (name.clone(), Value::Object(field.schema())))
+                .collect::<Map<_, _>>();
+            schema.insert("properties".into(), Value::Object(properties));
+            let required = objects
+                .fields
+                .keys()
+                .filter(|name| objects.is_required(name))
+                .cloned()
+                .map(Value::from)
+                .collect::<Vec<_>>();
+            if !required.is_empty() {
+                schema.insert("required".into(), Value::Array(required));
+            }
+        }
+        schema
+    }
+
+    /// Render the shape as Rust source code for types that can deserialize
+    /// all of the values observed, using `serde`'s derive macros.
+    ///
+    /// If the values observed were all objects, the output defines a struct
+    /// named `name`; otherwise, it defines a type alias named `name`.  Nested
+    /// objects become further structs named after the fields containing them.
+    /// Fields whose names are not valid Rust identifiers in `snake_case` are
+    /// renamed with `#[serde(rename = "...")]`.  Fields that were `null` or
+    /// absent in some objects become `Option`s, and those that were absent
+    /// are additionally skipped when serializing `None`.  Locations at which
+    /// values of more than one JSON type were observed (other than `null`,
+    /// or integers mixed with non-integers) are typed as
+    /// [`serde_json::Value`].
+    ///
+    /// `name` is used as-is and should be a valid Rust identifier.
+    pub fn to_rust(&self, name: &str) -> String {
+        let mut generator = RustGenerator::default();
+        generator
+            .names
+            .extend(RESERVED_TYPE_NAMES.iter().map(|&s| s.to_owned()));
+        generator.names.insert(name.to_owned());
+        if let (Some(objects), ["object"]) = (&self.objects, self.types().as_slice()) {
+            generator.add_struct(name.to_owned(), objects);
+        } else {
+            let ty = generator.nullable_type(self, &format!("{name}Item"));
+            generator
+                .items
+                .insert(0, format!("pub type {name} = {ty};\n"));
+        }
+        let mut out = String::from("use serde::{Deserialize, Serialize};\n");
+        for item in generator.items {
+            out.push('\n');
+            out.push_str(&item);
+        }
+        out
+    }
+}
+
+/// Statistics on the numbers observed at a location, as recorded in a
+/// [`Shape`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NumberStats<T> {
+    /// The number of numbers observed
+    pub count: u64,
+    /// The smallest number observed
+    pub min: T,
+    /// The largest number observed
+    pub max: T,
+}
+
+impl<T: Copy + PartialOrd> NumberStats<T> {
+    fn update(stats: &mut Option<Self>, n: T) {
+        if let Some(stats) = stats {
+            stats.count += 1;
+            if n < stats.min {
+                stats.min = n;
+            }
+            if n > stats.max {
+                stats.max = n;
+            }
+        } else {
+            *stats = Some(NumberStats {
+                count: 1,
+                min: n,
+                max: n,
+            });
+        }
+    }
+}
+
+/// A summary of the arrays observed at a location, as recorded in a
+/// [`Shape`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrayShape {
+    /// The number of arrays observed
+    pub count: u64,
+    /// The length of the shortest array observed
+    pub min_len: usize,
+    /// The length of the longest array observed
+    pub max_len: usize,
+    /// The merged shape of the elements of all of the arrays observed
+    pub items: Box<Shape>,
+}
+
+/// A summary of the objects observed at a location, as recorded in a
+/// [`Shape`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjectShape {
+    /// The number of objects observed
+    pub count: u64,
+    /// The merged shapes of the values of each field, keyed by field name.
+    /// The `count` of each field's shape is the number of objects in which
+    /// the field was present.
+    pub fields: BTreeMap<String, Shape>,
+}
+
+impl ObjectShape {
+    /// Return the fraction of the objects observed in which the given field
+    /// was present (including when its value was `null`)
+    pub fn presence(&self, field: &str) -> f64 {
+        match self.fields.get(field) {
+            // Precision is only lost for counts above 2^53, which would not
+            // meaningfully change the ratio.
+            #[allow(clippy::cast_precision_loss)]
+            Some(shape) if self.count > 0 => shape.count as f64 / self.count as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns `true` if the given field was present in every object observed
+    pub fn is_required(&self, field: &str) -> bool {
+        self.fields
+            .get(field)
+            .is_some_and(|shape| shape.count == self.count)
+    }
+}
+
+fn integer_value(n: i128) -> Value {
+    // Integers are only ever recorded from `i64`s and `u64`s, so one of
+    // these conversions always succeeds.
+    i64::try_from(n)
+        .map(Value::from)
+        .or_else(|_| u64::try_from(n).map(Value::from))
+        .unwrap_or(Value::Null)
+}
+
+#[derive(Debug, Default)]
+struct RustGenerator {
+    /// Rendered struct definitions & type aliases, in output order
+    items: Vec<String>,
+    /// Type names that have already been used
+    names: HashSet<String>,
+}
+
+impl RustGenerator {
+    fn add_struct(&mut self, name: String, objects: &ObjectShape) {
+        // Reserve this struct's place before rendering any nested structs so
+        // that structs appear in the order in which they're referenced
+        let index = self.items.len();
+        self.items.push(String::new());
+        let mut body = String::new();
+        let mut idents = HashSet::new();
+        for (field, shape) in &objects.fields {
+            let required = objects.is_required(field);
+            let mut ty = self.nullable_type(shape, field);
+            if !required && !ty.starts_with("Option<") {
+                ty = format!("Option<{ty}>");
+            }
+            let mut ident = field_ident(field);
+            if !idents.insert(ident.clone()) {
+                let base = ident;
+                ident = (2..)
+                    .map(|i| format!("{base}_{i}"))
+                    .find(|s| !idents.contains(s))
+                    .unwrap_or_default();
+                idents.insert(ident.clone());
+            }
+            let mut attrs = Vec::new();
+            if ident.strip_prefix("r#").unwrap_or(&ident) != field {
+                attrs.push(format!("rename = {field:?}"));
+            }
+            if !required {
+                attrs.push(String::from("skip_serializing_if = \"Option::is_none\""));
+            }
+            if !attrs.is_empty() {
+                let _ = writeln!(body, "    #[serde({})]", attrs.join(", "));
+            }
+            let _ = writeln!(body, "    pub {ident}: {ty},");
+        }
+        let mut out = String::from("#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n");
+        if body.is_empty() {
+            let _ = writeln!(out, "pub struct {name} {{}}");
+        } else {
+            let _ = write!(out, "pub struct {name} {{\n{body}}}\n");
+        }
+        self.items[index] = out;
+    }
+
+    /// Return the Rust type for values of the given shape, wrapped in
+    /// `Option` if any values were `null`
+    fn nullable_type(&mut self, shape: &Shape, hint: &str) -> String {
+        let ty = self.base_type(shape, hint);
+        if shape.is_nullable() && ty != "serde_json::Value" {
+            format!("Option<{ty}>")
+        } else {
+            ty
+        }
+    }
+
+    /// Return the Rust type for the non-`null` values of the given shape
+    fn base_type(&mut self, shape: &Shape, hint: &str) -> String {
+        let types = shape.types();
+        let types = types.strip_prefix(&["null"]).unwrap_or(&types);
+        match types {
+            ["boolean"] => "bool".into(),
+            ["integer"] => match shape.integers {
+                Some(n) if n.max > i128::from(i64::MAX) && n.min < 0 => "i128".into(),
+                Some(n) if n.max > i128::from(i64::MAX) => "u64".into(),
+                _ => "i64".into(),
+            },
+            ["number"] => "f64".into(),
+            ["string"] => "String".into(),
+            ["array"] => {
+                if let Some(arrays) = shape.arrays.as_ref().filter(|a| a.items.count > 0) {
+                    let item = self.nullable_type(&arrays.items, &singular(hint));
+                    format!("Vec<{item}>")
+                } else {
+                    "Vec<serde_json::Value>".into()
+                }
+            }
+            ["object"] => {
+                if let Some(objects) = &shape.objects {
+                    let name = self.struct_name(hint);
+                    self.add_struct(name.clone(), objects);
+                    name
+                } else {
+                    "serde_json::Value".into()
+                }
+            }
+            _ => "serde_json::Value".into(),
+        }
+    }
+
+    fn struct_name(&mut self, hint: &str) -> String {
+        let base = pascal_case(hint);
+        let name = if self.names.contains(&base) {
+            (2..)
+                .map(|i| format!("{base}{i}"))
+                .find(|s| !self.names.contains(s))
+                .unwrap_or_default()
+        } else {
+            base
+        };
+        self.names.insert(name.clone());
+        name
+    }
+}
+
+/// Convert a field name to a `snake_case` Rust identifier
+fn field_ident(field: &str) -> String {
+    let chars = field.chars().collect::<Vec<_>>();
+    let mut ident = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_alphanumeric() {
+            if c.is_uppercase() {
+                let prev = i.checked_sub(1).and_then(|j| chars.get(j));
+                let next = chars.get(i + 1);
+                if prev.is_some_and(|p| p.is_lowercase() || p.is_numeric())
+                    || (prev.is_some_and(|p| p.is_uppercase())
+                        && next.is_some_and(|n| n.is_lowercase()))
+                {
+                    ident.push('_');
+                }
+                ident.extend(c.to_lowercase());
+            } else {
+                ident.push(c);
+            }
+        } else if !ident.ends_with('_') || ident.is_empty() {
+            ident.push('_');
+        }
+    }
+    if ident.is_empty() || ident == "_" {
+        return String::from("field");
+    }
+    if ident.starts_with(|c: char| c.is_numeric()) {
+        ident.insert(0, '_');
+    }
+    match ident.as_str() {
+        "crate" | "self" | "super" => ident.push('_'),
+        s if KEYWORDS.contains(&s) => ident.insert_str(0, "r#"),
+        _ => (),
+    }
+    ident
+}
+
+/// Convert a field name to a `PascalCase` type name
+fn pascal_case(s: &str) -> String {
+    let mut name = String::new();
+    for word in s.split(|c: char| !c.is_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.extend(first.to_uppercase());
+            name.extend(chars);
+        }
+    }
+    if name.is_empty() || name.starts_with(|c: char| c.is_numeric()) {
+        name.insert_str(0, "Field");
+    }
+    name
+}
+
+/// Guess the singular form of a (plural) field name for naming the type of
+/// its elements
+fn singular(s: &str) -> String {
+    if let Some(stem) = s.strip_suffix("ies")
+        && !stem.is_empty()
+    {
+        format!("{stem}y")
+    } else if let Some(stem) = s.strip_suffix('s')
+        && !stem.is_empty()
+        && !stem.ends_with('s')
+    {
+        stem.to_owned()
+    } else {
+        format!("{s}_item")
+    }
+}
+
+/// Names of types & traits referred to by the generated code, which
+/// generated structs must not shadow
+static RESERVED_TYPE_NAMES: &[&str] = &[
+    "Box",
+    "Clone",
+    "Debug",
+    "Deserialize",
+    "Option",
+    "PartialEq",
+    "Result",
+    "Self",
+    "Serialize",
+    "String",
+    "Vec",
+];
+
+static KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
@@ -0,0 +1,394 @@
+#![cfg_attr(docsrs, doc(cfg(feature = "jq")))]
+use crate::{JsonLinesReader, JsonLinesWriter};
+use jaq_core::load::{Arena, File, Loader, span};
+use jaq_core::{Compiler, Ctx, Native, RcIter};
+use jaq_json::Val;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Number, Value};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{BufRead, Error, ErrorKind, Result, Write};
+use std::marker::PhantomData;
+
+/// A compiled [jq](https://jqlang.github.io/jq/) filter that can be applied
+/// to JSON values.
+///
+/// Filters are compiled & executed with [jaq](https://github.com/01mf02/jaq),
+/// a jq clone, and have access to jaq's standard library.  Applying a filter
+/// to a value yields zero or more output values.
+///
+/// The `input` and `inputs` functions are not supported; each value is
+/// processed on its own.
+///
+/// # Example
+///
+/// ```
+/// use serde_json::json;
+/// use serde_jsonlines::{JqFilter, JsonLinesReader};
+///
+/// fn main() -> std::io::Result<()> {
+///     let input = concat!(
+///         "{\"user\": \"alice\", \"tags\": [\"a\", \"b\"]}\n",
+///         "{\"user\": \"bob\", \"tags\": []}\n",
+///         "{\"user\": \"carol\", \"tags\": [\"c\"]}\n",
+///     );
+///     let filter = JqFilter::compile(".tags[] as $t | {user, tag: $t}")?;
+///     let items = JsonLinesReader::new(input.as_bytes())
+///         .jq_all::<serde_json::Value>(filter)
+///         .collect::<std::io::Result<Vec<_>>>()?;
+///     assert_eq!(
+///         items,
+///         [
+///             json!({"user": "alice", "tag": "a"}),
+///             json!({"user": "alice", "tag": "b"}),
+///             json!({"user": "carol", "tag": "c"}),
+///         ]
+///     );
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct JqFilter {
+    code: String,
+    filter: jaq_core::Filter<Native<Val>>,
+}
+
+impl JqFilter {
+    /// Compile a jq filter from source code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if the
+    /// filter cannot be parsed or refers to undefined functions or variables.
+    /// The error message describes each problem along with its byte offset
+    /// in `code`.
+    pub fn compile(code: &str) -> Result<Self> {
+        let arena = Arena::default();
+        let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+        let modules = loader
+            .load(&arena, File { code, path: () })
+            .map_err(|errs| {
+                let mut msgs = Vec::new();
+                for (_, e) in errs {
+                    match e {
+                        jaq_core::load::Error::Io(es) => {
+                            msgs.extend(es.into_iter().map(|(path, msg)| {
+                                format!("could not load module {path:?}: {msg}")
+                            }));
+                        }
+                        jaq_core::load::Error::Lex(es) => {
+                            msgs.extend(es.into_iter().map(|(expect, found)| {
+                                format!(
+                                    "expected {} at offset {}",
+                                    expect.as_str(),
+                                    span(code, found).start
+                                )
+                            }));
+                        }
+                        jaq_core::load::Error::Parse(es) => {
+                            msgs.extend(es.into_iter().map(|(expect, found)| {
+                                format!(
+                                    "expected {} at offset {}",
+                                    expect.as_str(),
+                                    span(code, found).start
+                                )
+                            }));
+                        }
+                    }
+                }
+                compile_error(&msgs)
+            })?;
+        let filter = Compiler::default()
+            .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+            .compile(modules)
+            .map_err(|errs| {
+                let msgs = errs
+                    .into_iter()
+                    .flat_map(|(_, es)| es)
+                    .map(|(name, undef)| {
+                        format!(
+                            "undefined {} {name:?} at offset {}",
+                            undef.as_str(),
+                            span(code, name).start
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                compile_error(&msgs)
+            })?;
+        Ok(JqFilter {
+            code: code.to_owned(),
+            filter,
+        })
+    }
+
+    /// Return the source code of the filter
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Apply the filter to a value and return all of its outputs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if the
+    /// filter raises an error (e.g., by trying to index a number) or outputs
+    /// a value that cannot be represented as a [`serde_json::Value`].  In
+    /// such cases, any outputs produced before the error are discarded.
+    pub fn apply(&self, value: Value) -> Result<Vec<Value>> {
+        let inputs = RcIter::new(core::iter::empty());
+        self.filter
+            .run((Ctx::new([], &inputs), Val::from(value)))
+            .map(|r| match r {
+                Ok(v) => to_value(&v),
+                Err(e) => Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("jq filter failed: {e}"),
+                )),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for JqFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JqFilter")
+            .field("code", &self.code)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    /// Read a line of JSON from the underlying reader, apply `filter` to it,
+    /// and return the filter's outputs.
+    ///
+    /// If end-of-file is reached, this method returns `Ok(None)`.  Note that
+    /// a line for which the filter produces no outputs results in
+    /// `Ok(Some(vec![]))`.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`read()`][JsonLinesReader::read]
+    /// and [`JqFilter::apply()`].  In the latter case, continuing to read
+    /// from the `JsonLinesReader` afterwards will pick up on the next line.
+    pub fn read_jq(&mut self, filter: &JqFilter) -> Result<Option<Vec<Value>>> {
+        match self.read::<Value>()? {
+            Some(value) => filter.apply(value).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<R> JsonLinesReader<R> {
+    /// Consume the `JsonLinesReader` and return an iterator over the outputs
+    /// of `filter` applied to each line, deserialized as values of type `T`.
+    ///
+    /// The returned iterator has an `Item` type of `std::io::Result<T>`.  Each
+    /// line may produce zero or more items.  An error in reading or filtering
+    /// a line produces a single `Err` item, after which iteration continues
+    /// with the next line.  Errors also occur if an output cannot be
+    /// deserialized as `T`.
+    pub fn jq_all<T>(self, filter: JqFilter) -> JsonLinesJqIter<R, T> {
+        JsonLinesJqIter {
+            reader: self,
+            filter,
+            pending: VecDeque::new(),
+            _output: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the outputs of a [`JqFilter`] applied to each line of a
+/// [`BufRead`] value `R`, with each output deserialized as a value of type
+/// `T`.
+///
+/// Iterators of this type are returned by [`JsonLinesReader::jq_all()`].
+#[derive(Debug)]
+pub struct JsonLinesJqIter<R, T> {
+    reader: JsonLinesReader<R>,
+    filter: JqFilter,
+    pending: VecDeque<Value>,
+    _output: PhantomData<T>,
+}
+
+impl<R, T> JsonLinesJqIter<R, T> {
+    /// Consume the `JsonLinesJqIter` and return the underlying reader.
+    ///
+    /// Any outputs that have been computed but not yet yielded are lost.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+}
+
+impl<R, T> Iterator for JsonLinesJqIter<R, T>
+where
+    R: BufRead,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        loop {
+            if let Some(value) = self.pending.pop_front() {
+                return Some(serde_json::from_value(value).map_err(Into::into));
+            }
+            match self.reader.read_jq(&self.filter) {
+                Ok(Some(outputs)) => self.pending.extend(outputs),
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// A structure for writing the outputs of a [`JqFilter`] applied to values
+/// as JSON Lines.
+///
+/// Each value passed to [`write()`][JsonLinesJqWriter::write] is serialized,
+/// run through the filter, and each of the filter's outputs is then written
+/// as a line via a [`JsonLinesWriter`].
+///
+/// # Example
+///
+/// ```
+/// use serde::Serialize;
+/// use serde_jsonlines::{JqFilter, JsonLinesJqWriter};
+///
+/// #[derive(Serialize)]
+/// pub struct Order {
+///     pub id: u32,
+///     pub total: u32,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let filter = JqFilter::compile("select(.total > 10) | .id")?;
+///     let mut writer = JsonLinesJqWriter::new(Vec::new(), filter);
+///     writer.write(&Order { id: 1, total: 5 })?;
+///     writer.write(&Order { id: 2, total: 50 })?;
+///     assert_eq!(writer.into_inner(), b"2\n");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct JsonLinesJqWriter<W> {
+    inner: JsonLinesWriter<W>,
+    filter: JqFilter,
+}
+
+impl<W> JsonLinesJqWriter<W> {
+    /// Construct a new `JsonLinesJqWriter` that writes the outputs of
+    /// `filter` to `writer`
+    pub fn new(writer: W, filter: JqFilter) -> Self {
+        JsonLinesJqWriter {
+            inner: JsonLinesWriter::new(writer),
+            filter,
+        }
+    }
+
+    /// Consume the `JsonLinesJqWriter` and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.inner.into_inner()
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying writer
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.get_mut()
+    }
+}
+
+impl<W: Write> JsonLinesJqWriter<W> {
+    /// Apply the filter to a value and write each of the filter's outputs as
+    /// a line of JSON, returning the number of lines written.
+    ///
+    /// This method does not flush.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`JqFilter::apply()`] and
+    /// [`JsonLinesWriter::write()`].  If the filter fails, nothing is
+    /// written for `value`.
+    pub fn write<T>(&mut self, value: &T) -> Result<usize>
+    where
+        T: ?Sized + Serialize,
+    {
+        let outputs = self.filter.apply(serde_json::to_value(value)?)?;
+        self.inner.write_all(&outputs)?;
+        Ok(outputs.len())
+    }
+
+    /// Apply the filter to each item in an iterator and write each of the
+    /// filter's outputs as a line of JSON, returning the total number of
+    /// lines written.
+    ///
+    /// This method does not flush.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`write()`][JsonLinesJqWriter::write].
+    pub fn write_all<T, I>(&mut self, items: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = T>,
+        T: Serialize,
+    {
+        let mut written = 0;
+        for value in items {
+            written += self.write(&value)?;
+        }
+        Ok(written)
+    }
+
+    /// Flush the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::io::Write::flush()`].
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Convert a jaq value to a `serde_json::Value`
+fn to_value(v: &Val) -> Result<Value> {
+    Ok(match v {
+        Val::Null => Value::Null,
+        Val::Bool(b) => Value::Bool(*b),
+        Val::Int(i) => Value::Number(Number::from(*i)),
+        // jq outputs NaN as null
+        Val::Float(f) => Number::from_f64(*f).map_or(Value::Null, Value::Number),
+        Val::Num(n) => Value::Number(n.parse::<Number>().map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("jq filter output number out of range: {n}"),
+            )
+        })?),
+        Val::Str(s) => Value::String(String::clone(s)),
+        Val::Arr(a) => Value::Array(a.iter().map(to_value).collect::<Result<_>>()?),
+        Val::Obj(o) => Value::Object(
+            o.iter()
+                .map(|(k, v)| Ok((String::clone(k), to_value(v)?)))
+                .collect::<Result<Map<_, _>>>()?,
+        ),
+    })
+}
+
+fn compile_error(msgs: &[String]) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("invalid jq filter: {}", msgs.join("; ")),
+    )
+}
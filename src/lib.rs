@@ -22,6 +22,28 @@
 //! When the `async` feature is enabled, analogous types for working with JSON
 //! Lines asynchronously under [`tokio`] become available.
 //!
+//! When the `gzip` feature is enabled, [`RotatingJsonLinesWriter`] can
+//! compress files with gzip after rotating them, and [`JsonLinesMultiReader`]
+//! can read gzipped files.  When the `glob` feature is enabled,
+//! [`JsonLinesMultiReader`] can read from files matching a glob pattern.
+//! When the `jq` feature is enabled, [jq](https://jqlang.github.io/jq/)
+//! filters can be applied to the values read or written; see `JqFilter`.
+//! When the `derive` feature is enabled, [`JsonLinesRecord`] can be derived
+//! for enums of record kinds and for versioned structs.  When the
+//! `jsonschema` feature is enabled, values can be validated against a [JSON
+//! Schema](https://json-schema.org) as they are read or written; see
+//! `JsonSchema`.  When the `path-to-error` feature is enabled, readers can
+//! report the path to the field at which deserialization failed along with the
+//! line number; see `JsonLinesReader::field_paths()`.  When the `csv` feature
+//! is enabled, CSV can be converted to & from JSON Lines; see
+//! `CsvToJsonLines` and `JsonLinesToCsv`.  When the `miette` feature is
+//! enabled, a [`Diagnostic`] can be converted into a
+//! [`miette`](https://crates.io/crates/miette) report.  When the `arrow`
+//! feature is enabled, JSON Lines can be converted to & from
+//! [Arrow](https://arrow.apache.org) record batches and Parquet files; see
+//! `JsonLinesReader::read_batches()`, `JsonLinesWriter::write_batch()`,
+//! `json_lines_to_parquet()`, and `parquet_to_json_lines()`.
+//!
 //! Example
 //! =======
 //!
@@ -62,9 +84,13 @@
 //! }
 //! ```
 
-use serde::{Serialize, de::DeserializeOwned};
+use serde::{
+    Serialize,
+    de::{DeserializeOwned, DeserializeSeed},
+};
+use std::fmt;
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, BufWriter, Result, Write};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Result, Write};
 use std::marker::PhantomData;
 use std::path::Path;
 
@@ -72,6 +98,63 @@ use std::path::Path;
 mod asynclib;
 #[cfg(feature = "async")]
 pub use asynclib::*;
+#[cfg(feature = "arrow")]
+mod batch;
+#[cfg(feature = "arrow")]
+pub use batch::*;
+#[cfg(feature = "csv")]
+mod convert;
+#[cfg(feature = "csv")]
+pub use convert::*;
+mod de;
+pub use de::*;
+mod diagnostic;
+pub use diagnostic::*;
+mod dispatch;
+pub use dispatch::*;
+#[cfg(feature = "path-to-error")]
+mod fieldpath;
+#[cfg(feature = "path-to-error")]
+pub use fieldpath::*;
+mod filter;
+pub use filter::*;
+mod handle;
+pub use handle::*;
+mod infer;
+pub use infer::*;
+#[cfg(feature = "jq")]
+mod jq;
+#[cfg(feature = "jq")]
+pub use jq::*;
+mod migrate;
+pub use migrate::*;
+mod multi;
+pub use multi::*;
+mod partition;
+pub use partition::*;
+mod project;
+pub use project::*;
+mod record;
+pub use record::*;
+mod rotate;
+pub use rotate::*;
+#[cfg(feature = "jsonschema")]
+mod schema;
+#[cfg(feature = "jsonschema")]
+pub use schema::*;
+mod ser;
+pub use ser::*;
+mod sniff;
+mod split;
+pub use split::*;
+mod validate;
+pub use validate::*;
+
+/// Derive macro for [`JsonLinesRecord`]; see the trait's documentation for
+/// the supported attributes
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use serde_jsonlines_derive::JsonLinesRecord;
 
 /// A type alias for a [`JsonLinesIter`] on a buffered file object.
 ///
@@ -136,12 +219,23 @@ pub type JsonLinesFileIter<T> = JsonLinesIter<BufReader<File>, T>;
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct JsonLinesWriter<W> {
     inner: W,
+    terminator: LineTerminator,
 }
 
 impl<W> JsonLinesWriter<W> {
     /// Construct a new `JsonLinesWriter` from a [`std::io::Write`] instance
     pub fn new(writer: W) -> Self {
-        JsonLinesWriter { inner: writer }
+        JsonLinesWriter {
+            inner: writer,
+            terminator: LineTerminator::default(),
+        }
+    }
+
+    /// Set the line terminator written after each value.  The default is
+    /// [`LineTerminator::Lf`].
+    pub fn line_terminator(mut self, terminator: LineTerminator) -> Self {
+        self.terminator = terminator;
+        self
     }
 
     /// Consume the `JsonLinesWriter` and return the underlying writer
@@ -161,8 +255,29 @@ impl<W> JsonLinesWriter<W> {
 }
 
 impl<W: Write> JsonLinesWriter<W> {
+    /// Serialize a header value as a line of JSON and write it to the
+    /// underlying writer, returning the writer for writing the records that
+    /// follow the header.
+    ///
+    /// This is intended to be called immediately after constructing the
+    /// writer (and setting its [line
+    /// terminator][JsonLinesWriter::line_terminator], if desired), so that the
+    /// header is written exactly once, before any records.  The header can be
+    /// read back with [`JsonLinesReader::read_with_header()`].
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`write()`][JsonLinesWriter::write].
+    pub fn with_header<H>(mut self, header: &H) -> Result<Self>
+    where
+        H: ?Sized + Serialize,
+    {
+        self.write(header)?;
+        Ok(self)
+    }
+
     /// Serialize a value as a line of JSON and write it to the underlying
-    /// writer, followed by a newline.
+    /// writer, followed by the line terminator.
     ///
     /// Note that separate calls to this method may write different types of
     /// values.
@@ -176,12 +291,12 @@ impl<W: Write> JsonLinesWriter<W> {
         T: ?Sized + Serialize,
     {
         serde_json::to_writer(&mut self.inner, value)?;
-        self.inner.write_all(b"\n")?;
+        self.inner.write_all(self.terminator.as_bytes())?;
         Ok(())
     }
 
     /// Serialize each item in an iterator as a line of JSON, and write out
-    /// each one followed by a newline to the underlying writer.
+    /// each one followed by the line terminator to the underlying writer.
     ///
     /// All values in a single call to `write_all()` must be the same type, but
     /// separate calls may write different types.
@@ -274,12 +389,93 @@ impl<W: Write> JsonLinesWriter<W> {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct JsonLinesReader<R> {
     inner: R,
+    // Reused between reads in order to avoid reallocating; always empty
+    // between method calls
+    buf: String,
+    lines: LineHandling,
 }
 
 impl<R> JsonLinesReader<R> {
     /// Construct a new `JsonLinesReader` from a [`std::io::BufRead`] instance
     pub fn new(reader: R) -> Self {
-        JsonLinesReader { inner: reader }
+        JsonLinesReader {
+            inner: reader,
+            buf: String::new(),
+            lines: LineHandling::default(),
+        }
+    }
+
+    /// Set whether to strip a UTF-8 byte order mark from the start of the
+    /// first line.
+    ///
+    /// By default, byte order marks are not stripped, and so a first line
+    /// that begins with one fails to parse as JSON.
+    pub fn strip_bom(mut self, strip: bool) -> Self {
+        self.lines.strip_bom = strip;
+        self
+    }
+
+    /// Set whether to reject lines that contain carriage returns.
+    ///
+    /// By default, lines terminated by `\r\n` are read without complaint, as
+    /// JSON treats the carriage return as whitespace.  When strict line
+    /// endings are enabled, reading a line that ends in `\r\n` or that
+    /// contains a lone `\r` results in an error of kind
+    /// [`std::io::ErrorKind::InvalidData`].
+    pub fn strict_line_endings(mut self, strict: bool) -> Self {
+        self.lines.strict = strict;
+        self
+    }
+
+    /// Set whether to report the path to the field at which deserialization
+    /// failed when a line cannot be deserialized by
+    /// [`read()`][JsonLinesReader::read],
+    /// [`read_into()`][JsonLinesReader::read_into],
+    /// [`read_seed()`][JsonLinesReader::read_seed],
+    /// [`read_filtered()`][JsonLinesReader::read_filtered],
+    /// [`read_projected()`][JsonLinesReader::read_projected],
+    /// [`read_dispatched()`][JsonLinesReader::read_dispatched],
+    /// [`read_record()`][JsonLinesReader::read_record] (for derived
+    /// implementations of [`JsonLinesRecord`]),
+    /// [`read_migrated()`][JsonLinesReader::read_migrated], or
+    /// `read_validated()` (or the iterators built on them).
+    ///
+    /// When enabled, such errors have a [`FieldPathError`] as their payload
+    /// instead of a [`serde_json::Error`], giving the path (e.g.,
+    /// `orders[3].items[0].qty`) along with the number of the line, counting
+    /// from where the reader started.  Tracking the path makes
+    /// deserialization slightly slower, and so it is disabled by default.
+    #[cfg(feature = "path-to-error")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "path-to-error")))]
+    pub fn field_paths(mut self, enabled: bool) -> Self {
+        self.lines.field_paths = enabled;
+        self
+    }
+
+    /// Set whether to report errors that occur while reading a line with
+    /// [`read()`][JsonLinesReader::read],
+    /// [`read_into()`][JsonLinesReader::read_into],
+    /// [`read_seed()`][JsonLinesReader::read_seed],
+    /// [`read_filtered()`][JsonLinesReader::read_filtered],
+    /// [`read_projected()`][JsonLinesReader::read_projected],
+    /// [`read_dispatched()`][JsonLinesReader::read_dispatched],
+    /// [`read_record()`][JsonLinesReader::read_record],
+    /// [`read_migrated()`][JsonLinesReader::read_migrated], or
+    /// `read_validated()` (or the iterators built on them) as
+    /// [`Diagnostic`]s.  Errors from reading batches, JSON Schema violations,
+    /// and errors from the underlying reader are not affected.
+    ///
+    /// When enabled, decoding errors (including, for example, unknown
+    /// discriminators and failed migrations) and (if
+    /// [`strict_line_endings()`][JsonLinesReader::strict_line_endings] is
+    /// enabled) line ending errors have a [`Diagnostic`] as their payload,
+    /// which records the text of the offending line and the position in it at
+    /// which the error occurred, and which displays the line with a caret
+    /// under that position.  This requires keeping a copy of the line, and so
+    /// it is disabled by default.
+    pub fn diagnostics(mut self, enabled: bool) -> Self {
+        self.lines.diagnostics = enabled;
+        self
     }
 
     /// Consume the `JsonLinesReader` and return the underlying reader
@@ -313,9 +509,45 @@ impl<R> JsonLinesReader<R> {
             _output: PhantomData,
         }
     }
+
+    /// Consume the `JsonLinesReader` and return an iterator that deserializes
+    /// each line using a fresh [`serde::de::DeserializeSeed`] value obtained
+    /// by calling `factory`.
+    ///
+    /// The returned iterator has an `Item` type of `std::io::Result<V>`,
+    /// where `V` is the seed's output type.  Each call to `next()` has the
+    /// same error conditions as [`read_seed()`][JsonLinesReader::read_seed].
+    pub fn read_all_seeded<F>(self, factory: F) -> JsonLinesSeedIter<R, F> {
+        JsonLinesSeedIter {
+            reader: self,
+            factory,
+        }
+    }
 }
 
 impl<R: BufRead> JsonLinesReader<R> {
+    /// Read & deserialize the first line of JSON from the underlying reader
+    /// as a header of type `H`, and return it along with an iterator over
+    /// the deserialized values of type `T` on the remaining lines.
+    ///
+    /// This is useful for formats in which a metadata record on the first
+    /// line is followed by homogeneous data records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::UnexpectedEof`] if the
+    /// input is empty.  Otherwise, has the same error conditions as
+    /// [`read()`][JsonLinesReader::read].
+    pub fn read_with_header<H, T>(mut self) -> Result<(H, JsonLinesIter<R, T>)>
+    where
+        H: DeserializeOwned,
+    {
+        match self.read::<H>()? {
+            Some(header) => Ok((header, self.read_all())),
+            None => Err(Error::new(ErrorKind::UnexpectedEof, "missing header line")),
+        }
+    }
+
     /// Read & deserialize a line of JSON from the underlying reader.
     ///
     /// If end-of-file is reached, this method returns `Ok(None)`.
@@ -335,13 +567,83 @@ impl<R: BufRead> JsonLinesReader<R> {
     where
         T: DeserializeOwned,
     {
-        let mut s = String::new();
-        let r = self.inner.read_line(&mut s)?;
-        if r == 0 {
-            Ok(None)
-        } else {
-            Ok(Some(serde_json::from_str::<T>(&s)?))
+        if !self.read_line()? {
+            return Ok(None);
         }
+        let r = self.lines.decode_seed(&self.buf, PhantomData::<T>);
+        self.buf.clear();
+        r.map(Some)
+    }
+
+    /// Read a line of JSON from the underlying reader and deserialize it into
+    /// an existing value, reusing the value's allocations where possible.
+    ///
+    /// This uses [`serde::Deserialize::deserialize_in_place()`], which allows
+    /// (for example) a `String` or `Vec` to keep its capacity from one line
+    /// to the next.  Note that structs & enums that derive `Deserialize` only
+    /// deserialize in place if serde's `deserialize_in_place` feature is
+    /// enabled; otherwise, `place` is simply overwritten with a newly
+    /// deserialized value.
+    ///
+    /// Returns `Ok(true)` if a line was read into `place`, or `Ok(false)` if
+    /// end-of-file was reached, in which case `place` is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`read()`][JsonLinesReader::read].
+    /// If deserialization fails, `place` may have been partially updated.
+    pub fn read_into<T>(&mut self, place: &mut T) -> Result<bool>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.read_line()? {
+            return Ok(false);
+        }
+        let r = self.lines.decode_in_place(&self.buf, place);
+        self.buf.clear();
+        r?;
+        Ok(true)
+    }
+
+    /// Read a line of JSON from the underlying reader and deserialize it
+    /// using the given [`serde::de::DeserializeSeed`] value.
+    ///
+    /// This allows lines to be decoded with the help of external state, such
+    /// as a string interner or a schema that is only known at runtime.  As
+    /// the line is discarded once it has been deserialized, the seed must be
+    /// able to deserialize from input of any lifetime, and its output cannot
+    /// borrow from the input.
+    ///
+    /// If end-of-file is reached, this method returns `Ok(None)` without
+    /// using the seed.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`read()`][JsonLinesReader::read].
+    pub fn read_seed<S, V>(&mut self, seed: S) -> Result<Option<V>>
+    where
+        S: for<'de> DeserializeSeed<'de, Value = V>,
+    {
+        if !self.read_line()? {
+            return Ok(None);
+        }
+        let r = self.lines.decode_seed(&self.buf, seed);
+        self.buf.clear();
+        r.map(Some)
+    }
+
+    /// Read a line from the underlying reader into `self.buf`, returning
+    /// `false` at end of file
+    fn read_line(&mut self) -> Result<bool> {
+        self.buf.clear();
+        if self.inner.read_line(&mut self.buf)? == 0 {
+            return Ok(false);
+        }
+        if let Err(e) = self.lines.process(&mut self.buf) {
+            self.buf.clear();
+            return Err(e);
+        }
+        Ok(true)
     }
 }
 
@@ -396,6 +698,231 @@ where
     }
 }
 
+/// An iterator over the lines of a [`BufRead`] value `R` that decodes each
+/// line as JSON using a [`serde::de::DeserializeSeed`] value produced by the
+/// seed factory `F`.
+///
+/// This iterator yields items of type `Result<V, std::io::Error>`, where `V`
+/// is the seed's output type.  Errors occur under the same conditions as for
+/// [`JsonLinesReader::read_seed()`].
+///
+/// Iterators of this type are returned by
+/// [`JsonLinesReader::read_all_seeded()`].
+#[derive(Clone)]
+pub struct JsonLinesSeedIter<R, F> {
+    reader: JsonLinesReader<R>,
+    factory: F,
+}
+
+impl<R: fmt::Debug, F> fmt::Debug for JsonLinesSeedIter<R, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLinesSeedIter")
+            .field("reader", &self.reader)
+            .field("factory", &"<function>")
+            .finish()
+    }
+}
+
+impl<R, F> JsonLinesSeedIter<R, F> {
+    /// Consume the `JsonLinesSeedIter` and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+}
+
+impl<R, F, S, V> Iterator for JsonLinesSeedIter<R, F>
+where
+    R: BufRead,
+    F: FnMut() -> S,
+    S: for<'de> DeserializeSeed<'de, Value = V>,
+{
+    type Item = Result<V>;
+
+    fn next(&mut self) -> Option<Result<V>> {
+        let seed = (self.factory)();
+        self.reader.read_seed(seed).transpose()
+    }
+}
+
+/// The line terminator written by [`JsonLinesWriter`] and
+/// `AsyncJsonLinesWriter`
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LineTerminator {
+    /// A line feed (`\n`), as called for by the JSON Lines format
+    #[default]
+    Lf,
+    /// A carriage return followed by a line feed (`\r\n`), as used on
+    /// Windows
+    CrLf,
+}
+
+impl LineTerminator {
+    /// Return the line terminator as a string
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineTerminator::Lf => "\n",
+            LineTerminator::CrLf => "\r\n",
+        }
+    }
+
+    /// Return the line terminator as a byte string
+    pub fn as_bytes(self) -> &'static [u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+/// How a reader treats byte order marks, carriage returns, and
+/// deserialization errors, along with the state needed to recognize the first
+/// line and to number lines
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct LineHandling {
+    pub(crate) strip_bom: bool,
+    pub(crate) strict: bool,
+    #[cfg(feature = "path-to-error")]
+    pub(crate) field_paths: bool,
+    pub(crate) diagnostics: bool,
+    pub(crate) at_start: bool,
+    /// The (1-based) number of the line most recently read, counting from
+    /// where the reader started
+    pub(crate) lineno: u64,
+}
+
+impl LineHandling {
+    /// Apply the reader's options to a line (including its terminator) that
+    /// was just read
+    pub(crate) fn process(&mut self, line: &mut String) -> Result<()> {
+        self.lineno += 1;
+        if std::mem::replace(&mut self.at_start, false)
+            && self.strip_bom
+            && line.starts_with('\u{FEFF}')
+        {
+            line.drain(..'\u{FEFF}'.len_utf8());
+        }
+        if self.strict
+            && let Some(i) = line.find('\r')
+        {
+            let msg = if line[i..] == *"\r\n" {
+                "line terminated by CR LF"
+            } else {
+                "carriage return in line"
+            };
+            if self.diagnostics {
+                let mut diag = Diagnostic::from_error(
+                    &Error::new(ErrorKind::InvalidData, msg),
+                    line,
+                    self.lineno,
+                );
+                diag.column = Some(i + 1);
+                return Err(Error::new(ErrorKind::InvalidData, diag));
+            }
+            return Err(Error::new(ErrorKind::InvalidData, msg));
+        }
+        Ok(())
+    }
+
+    /// Deserialize the line most recently read using a seed
+    pub(crate) fn decode_seed<'de, S>(&self, s: &'de str, seed: S) -> Result<S::Value>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        #[cfg(feature = "path-to-error")]
+        if self.field_paths {
+            return fieldpath::from_str_seed(s, seed, self.lineno)
+                .map_err(|e| self.diagnose(e.into(), s));
+        }
+        from_str_seed(s, seed).map_err(|e| self.diagnose(e.into(), s))
+    }
+
+    /// Deserialize the line most recently read into an existing value
+    pub(crate) fn decode_in_place<T>(&self, s: &str, place: &mut T) -> Result<()>
+    where
+        T: DeserializeOwned,
+    {
+        #[cfg(feature = "path-to-error")]
+        if self.field_paths {
+            return fieldpath::from_str_in_place(s, place, self.lineno)
+                .map_err(|e| self.diagnose(e.into(), s));
+        }
+        from_str_in_place(s, place).map_err(|e| self.diagnose(e.into(), s))
+    }
+
+    /// Deserialize a value from a deserializer built from the line most
+    /// recently read, such as a [`serde_json::Value`] parsed from it or a
+    /// projection of its fields
+    pub(crate) fn decode_from<'de, D, T>(&self, s: &str, de: D) -> Result<T>
+    where
+        D: serde::Deserializer<'de, Error = serde_json::Error>,
+        T: serde::Deserialize<'de>,
+    {
+        #[cfg(feature = "path-to-error")]
+        if self.field_paths {
+            return from_deserializer(de, self.lineno).map_err(|e| self.diagnose(e.into(), s));
+        }
+        T::deserialize(de).map_err(|e| self.diagnose(e.into(), s))
+    }
+
+    /// If diagnostics are enabled, wrap an error that occurred while
+    /// decoding the line `s` in a [`Diagnostic`] (unless it already is one)
+    pub(crate) fn diagnose(&self, e: Error, s: &str) -> Error {
+        if self.diagnostics
+            && !e
+                .get_ref()
+                .is_some_and(<dyn std::error::Error + Send + Sync>::is::<Diagnostic>)
+        {
+            Error::new(e.kind(), Diagnostic::from_error(&e, s, self.lineno))
+        } else {
+            e
+        }
+    }
+}
+
+impl Default for LineHandling {
+    fn default() -> Self {
+        LineHandling {
+            strip_bom: false,
+            strict: false,
+            #[cfg(feature = "path-to-error")]
+            field_paths: false,
+            diagnostics: false,
+            at_start: true,
+            lineno: 0,
+        }
+    }
+}
+
+/// Deserialize a single line of JSON using a seed, rejecting any trailing
+/// non-whitespace
+pub(crate) fn from_str_seed<'de, S>(s: &'de str, seed: S) -> serde_json::Result<S::Value>
+where
+    S: DeserializeSeed<'de>,
+{
+    let mut de = serde_json::Deserializer::from_str(s);
+    let value = seed.deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserialize a single line of JSON into an existing value, rejecting any
+/// trailing non-whitespace
+pub(crate) fn from_str_in_place<T>(s: &str, place: &mut T) -> serde_json::Result<()>
+where
+    T: DeserializeOwned,
+{
+    let mut de = serde_json::Deserializer::from_str(s);
+    T::deserialize_in_place(&mut de, place)?;
+    de.end()
+}
+
 /// An extension trait for the [`std::io::Write`] trait that adds a
 /// `write_json_lines()` method
 ///
@@ -701,6 +1228,77 @@ where
     fp.flush()
 }
 
+/// Append an iterator of values to the file at `path` as JSON Lines while
+/// holding an exclusive lock on the file.
+///
+/// This function is intended for situations in which multiple processes
+/// append to the same file concurrently.  Unlike with
+/// [`append_json_lines()`], whose buffered writer may split a single line
+/// across several writes, the lines written by a call to this function will
+/// never be interleaved with lines written by other callers of this function.
+///
+/// If the file does not already exist, it is created.  If it does exist, the
+/// new lines are added after any lines that are already present.
+///
+/// # Locking
+///
+/// All of the values are first serialized into a single in-memory buffer.
+/// The file is then opened in append mode, an exclusive lock is acquired on
+/// it with [`File::lock()`] (blocking until any other lock is released), the
+/// buffer is written out, and the lock is released when the file is closed.
+/// On Unix, the lock is an advisory `flock(2)` lock, and so it only excludes
+/// other processes that also lock the file, such as other callers of this
+/// function; processes that append to the file without locking it can still
+/// interleave their output with that of this function.  On Windows, the lock
+/// is mandatory.
+///
+/// If serialization of any value fails, nothing is written to the file.
+///
+/// # Errors
+///
+/// Has the same error conditions as [`OpenOptions::open()`],
+/// [`serde_json::to_writer()`], [`File::lock()`], and
+/// [`std::io::Write::write_all()`].
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Serialize;
+/// use serde_jsonlines::append_json_lines_locked;
+///
+/// #[derive(Serialize)]
+/// pub struct Event {
+///     pub worker: u32,
+///     pub message: String,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     append_json_lines_locked(
+///         "events.jsonl",
+///         [Event {
+///             worker: std::process::id(),
+///             message: "Started".into(),
+///         }],
+///     )
+/// }
+/// ```
+pub fn append_json_lines_locked<P, I, T>(path: P, items: I) -> Result<()>
+where
+    P: AsRef<Path>,
+    I: IntoIterator<Item = T>,
+    T: Serialize,
+{
+    let mut buf = Vec::new();
+    buf.write_json_lines(items)?;
+    let mut fp = OpenOptions::new().append(true).create(true).open(path)?;
+    if buf.is_empty() {
+        return Ok(());
+    }
+    fp.lock()?;
+    fp.write_all(&buf)?;
+    fp.unlock()
+}
+
 /// Iterate over JSON Lines values from a file.
 ///
 /// `json_lines(path)` returns an iterator of values deserialized from the JSON
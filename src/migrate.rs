@@ -0,0 +1,523 @@
+use crate::project::{array_index, parse_pointer};
+use crate::{JsonLinesReader, LineHandling, Projection, sniff};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{File, OpenOptions, metadata, remove_file, rename, set_permissions};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Result, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+type Step = Arc<dyn Fn(Value) -> Result<Value> + Send + Sync>;
+
+/// A chain of upgrade steps for bringing records written under older
+/// versions of a schema up to the latest version.
+///
+/// Each record's schema version is read from the nonnegative integer at a
+/// [JSON Pointer](https://www.rfc-editor.org/rfc/rfc6901) (e.g.,
+/// `"/schema_version"`); records without a value there can optionally be
+/// treated as having a default version.  Each step upgrades a record from
+/// one version `N` to version `N + 1`, after which the version field is set
+/// to `N + 1` automatically, and the latest version is one more than that of
+/// the last step.  Steps may operate on [`serde_json::Value`]s (see
+/// [`step()`][Migrations::step]) or on types implementing serde's traits (see
+/// [`typed_step()`][Migrations::typed_step]).
+///
+/// Migrations are used with [`JsonLinesReader::read_migrated()`],
+/// [`JsonLinesReader::migrate_all()`], and [`migrate_file()`].
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_json::{json, Value};
+/// use serde_jsonlines::{JsonLinesReader, Migrations};
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// pub struct Entry {
+///     pub schema_version: u64,
+///     pub actor: String,
+///     pub action: String,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let migrations = Migrations::new("/schema_version")?
+///         .default_version(1)
+///         // v1 -> v2: "user" was renamed to "actor"
+///         .step(1, |mut v: Value| {
+///             if let Some(obj) = v.as_object_mut() {
+///                 if let Some(user) = obj.remove("user") {
+///                     obj.insert("actor".into(), user);
+///                 }
+///             }
+///             Ok(v)
+///         })
+///         // v2 -> v3: "action" was added
+///         .step(2, |mut v: Value| {
+///             v["action"] = json!("unknown");
+///             Ok(v)
+///         });
+///     assert_eq!(migrations.latest_version(), 3);
+///     let input = concat!(
+///         "{\"user\": \"alice\"}\n",
+///         "{\"schema_version\": 3, \"actor\": \"bob\", \"action\": \"login\"}\n",
+///     );
+///     let entries = JsonLinesReader::new(input.as_bytes())
+///         .migrate_all::<Entry>(migrations)
+///         .collect::<std::io::Result<Vec<_>>>()?;
+///     assert_eq!(
+///         entries,
+///         [
+///             Entry {
+///                 schema_version: 3,
+///                 actor: "alice".into(),
+///                 action: "unknown".into(),
+///             },
+///             Entry {
+///                 schema_version: 3,
+///                 actor: "bob".into(),
+///                 action: "login".into(),
+///             },
+///         ]
+///     );
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Migrations {
+    pointer: String,
+    tokens: Vec<String>,
+    projection: Projection,
+    default_version: Option<u64>,
+    steps: BTreeMap<u64, Step>,
+}
+
+impl Migrations {
+    /// Construct a new `Migrations` with no steps that reads each record's
+    /// schema version from the JSON Pointer `pointer`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if
+    /// `pointer` is not a valid JSON Pointer or is the empty pointer.
+    pub fn new<S: Into<String>>(pointer: S) -> Result<Self> {
+        let pointer = pointer.into();
+        let tokens = parse_pointer(&pointer)?;
+        if tokens.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "schema version pointer must not be empty",
+            ));
+        }
+        let projection = Projection::new([pointer.as_str()])?;
+        Ok(Migrations {
+            pointer,
+            tokens,
+            projection,
+            default_version: None,
+            steps: BTreeMap::new(),
+        })
+    }
+
+    /// Treat records that have no value at the version pointer as being at
+    /// version `version`
+    pub fn default_version(mut self, version: u64) -> Self {
+        self.default_version = Some(version);
+        self
+    }
+
+    /// Add a step that upgrades records from version `from` to version
+    /// `from + 1`.
+    ///
+    /// The version field of the step's output is set to `from + 1`
+    /// afterwards.  If a step was already registered for `from`, it is
+    /// replaced.
+    pub fn step<F>(mut self, from: u64, f: F) -> Self
+    where
+        F: Fn(Value) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.steps.insert(from, Arc::new(f));
+        self
+    }
+
+    /// Add a step that upgrades records from version `from` to version
+    /// `from + 1` by deserializing them as `A`, passing them to `f`, and
+    /// serializing the result.
+    ///
+    /// The version field of the step's output is set to `from + 1`
+    /// afterwards.  If a step was already registered for `from`, it is
+    /// replaced.
+    pub fn typed_step<A, B, F>(self, from: u64, f: F) -> Self
+    where
+        A: DeserializeOwned,
+        B: Serialize,
+        F: Fn(A) -> B + Send + Sync + 'static,
+    {
+        self.step(from, move |value| {
+            let old = serde_json::from_value::<A>(value)?;
+            Ok(serde_json::to_value(f(old))?)
+        })
+    }
+
+    /// Return the JSON Pointer at which schema versions are read
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// Return the latest schema version, i.e., the version that records are
+    /// migrated to.
+    ///
+    /// This is one more than the version of the last step, or the default
+    /// version (or zero) if there are no steps.
+    pub fn latest_version(&self) -> u64 {
+        self.steps
+            .last_key_value()
+            .map_or_else(|| self.default_version.unwrap_or(0), |(&v, _)| v + 1)
+    }
+
+    /// Determine the schema version of a line of JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if the
+    /// line is not valid JSON, if the value at the version pointer is not a
+    /// nonnegative integer, or if there is no such value and no default
+    /// version.
+    pub fn version_of(&self, line: &str) -> Result<u64> {
+        sniff::version(&self.projection, line, self.default_version)
+    }
+
+    /// Upgrade a record at version `from` to the latest version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if `from`
+    /// is newer than the latest version, if there is no step for some
+    /// version between `from` and the latest version, or if the version
+    /// field cannot be set on the output of a step.  Also fails if any of
+    /// the steps fail.
+    pub fn migrate(&self, mut value: Value, from: u64) -> Result<Value> {
+        let latest = self.latest_version();
+        if from > latest {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("schema version {from} is newer than latest version {latest}"),
+            ));
+        }
+        for v in from..latest {
+            let Some(step) = self.steps.get(&v) else {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("no migration from schema version {v}"),
+                ));
+            };
+            value = step(value)?;
+            if !set_at(&mut value, &self.tokens, Value::from(v + 1)) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("cannot set schema version at {}", self.pointer),
+                ));
+            }
+        }
+        Ok(value)
+    }
+
+    /// Decode a line of JSON as a value of type `T`, first migrating it to
+    /// the latest version if necessary.
+    ///
+    /// Lines that are already at the latest version are deserialized
+    /// directly, without building an intermediate [`serde_json::Value`].
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`version_of()`][Migrations::version_of] and
+    /// [`migrate()`][Migrations::migrate], and also fails if the migrated
+    /// record cannot be deserialized as `T`.
+    pub fn decode<T: DeserializeOwned>(&self, line: &str) -> Result<T> {
+        self.decode_with(line, &LineHandling::default())
+    }
+
+    /// Decode a line of JSON as with [`decode()`][Migrations::decode], using
+    /// the field path & diagnostic settings of the reader it came from
+    pub(crate) fn decode_with<T: DeserializeOwned>(
+        &self,
+        line: &str,
+        lines: &LineHandling,
+    ) -> Result<T> {
+        let from = self.version_of(line).map_err(|e| lines.diagnose(e, line))?;
+        if from == self.latest_version() {
+            lines.decode_seed(line, PhantomData::<T>)
+        } else {
+            let value = lines.decode_seed(line, PhantomData::<Value>)?;
+            let value = self
+                .migrate(value, from)
+                .map_err(|e| lines.diagnose(e, line))?;
+            lines.decode_from(line, value)
+        }
+    }
+}
+
+impl fmt::Debug for Migrations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migrations")
+            .field("pointer", &self.pointer)
+            .field("default_version", &self.default_version)
+            .field("steps", &self.steps.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Set the value at the location given by a parsed JSON Pointer, creating
+/// the final object member if necessary.  Returns `false` if the location's
+/// parent does not exist.
+fn set_at(value: &mut Value, tokens: &[String], new: Value) -> bool {
+    let Some((last, parents)) = tokens.split_last() else {
+        *value = new;
+        return true;
+    };
+    let mut current = value;
+    for token in parents {
+        let next = match current {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(array) => array_index(token).and_then(|i| array.get_mut(i)),
+            _ => None,
+        };
+        let Some(next) = next else {
+            return false;
+        };
+        current = next;
+    }
+    match current {
+        Value::Object(map) => {
+            map.insert(last.clone(), new);
+            true
+        }
+        Value::Array(array) => {
+            if let Some(slot) = array_index(last).and_then(|i| array.get_mut(i)) {
+                *slot = new;
+                true
+            } else {
+                false
+            }
+        }
+        _ => false,
+    }
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    /// Read a line of JSON from the underlying reader, migrate it to the
+    /// latest schema version with `migrations`, and deserialize it as a value
+    /// of type `T`.
+    ///
+    /// If end-of-file is reached, this method returns `Ok(None)`.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::io::BufRead::read_line()`] and
+    /// [`Migrations::decode()`].  As with [`read()`][JsonLinesReader::read],
+    /// reading can continue with the next line after a decoding error.
+    pub fn read_migrated<T>(&mut self, migrations: &Migrations) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.read_line()? {
+            return Ok(None);
+        }
+        let r = migrations.decode_with(&self.buf, &self.lines);
+        self.buf.clear();
+        r.map(Some)
+    }
+}
+
+impl<R> JsonLinesReader<R> {
+    /// Consume the `JsonLinesReader` and return an iterator over the values
+    /// of type `T` deserialized from each line after migrating it to the
+    /// latest schema version with `migrations`.
+    ///
+    /// The returned iterator has an `Item` type of `std::io::Result<T>`.  Each
+    /// call to `next()` has the same error conditions as
+    /// [`read_migrated()`][JsonLinesReader::read_migrated].
+    pub fn migrate_all<T>(self, migrations: Migrations) -> JsonLinesMigrateIter<R, T> {
+        JsonLinesMigrateIter {
+            reader: self,
+            migrations,
+            _output: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`] value `R` that migrates each
+/// line to the latest schema version and decodes it as JSON of type `T`.
+///
+/// This iterator yields items of type `Result<T, std::io::Error>`.  Errors
+/// occur under the same conditions as for
+/// [`JsonLinesReader::read_migrated()`].
+///
+/// Iterators of this type are returned by [`JsonLinesReader::migrate_all()`].
+#[derive(Clone, Debug)]
+pub struct JsonLinesMigrateIter<R, T> {
+    reader: JsonLinesReader<R>,
+    migrations: Migrations,
+    _output: PhantomData<T>,
+}
+
+impl<R, T> JsonLinesMigrateIter<R, T> {
+    /// Consume the `JsonLinesMigrateIter` and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+
+    /// Get a reference to the migrations
+    pub fn migrations(&self) -> &Migrations {
+        &self.migrations
+    }
+}
+
+impl<R, T> Iterator for JsonLinesMigrateIter<R, T>
+where
+    R: BufRead,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.reader.read_migrated(&self.migrations).transpose()
+    }
+}
+
+/// Rewrite the JSON Lines file at `path` so that every record is at the
+/// latest schema version of `migrations`.
+///
+/// Records that are already at the latest version are copied through
+/// unchanged; all others are migrated and reserialized.  Each line keeps its
+/// original line terminator (LF, CR LF, or none at the end of the file).  The
+/// new contents are written to a temporary file in the same directory, which
+/// is given the original file's permissions and then replaces the original
+/// file via a rename, so the file at `path` is never left partially
+/// migrated.
+///
+/// # Errors
+///
+/// Fails if the file cannot be read or the temporary file cannot be
+/// written or renamed.  Also fails if any line cannot be migrated, as for
+/// [`Migrations::version_of()`] and [`Migrations::migrate()`], in which case
+/// the error message includes the line number and the original file is left
+/// unchanged.
+pub fn migrate_file<P: AsRef<Path>>(path: P, migrations: &Migrations) -> Result<MigrationReport> {
+    let path = path.as_ref();
+    let input = BufReader::new(File::open(path)?);
+    let permissions = metadata(path)?.permissions();
+    let (tmp_path, output) = create_temp_file(path, "migrate")?;
+    let r = migrate_lines(input, BufWriter::new(output), migrations).and_then(|report| {
+        set_permissions(&tmp_path, permissions)?;
+        rename(&tmp_path, path)?;
+        Ok(report)
+    });
+    if r.is_err() {
+        let _ = remove_file(&tmp_path);
+    }
+    r
+}
+
+/// Create a new file in the same directory as `path`, for writing contents
+/// that will replace `path`, with a name not already in use, so that files
+/// left behind by an interrupted run are not clobbered
+pub(crate) fn create_temp_file(path: &Path, label: &str) -> Result<(PathBuf, File)> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "path has no file name"))?;
+    let pid = std::process::id();
+    let mut attempt = 0u32;
+    loop {
+        let mut tmp_name = file_name.to_owned();
+        tmp_name.push(format!(".{label}-{pid}-{attempt}.tmp"));
+        let tmp_path = path.with_file_name(tmp_name);
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)
+        {
+            Ok(fp) => return Ok((tmp_path, fp)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists && attempt < 1000 => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn migrate_lines<R: BufRead>(
+    mut input: R,
+    mut output: BufWriter<File>,
+    migrations: &Migrations,
+) -> Result<MigrationReport> {
+    let latest = migrations.latest_version();
+    let mut report = MigrationReport {
+        latest_version: latest,
+        ..MigrationReport::default()
+    };
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+        report.lines += 1;
+        let lineno = report.lines;
+        let with_lineno = |e: Error| Error::new(e.kind(), format!("line {lineno}: {e}"));
+        let record = line.strip_suffix('\n').unwrap_or(&line);
+        let record = record.strip_suffix('\r').unwrap_or(record);
+        let terminator = &line[record.len()..];
+        let from = migrations.version_of(record).map_err(with_lineno)?;
+        *report.counts.entry(from).or_insert(0) += 1;
+        if from == latest {
+            output.write_all(record.as_bytes())?;
+        } else {
+            let value = serde_json::from_str(record)
+                .map_err(Error::from)
+                .and_then(|value| migrations.migrate(value, from))
+                .map_err(with_lineno)?;
+            serde_json::to_writer(&mut output, &value)?;
+        }
+        output.write_all(terminator.as_bytes())?;
+    }
+    output
+        .into_inner()
+        .map_err(std::io::IntoInnerError::into_error)?
+        .sync_all()?;
+    Ok(report)
+}
+
+/// A summary of the records rewritten by [`migrate_file()`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MigrationReport {
+    /// The total number of records in the file
+    pub lines: u64,
+    /// The schema version that all records were migrated to
+    pub latest_version: u64,
+    /// The number of records found at each schema version before migrating
+    pub counts: BTreeMap<u64, u64>,
+}
+
+impl MigrationReport {
+    /// Return the number of records that were migrated, i.e., that were not
+    /// already at the latest version
+    pub fn migrated(&self) -> u64 {
+        self.counts
+            .iter()
+            .filter(|&(&v, _)| v != self.latest_version)
+            .map(|(_, &n)| n)
+            .sum()
+    }
+}
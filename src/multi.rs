@@ -0,0 +1,325 @@
+use crate::JsonLinesReader;
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{File, read_dir};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Result};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// An iterator over the deserialized JSON values in a sequence of JSON Lines
+/// files that reports the file & line that each value came from.
+///
+/// The files are read one after another in the order in which they were
+/// supplied.  Each item yielded by the iterator is either a
+/// [`SourcedValue<T>`] containing a value along with its source path & line
+/// number, or a [`SourcedError`] describing a failure to open a file, read
+/// from it, or deserialize one of its lines.
+///
+/// By default, errors do not stop the iteration: after a line fails to
+/// deserialize, reading resumes with the next line, and after a file cannot
+/// be opened or read from, reading resumes with the next file.  Call
+/// [`fail_fast()`][JsonLinesMultiReader::fail_fast] to instead stop after the
+/// first error.
+///
+/// When the `gzip` feature is enabled, files whose names end in `.gz` are
+/// decompressed while reading, and so gzipped & plain files may be freely
+/// mixed.
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde_jsonlines::JsonLinesMultiReader;
+///
+/// #[derive(Debug, Deserialize)]
+/// pub struct Event {
+///     pub id: u64,
+///     pub message: String,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     for r in JsonLinesMultiReader::<Event>::from_dir("shards")? {
+///         match r {
+///             Ok(sv) => println!("{}:{}: {:?}", sv.path.display(), sv.line, sv.value),
+///             Err(e) => eprintln!("{e}"),
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct JsonLinesMultiReader<T> {
+    paths: VecDeque<PathBuf>,
+    current: Option<CurrentFile>,
+    fail_fast: bool,
+    done: bool,
+    _output: PhantomData<T>,
+}
+
+impl<T> JsonLinesMultiReader<T> {
+    /// Construct a new `JsonLinesMultiReader` that reads from the given files
+    /// in the given order.
+    ///
+    /// Any path that refers to a directory at the time that it would be
+    /// opened is replaced by the JSON Lines files in it, as for
+    /// [`from_dir()`][JsonLinesMultiReader::from_dir].
+    pub fn new<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        JsonLinesMultiReader {
+            paths: paths.into_iter().map(Into::into).collect(),
+            current: None,
+            fail_fast: false,
+            done: false,
+            _output: PhantomData,
+        }
+    }
+
+    /// Construct a new `JsonLinesMultiReader` that reads from the JSON Lines
+    /// files in the directory `dir`, sorted by name.
+    ///
+    /// Only files whose names end in `.jsonl` or `.ndjson` (optionally
+    /// followed by `.gz` when the `gzip` feature is enabled) are read.
+    /// Subdirectories are not descended into.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::fs::read_dir()`].
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        Ok(JsonLinesMultiReader::new(list_dir(dir.as_ref())?))
+    }
+
+    /// Construct a new `JsonLinesMultiReader` that reads from the files
+    /// matching the glob pattern `pattern`, sorted by path.
+    ///
+    /// See [`glob::glob()`] for the supported syntax.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if
+    /// `pattern` is not a valid glob pattern, or an I/O error if a directory
+    /// could not be read while matching the pattern.
+    #[cfg(feature = "glob")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "glob")))]
+    pub fn from_glob(pattern: &str) -> Result<Self> {
+        let paths = glob::glob(pattern)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Error::from)?;
+        Ok(JsonLinesMultiReader::new(paths))
+    }
+
+    /// If `fail_fast` is true, stop iterating after the first error.
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Return the path of the file currently being read, if any
+    pub fn current_path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|cf| &*cf.path)
+    }
+
+    /// Open the next file in the queue, returning `Ok(false)` if there are no
+    /// more files
+    fn open_next(&mut self) -> std::result::Result<bool, SourcedError> {
+        let Some(path) = self.paths.pop_front() else {
+            return Ok(false);
+        };
+        if path.is_dir() {
+            let entries = list_dir(&path).map_err(|source| SourcedError {
+                path: Arc::from(path.as_path()),
+                line: None,
+                source,
+            })?;
+            for p in entries.into_iter().rev() {
+                self.paths.push_front(p);
+            }
+            return Ok(true);
+        }
+        let path = Arc::<Path>::from(path);
+        match open(&path) {
+            Ok(reader) => {
+                self.current = Some(CurrentFile {
+                    path,
+                    reader: JsonLinesReader::new(reader),
+                    line: 0,
+                });
+                Ok(true)
+            }
+            Err(source) => Err(SourcedError {
+                path,
+                line: None,
+                source,
+            }),
+        }
+    }
+
+    fn fail(&mut self, e: SourcedError) -> SourcedError {
+        if self.fail_fast {
+            self.done = true;
+        }
+        e
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonLinesMultiReader<T> {
+    type Item = std::result::Result<SourcedValue<T>, SourcedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            let Some(cf) = self.current.as_mut() else {
+                match self.open_next() {
+                    Ok(true) => continue,
+                    Ok(false) => {
+                        self.done = true;
+                        return None;
+                    }
+                    Err(e) => return Some(Err(self.fail(e))),
+                }
+            };
+            cf.line += 1;
+            let line = cf.line;
+            match cf.reader.read::<T>() {
+                Ok(Some(value)) => {
+                    return Some(Ok(SourcedValue {
+                        path: Arc::clone(&cf.path),
+                        line,
+                        value,
+                    }));
+                }
+                Ok(None) => self.current = None,
+                Err(source) => {
+                    let path = Arc::clone(&cf.path);
+                    // Invalid UTF-8 & invalid JSON only affect the current line;
+                    // any other error makes the rest of the file unusable.
+                    if source.kind() != ErrorKind::InvalidData
+                        && source
+                            .get_ref()
+                            .is_none_or(|e| !e.is::<serde_json::Error>())
+                    {
+                        self.current = None;
+                    }
+                    return Some(Err(self.fail(SourcedError {
+                        path,
+                        line: Some(line),
+                        source,
+                    })));
+                }
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for JsonLinesMultiReader<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLinesMultiReader")
+            .field("paths", &self.paths)
+            .field("current", &self.current)
+            .field("fail_fast", &self.fail_fast)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A value read by a [`JsonLinesMultiReader`], along with where it was read
+/// from
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourcedValue<T> {
+    /// The path of the file that the value was read from
+    pub path: Arc<Path>,
+    /// The (1-based) number of the line that the value was read from
+    pub line: u64,
+    /// The deserialized value
+    pub value: T,
+}
+
+/// An error encountered by a [`JsonLinesMultiReader`], along with the file &
+/// line at which it occurred
+#[derive(Debug)]
+pub struct SourcedError {
+    /// The path of the file being read when the error occurred
+    pub path: Arc<Path>,
+    /// The (1-based) number of the line being read when the error occurred,
+    /// or `None` if the file could not be opened
+    pub line: Option<u64>,
+    /// The underlying error
+    pub source: Error,
+}
+
+impl fmt::Display for SourcedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.source),
+            None => write!(f, "{}: {}", self.path.display(), self.source),
+        }
+    }
+}
+
+impl std::error::Error for SourcedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<SourcedError> for Error {
+    fn from(e: SourcedError) -> Error {
+        Error::new(e.source.kind(), e)
+    }
+}
+
+struct CurrentFile {
+    path: Arc<Path>,
+    reader: JsonLinesReader<Box<dyn BufRead + Send>>,
+    line: u64,
+}
+
+impl fmt::Debug for CurrentFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CurrentFile")
+            .field("path", &self.path)
+            .field("line", &self.line)
+            .finish_non_exhaustive()
+    }
+}
+
+fn list_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            continue;
+        }
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        #[cfg(feature = "gzip")]
+        let name = name.strip_suffix(".gz").unwrap_or(&name);
+        if name.ends_with(".jsonl") || name.ends_with(".ndjson") {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(feature = "gzip")]
+fn open(path: &Path) -> Result<Box<dyn BufRead + Send>> {
+    let fp = File::open(path)?;
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        let decoder = flate2::read::MultiGzDecoder::new(BufReader::new(fp));
+        Ok(Box::new(BufReader::new(decoder)))
+    } else {
+        Ok(Box::new(BufReader::new(fp)))
+    }
+}
+
+#[cfg(not(feature = "gzip"))]
+fn open(path: &Path) -> Result<Box<dyn BufRead + Send>> {
+    Ok(Box::new(BufReader::new(File::open(path)?)))
+}
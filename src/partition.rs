@@ -0,0 +1,338 @@
+use crate::JsonLinesWriter;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::{File, OpenOptions, create_dir_all};
+use std::hash::Hash;
+use std::io::{BufWriter, Error, ErrorKind, Result};
+use std::path::PathBuf;
+
+/// A trait for partition keys used by [`PartitionedJsonLinesWriter`] that
+/// supplies the values for the placeholders in a path template
+pub trait PartitionKey: Clone + Eq + Hash {
+    /// Return the value to substitute for the placeholder `{name}` in a path
+    /// template, or `None` if the key has no such field
+    fn field(&self, name: &str) -> Option<String>;
+}
+
+/// A `String` key substitutes its value for every placeholder, regardless of
+/// the placeholder's name.
+impl PartitionKey for String {
+    fn field(&self, _name: &str) -> Option<String> {
+        Some(self.clone())
+    }
+}
+
+/// A `BTreeMap` key substitutes the value for each placeholder's name.
+impl PartitionKey for BTreeMap<String, String> {
+    fn field(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+/// A structure for writing JSON values as JSON Lines to multiple files, with
+/// each value routed to a file according to a key computed from it.
+///
+/// A `PartitionedJsonLinesWriter` is constructed from a path template and a
+/// key function.  When a value is written, the key function is applied to it
+/// to determine its partition, and the partition's path is then obtained by
+/// replacing each `{name}` placeholder in the template with the
+/// corresponding [field][PartitionKey::field] of the key.  (Literal braces
+/// can be included in the template by doubling them.)  As field values
+/// typically come from the data being written, a value that is empty, is `.`
+/// or `..`, or contains a path separator is rejected rather than being allowed
+/// to escape the directory structure described by the template.  Keys that
+/// render to the same path share a single file.  Each partition's file
+/// is created (along with any missing parent directories) the first time a
+/// value is written to it; if the file already exists, its contents are
+/// discarded.
+///
+/// By default, every partition's file is kept open until
+/// [`finish()`][PartitionedJsonLinesWriter::finish] is called.  If there are
+/// many partitions, the number of open files can be capped with
+/// [`max_open_files()`][PartitionedJsonLinesWriter::max_open_files], in which
+/// case the least recently written-to file is flushed and closed whenever
+/// the cap would be exceeded; it is reopened in append mode if it is written
+/// to again.
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Serialize;
+/// use serde_jsonlines::PartitionedJsonLinesWriter;
+///
+/// #[derive(Serialize)]
+/// pub struct Order {
+///     pub customer: String,
+///     pub date: String,
+///     pub total: u64,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut writer = PartitionedJsonLinesWriter::new(
+///         "out/date={date}/part.jsonl",
+///         |order: &Order| order.date.clone(),
+///     )
+///     .max_open_files(32);
+///     writer.write(&Order {
+///         customer: "Acme".into(),
+///         date: "2025-10-17".into(),
+///         total: 42,
+///     })?;
+///     writer.write(&Order {
+///         customer: "Initech".into(),
+///         date: "2025-10-18".into(),
+///         total: 23,
+///     })?;
+///     let partitions = writer.finish()?;
+///     assert_eq!(partitions.len(), 2);
+///     Ok(())
+/// }
+/// ```
+pub struct PartitionedJsonLinesWriter<K, T> {
+    template: String,
+    key_fn: Box<dyn FnMut(&T) -> K + Send>,
+    max_open: Option<usize>,
+    open: HashMap<PathBuf, OpenPartition>,
+    paths: HashMap<K, PathBuf>,
+    created: HashSet<PathBuf>,
+    tick: u64,
+}
+
+impl<K: PartitionKey, T> PartitionedJsonLinesWriter<K, T> {
+    /// Construct a new `PartitionedJsonLinesWriter` that writes to the paths
+    /// given by `template` according to the keys returned by `key_fn`
+    pub fn new<S, F>(template: S, key_fn: F) -> Self
+    where
+        S: Into<String>,
+        F: FnMut(&T) -> K + Send + 'static,
+    {
+        PartitionedJsonLinesWriter {
+            template: template.into(),
+            key_fn: Box::new(key_fn),
+            max_open: None,
+            open: HashMap::new(),
+            paths: HashMap::new(),
+            created: HashSet::new(),
+            tick: 0,
+        }
+    }
+
+    /// Keep at most `max_open` partition files open at once, closing the
+    /// least recently used file when the limit would be exceeded.
+    ///
+    /// A `max_open` of zero is treated as one.
+    pub fn max_open_files(mut self, max_open: usize) -> Self {
+        self.max_open = Some(max_open.max(1));
+        self
+    }
+
+    /// Return the number of partition files currently open
+    pub fn open_files(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Serialize a value as a line of JSON and write it, followed by a
+    /// newline, to the file for the value's partition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if the
+    /// path template is malformed or contains a placeholder for which the
+    /// value's key has no field.  Returns an error of kind
+    /// [`std::io::ErrorKind::InvalidData`] if a field value substituted into
+    /// the template is empty, is `.` or `..`, or contains a path separator.
+    /// Also has the same error conditions as
+    /// [`std::fs::create_dir_all()`], [`std::fs::OpenOptions::open()`], and
+    /// [`JsonLinesWriter::write()`].
+    pub fn write(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let key = (self.key_fn)(value);
+        let path = match self.paths.get(&key) {
+            Some(p) => p.clone(),
+            None => render(&self.template, &key)?,
+        };
+        self.tick += 1;
+        if let Some(part) = self.open.get_mut(&path) {
+            part.last_used = self.tick;
+            part.writer.write(value)?;
+            self.paths.entry(key).or_insert(path);
+            return Ok(());
+        }
+        if let Some(max_open) = self.max_open
+            && self.open.len() >= max_open
+        {
+            self.evict()?;
+        }
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let fp = if self.created.contains(&path) {
+            OpenOptions::new().append(true).open(&path)?
+        } else {
+            File::create(&path)?
+        };
+        self.created.insert(path.clone());
+        let mut writer = JsonLinesWriter::new(BufWriter::new(fp));
+        writer.write(value)?;
+        self.paths.entry(key).or_insert_with(|| path.clone());
+        self.open.insert(
+            path,
+            OpenPartition {
+                writer,
+                last_used: self.tick,
+            },
+        );
+        Ok(())
+    }
+
+    /// Serialize each item in an iterator as a line of JSON, and write out
+    /// each one followed by a newline to the file for its partition.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`write()`][PartitionedJsonLinesWriter::write].
+    pub fn write_all<I>(&mut self, items: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: Serialize,
+    {
+        for value in items {
+            self.write(&value)?;
+        }
+        Ok(())
+    }
+
+    /// Flush all open partition files.
+    ///
+    /// Every file is flushed even if flushing an earlier one fails.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::io::Write::flush()`].  If
+    /// flushing more than one file fails, the first error is returned.
+    pub fn flush(&mut self) -> Result<()> {
+        let mut r = Ok(());
+        for part in self.open.values_mut() {
+            let flushed = part.writer.flush();
+            if r.is_ok() {
+                r = flushed;
+            }
+        }
+        r
+    }
+
+    /// Flush and close all open partition files, and return a map from each
+    /// partition key that was written to its file's path.
+    ///
+    /// Every file is flushed & closed even if flushing an earlier one fails.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::io::Write::flush()`].  If
+    /// flushing more than one file fails, the first error is returned.
+    pub fn finish(mut self) -> Result<HashMap<K, PathBuf>> {
+        self.flush()?;
+        Ok(self.paths)
+    }
+
+    fn evict(&mut self) -> Result<()> {
+        let lru = self
+            .open
+            .iter()
+            .min_by_key(|(_, part)| part.last_used)
+            .map(|(path, _)| path.clone());
+        if let Some(path) = lru
+            && let Some(mut part) = self.open.remove(&path)
+        {
+            part.writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl<K: fmt::Debug, T> fmt::Debug for PartitionedJsonLinesWriter<K, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartitionedJsonLinesWriter")
+            .field("template", &self.template)
+            .field("key_fn", &"<function>")
+            .field("max_open", &self.max_open)
+            .field("open", &self.open)
+            .field("paths", &self.paths)
+            .field("created", &self.created)
+            .field("tick", &self.tick)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+struct OpenPartition {
+    writer: JsonLinesWriter<BufWriter<File>>,
+    last_used: u64,
+}
+
+fn render<K: PartitionKey>(template: &str, key: &K) -> Result<PathBuf> {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('{') if name.is_empty() => {
+                            out.push('{');
+                            break;
+                        }
+                        Some('}') => {
+                            let Some(value) = key.field(&name) else {
+                                return Err(Error::new(
+                                    ErrorKind::InvalidInput,
+                                    format!("partition key has no field {name:?}"),
+                                ));
+                            };
+                            check_component(&name, &value)?;
+                            out.push_str(&value);
+                            break;
+                        }
+                        Some(c) => name.push(c),
+                        None => {
+                            return Err(Error::new(
+                                ErrorKind::InvalidInput,
+                                "unterminated placeholder in path template",
+                            ));
+                        }
+                    }
+                }
+            }
+            '}' => {
+                if chars.next() != Some('}') {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "unmatched '}' in path template",
+                    ));
+                }
+                out.push('}');
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(PathBuf::from(out))
+}
+
+fn check_component(name: &str, value: &str) -> Result<()> {
+    if value.is_empty()
+        || value == "."
+        || value == ".."
+        || value.chars().any(std::path::is_separator)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("partition key field {name:?} has invalid path component {value:?}"),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,462 @@
+use crate::JsonLinesReader;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor,
+};
+use serde::{Deserialize, forward_to_deserialize_any};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{BufRead, Error, ErrorKind, Result};
+use std::marker::PhantomData;
+
+/// A set of [JSON Pointers](https://www.rfc-editor.org/rfc/rfc6901) to
+/// extract from each line of JSON Lines input without deserializing the rest
+/// of the line.
+///
+/// When a line is read with a `Projection`, only the values at the requested
+/// pointers are built; every other part of the line is merely checked for
+/// well-formedness and then skipped over.  This makes it much cheaper to
+/// pull one or two fields out of large, heterogeneous records than it would
+/// be to deserialize each line as a [`serde_json::Value`].
+///
+/// The extracted fields can be deserialized as either:
+///
+/// - a sequence, such as a tuple, array, or `Vec`, in which the fields
+///   appear in the order in which the pointers were given, with any pointer
+///   that does not resolve in a given line producing `null`; or
+///
+/// - a map, such as a `BTreeMap<String, serde_json::Value>`, or a struct
+///   whose fields are renamed to the pointers, in which each key is a
+///   pointer and only the pointers that resolve in a given line are
+///   present.
+///
+/// See [`JsonLinesReader::read_projected()`] and
+/// [`JsonLinesReader::project_all()`].
+///
+/// # Example
+///
+/// ```
+/// use serde_jsonlines::{JsonLinesReader, Projection};
+///
+/// fn main() -> std::io::Result<()> {
+///     let input = concat!(
+///         "{\"ts\": 1, \"user\": {\"id\": 42, \"name\": \"Alice\"}, \"payload\": [1, 2, 3]}\n",
+///         "{\"ts\": 2, \"user\": {\"name\": \"Bob\"}}\n",
+///     );
+///     let projection = Projection::new(["/ts", "/user/id"])?;
+///     let items = JsonLinesReader::new(input.as_bytes())
+///         .project_all::<(u64, Option<u64>)>(projection)
+///         .collect::<std::io::Result<Vec<_>>>()?;
+///     assert_eq!(items, [(1, Some(42)), (2, None)]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Projection {
+    pointers: Vec<String>,
+    root: Node,
+}
+
+impl Projection {
+    /// Construct a new `Projection` from a sequence of JSON Pointers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if any
+    /// of the pointers is not a syntactically valid JSON Pointer, i.e., if it
+    /// is nonempty and does not start with `/`, or if it contains a `~` that
+    /// is not followed by `0` or `1`.
+    pub fn new<I, S>(pointers: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let pointers = pointers.into_iter().map(Into::into).collect::<Vec<_>>();
+        let mut root = Node::default();
+        for (i, ptr) in pointers.iter().enumerate() {
+            let mut node = &mut root;
+            for token in parse_pointer(ptr)? {
+                if let Some(i) = array_index(&token) {
+                    node.indices.insert(i, token.clone());
+                }
+                node = node.children.entry(token).or_default();
+            }
+            node.slots.push(i);
+        }
+        Ok(Projection { pointers, root })
+    }
+
+    /// Return the pointers in the projection, in the order in which they were
+    /// given
+    pub fn pointers(&self) -> &[String] {
+        &self.pointers
+    }
+
+    /// Extract the projected fields from a line of JSON
+    fn extract(&self, line: &str) -> serde_json::Result<Fields<'_>> {
+        Ok(Fields {
+            pointers: &self.pointers,
+            values: self.extract_values(line)?,
+        })
+    }
+
+    /// Extract the value at each pointer from a line of JSON, in the order
+    /// of the pointers
+    pub(crate) fn extract_values(&self, line: &str) -> serde_json::Result<Vec<Option<Value>>> {
+        let mut values = vec![None; self.pointers.len()];
+        let mut de = serde_json::Deserializer::from_str(line);
+        Walk {
+            node: &self.root,
+            values: &mut values,
+        }
+        .deserialize(&mut de)?;
+        de.end()?;
+        Ok(values)
+    }
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    /// Read a line of JSON from the underlying reader and deserialize just
+    /// the fields selected by `projection` as a value of type `T`.
+    ///
+    /// See [`Projection`] for the types that `T` can be.
+    ///
+    /// If end-of-file is reached, this method returns `Ok(None)`.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`read()`][JsonLinesReader::read].
+    /// In particular, a line that is not valid JSON results in an error even
+    /// if the invalid portion would have been skipped.
+    pub fn read_projected<T>(&mut self, projection: &Projection) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.read_line()? {
+            return Ok(None);
+        }
+        let r = projection
+            .extract(&self.buf)
+            .map_err(|e| self.lines.diagnose(e.into(), &self.buf))
+            .and_then(|fields| self.lines.decode_from(&self.buf, fields));
+        self.buf.clear();
+        r.map(Some)
+    }
+}
+
+impl<R> JsonLinesReader<R> {
+    /// Consume the `JsonLinesReader` and return an iterator over the fields
+    /// selected by `projection` from each line, deserialized as values of
+    /// type `T`.
+    ///
+    /// The returned iterator has an `Item` type of `std::io::Result<T>`.  Each
+    /// call to `next()` has the same error conditions as
+    /// [`read_projected()`][JsonLinesReader::read_projected].
+    pub fn project_all<T>(self, projection: Projection) -> JsonLinesProjectionIter<R, T> {
+        JsonLinesProjectionIter {
+            reader: self,
+            projection,
+            _output: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`] value `R` that extracts the
+/// fields selected by a [`Projection`] from each line and deserializes them
+/// as a value of type `T`.
+///
+/// This iterator yields items of type `Result<T, std::io::Error>`.  Errors
+/// occur under the same conditions as for
+/// [`JsonLinesReader::read_projected()`].
+///
+/// Iterators of this type are returned by [`JsonLinesReader::project_all()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JsonLinesProjectionIter<R, T> {
+    reader: JsonLinesReader<R>,
+    projection: Projection,
+    _output: PhantomData<T>,
+}
+
+impl<R, T> JsonLinesProjectionIter<R, T> {
+    /// Consume the `JsonLinesProjectionIter` and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+
+    /// Get a reference to the projection
+    pub fn projection(&self) -> &Projection {
+        &self.projection
+    }
+}
+
+impl<R, T> Iterator for JsonLinesProjectionIter<R, T>
+where
+    R: BufRead,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.reader.read_projected(&self.projection).transpose()
+    }
+}
+
+/// A node in the trie of reference tokens built from a projection's pointers
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Node {
+    /// The indices of the pointers that end at this node
+    slots: Vec<usize>,
+    children: BTreeMap<String, Node>,
+    /// The keys in `children` that are also array indices, keyed by index,
+    /// so that array elements can be matched without formatting each index
+    indices: BTreeMap<usize, String>,
+}
+
+impl Node {
+    /// Return the child for the array element at index `i`, if any
+    fn element(&self, i: usize) -> Option<&Node> {
+        let token = self.indices.get(&i)?;
+        self.children.get(token)
+    }
+
+    /// Fill in the slots for this node and all of its descendants from an
+    /// already-built value
+    fn fill(&self, value: &Value, values: &mut [Option<Value>]) {
+        for &i in &self.slots {
+            if let Some(v) = values.get_mut(i) {
+                *v = Some(value.clone());
+            }
+        }
+        for (token, child) in &self.children {
+            let sub = match value {
+                Value::Object(map) => map.get(token),
+                Value::Array(array) => array_index(token).and_then(|i| array.get(i)),
+                _ => None,
+            };
+            if let Some(sub) = sub {
+                child.fill(sub, values);
+            }
+        }
+    }
+}
+
+/// A [`DeserializeSeed`] that walks a JSON value alongside a trie node,
+/// building only the requested subtrees
+struct Walk<'a> {
+    node: &'a Node,
+    values: &'a mut [Option<Value>],
+}
+
+impl<'de> DeserializeSeed<'de> for Walk<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        if !self.node.slots.is_empty() {
+            let value = Value::deserialize(deserializer)?;
+            self.node.fill(&value, self.values);
+            Ok(())
+        } else if self.node.children.is_empty() {
+            IgnoredAny::deserialize(deserializer).map(|_| ())
+        } else {
+            deserializer.deserialize_any(self)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Walk<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        while let Some(child) = map.next_key_seed(ChildKey(&self.node.children))? {
+            match child {
+                Some(child) => map.next_value_seed(Walk {
+                    node: child,
+                    values: &mut *self.values,
+                })?,
+                None => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        let mut i = 0;
+        loop {
+            let child = self.node.element(i);
+            let more = match child {
+                Some(child) => seq
+                    .next_element_seed(Walk {
+                        node: child,
+                        values: &mut *self.values,
+                    })?
+                    .is_some(),
+                None => seq.next_element::<IgnoredAny>()?.is_some(),
+            };
+            if !more {
+                return Ok(());
+            }
+            i += 1;
+        }
+    }
+
+    fn visit_bool<E: de::Error>(self, _v: bool) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E: de::Error>(self, _v: i64) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E: de::Error>(self, _v: u64) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E: de::Error>(self, _v: f64) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E: de::Error>(self, _v: &str) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<(), E> {
+        Ok(())
+    }
+}
+
+/// A [`DeserializeSeed`] for an object key that looks up the key's child
+/// node without allocating a `String` for the key
+struct ChildKey<'a>(&'a BTreeMap<String, Node>);
+
+impl<'de, 'a> DeserializeSeed<'de> for ChildKey<'a> {
+    type Value = Option<&'a Node>;
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'a> Visitor<'_> for ChildKey<'a> {
+    type Value = Option<&'a Node>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("an object key")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Self::Value, E> {
+        Ok(self.0.get(v))
+    }
+}
+
+/// The fields extracted from a line by a [`Projection`], which can be
+/// deserialized as either a sequence or a map
+struct Fields<'a> {
+    pointers: &'a [String],
+    values: Vec<Option<Value>>,
+}
+
+impl<'de> de::Deserializer<'de> for Fields<'_> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        let mut seq =
+            SeqDeserializer::new(self.values.into_iter().map(|v| v.unwrap_or(Value::Null)));
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        let mut map = MapDeserializer::new(
+            self.pointers
+                .iter()
+                .zip(self.values)
+                .filter_map(|(ptr, v)| Some((ptr.clone(), v?))),
+        );
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        self.deserialize_map(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct enum identifier ignored_any
+    }
+}
+
+/// Split a JSON Pointer into its unescaped reference tokens
+pub(crate) fn parse_pointer(ptr: &str) -> Result<Vec<String>> {
+    if ptr.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = ptr.strip_prefix('/') else {
+        return Err(invalid_pointer(ptr));
+    };
+    rest.split('/')
+        .map(|token| {
+            let mut out = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                if c == '~' {
+                    match chars.next() {
+                        Some('0') => out.push('~'),
+                        Some('1') => out.push('/'),
+                        _ => return Err(invalid_pointer(ptr)),
+                    }
+                } else {
+                    out.push(c);
+                }
+            }
+            Ok(out)
+        })
+        .collect()
+}
+
+/// Parse a reference token as an array index, rejecting leading zeroes as
+/// required by RFC 6901
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    if !token.bytes().all(|b| b.is_ascii_digit()) || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+fn invalid_pointer(ptr: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("invalid JSON Pointer: {ptr:?}"),
+    )
+}
@@ -0,0 +1,277 @@
+use crate::JsonLinesReader;
+use std::io::{BufRead, Result};
+use std::marker::PhantomData;
+
+/// A type that can be decoded from a single line of JSON Lines input by
+/// inspecting the line before (or instead of) deserializing it directly.
+///
+/// This is primarily intended to be implemented via `#[derive(JsonLinesRecord)]`
+/// (available when the `derive` feature is enabled), which supports:
+///
+/// - **Enums**, in which each variant wraps the type for one kind of record.
+///   The value of a discriminator field (`"type"` by default; set with
+///   `#[jsonlines(tag = "...")]`) is looked up without deserializing the rest
+///   of the line, and the line is then deserialized as the payload of the
+///   variant with that name (which can be changed with
+///   `#[jsonlines(rename = "...")]` on the variant).  A variant marked
+///   `#[jsonlines(other)]` receives lines whose discriminator matches no
+///   other variant, and a variant marked `#[jsonlines(record)]` decodes its
+///   payload with the payload's own `JsonLinesRecord` implementation rather
+///   than with [`serde::Deserialize`].
+///
+/// - **Structs** that carry a schema version.  With `#[jsonlines(version =
+///   N)]`, the value of a version field (`"schema_version"` by default; set
+///   with `#[jsonlines(version_field = "...")]`) is checked before
+///   deserializing.  Lines at version `N` are deserialized as the struct
+///   itself, while lines at older versions can be upgraded by listing types
+///   that implement `Into<Self>` with `#[jsonlines(upgrade(1 = EventV1, 2 =
+///   EventV2))]`.  Lines without a version field are treated as having the
+///   version given by `#[jsonlines(default_version = M)]`, if any.
+///
+/// Values are still written with [`serde::Serialize`], e.g., via
+/// [`JsonLinesWriter`][crate::JsonLinesWriter].
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # fn main() -> std::io::Result<()> {
+/// use serde::Deserialize;
+/// use serde_jsonlines::{JsonLinesReader, JsonLinesRecord};
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// pub struct LoginV1 {
+///     pub user: String,
+/// }
+///
+/// #[derive(Debug, Deserialize, JsonLinesRecord, PartialEq)]
+/// #[jsonlines(version = 2, default_version = 1, upgrade(1 = LoginV1))]
+/// pub struct Login {
+///     pub user: String,
+///     pub method: String,
+/// }
+///
+/// impl From<LoginV1> for Login {
+///     fn from(v1: LoginV1) -> Login {
+///         Login {
+///             user: v1.user,
+///             method: "password".into(),
+///         }
+///     }
+/// }
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// pub struct Logout {
+///     pub user: String,
+/// }
+///
+/// #[derive(Debug, JsonLinesRecord, PartialEq)]
+/// pub enum Event {
+///     #[jsonlines(rename = "login", record)]
+///     Login(Login),
+///     #[jsonlines(rename = "logout")]
+///     Logout(Logout),
+/// }
+///
+/// let input = concat!(
+///     "{\"type\": \"login\", \"user\": \"alice\"}\n",
+///     "{\"type\": \"login\", \"schema_version\": 2, \"user\": \"bob\", \"method\": \"sso\"}\n",
+///     "{\"user\": \"alice\", \"type\": \"logout\"}\n",
+/// );
+/// let events = JsonLinesReader::new(input.as_bytes())
+///     .read_all_records::<Event>()
+///     .collect::<std::io::Result<Vec<_>>>()?;
+/// assert_eq!(
+///     events,
+///     [
+///         Event::Login(Login {
+///             user: "alice".into(),
+///             method: "password".into()
+///         }),
+///         Event::Login(Login {
+///             user: "bob".into(),
+///             method: "sso".into()
+///         }),
+///         Event::Logout(Logout {
+///             user: "alice".into()
+///         }),
+///     ]
+/// );
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// ```
+pub trait JsonLinesRecord: Sized {
+    /// Decode a value from a single line of JSON (without its line
+    /// terminator).
+    ///
+    /// # Errors
+    ///
+    /// Implementations should return an error of kind
+    /// [`std::io::ErrorKind::InvalidData`] if the line is not valid JSON or
+    /// does not describe a value of this type.
+    fn from_json_line(line: &str) -> Result<Self>;
+
+    /// Decode a value from a single line of JSON using the field path &
+    /// diagnostic settings of the reader that the line was read from.
+    ///
+    /// This is overridden by `#[derive(JsonLinesRecord)]`; other
+    /// implementations should use the default, which calls
+    /// [`from_json_line()`][JsonLinesRecord::from_json_line].
+    #[doc(hidden)]
+    fn from_json_line_with(line: &str, _cx: &__private::Context) -> Result<Self> {
+        Self::from_json_line(line)
+    }
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    /// Read a line of JSON from the underlying reader and decode it as a
+    /// [`JsonLinesRecord`] of type `T`.
+    ///
+    /// If end-of-file is reached, this method returns `Ok(None)`.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::io::BufRead::read_line()`] and
+    /// [`JsonLinesRecord::from_json_line()`].  As with
+    /// [`read()`][JsonLinesReader::read], reading can continue with the next
+    /// line after a decoding error.
+    pub fn read_record<T: JsonLinesRecord>(&mut self) -> Result<Option<T>> {
+        if !self.read_line()? {
+            return Ok(None);
+        }
+        let line = self.buf.trim_end_matches(['\n', '\r']);
+        let r = T::from_json_line_with(line, &__private::Context(self.lines))
+            .map_err(|e| self.lines.diagnose(e, line));
+        self.buf.clear();
+        r.map(Some)
+    }
+}
+
+impl<R> JsonLinesReader<R> {
+    /// Consume the `JsonLinesReader` and return an iterator over the
+    /// [`JsonLinesRecord`] values decoded from each line.
+    ///
+    /// The returned iterator has an `Item` type of `std::io::Result<T>`.  Each
+    /// call to `next()` has the same error conditions as
+    /// [`read_record()`][JsonLinesReader::read_record].
+    pub fn read_all_records<T>(self) -> JsonLinesRecordIter<R, T> {
+        JsonLinesRecordIter {
+            reader: self,
+            _output: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`] value `R` that decodes each
+/// line as a [`JsonLinesRecord`] of type `T`.
+///
+/// This iterator yields items of type `Result<T, std::io::Error>`.  Errors
+/// occur under the same conditions as for
+/// [`JsonLinesReader::read_record()`].
+///
+/// Iterators of this type are returned by
+/// [`JsonLinesReader::read_all_records()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JsonLinesRecordIter<R, T> {
+    reader: JsonLinesReader<R>,
+    _output: PhantomData<T>,
+}
+
+impl<R, T> JsonLinesRecordIter<R, T> {
+    /// Consume the `JsonLinesRecordIter` and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+}
+
+impl<R: BufRead, T: JsonLinesRecord> Iterator for JsonLinesRecordIter<R, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.reader.read_record().transpose()
+    }
+}
+
+/// Support code for `#[derive(JsonLinesRecord)]`.  Not public API.
+#[doc(hidden)]
+pub mod __private {
+    use crate::{LineHandling, Projection, sniff};
+    use serde::de::DeserializeOwned;
+    pub use serde_json::Value;
+    use std::io::{Error, ErrorKind, Result};
+    use std::marker::PhantomData;
+    use std::sync::OnceLock;
+
+    /// The field path & diagnostic settings of the reader that a line was
+    /// read from
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Context(pub(crate) LineHandling);
+
+    impl Context {
+        pub fn decode<T: DeserializeOwned>(&self, line: &str) -> Result<T> {
+            self.0.decode_seed(line, PhantomData::<T>)
+        }
+    }
+
+    /// A JSON Pointer to a top-level field, along with a [`Projection`] for
+    /// it that is built on first use and then reused for every line
+    #[derive(Debug)]
+    pub struct Field {
+        pointer: &'static str,
+        projection: OnceLock<Projection>,
+    }
+
+    impl Field {
+        pub const fn new(pointer: &'static str) -> Field {
+            Field {
+                pointer,
+                projection: OnceLock::new(),
+            }
+        }
+
+        fn projection(&self) -> &Projection {
+            self.projection.get_or_init(|| {
+                Projection::new([self.pointer])
+                    .expect("derive-generated JSON Pointer should be valid")
+            })
+        }
+
+        /// Extract the value of the field from a line of JSON without
+        /// deserializing the rest of the line
+        pub fn get(&self, line: &str) -> Result<Option<Value>> {
+            sniff::field(self.projection(), line)
+        }
+
+        /// Extract the schema version in the field from a line of JSON
+        pub fn version(&self, line: &str, default: Option<u64>) -> Result<u64> {
+            sniff::version(self.projection(), line, default)
+        }
+    }
+
+    pub fn unsupported_version(pointer: &str, version: u64) -> Error {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("unsupported schema version at {pointer}: {version}"),
+        )
+    }
+
+    pub fn unknown_kind(pointer: &str, kind: Option<&Value>) -> Error {
+        let msg = match kind {
+            Some(kind) => format!("unknown record type at {pointer}: {kind}"),
+            None => format!("no record type found at {pointer}"),
+        };
+        Error::new(ErrorKind::InvalidData, msg)
+    }
+}
@@ -0,0 +1,519 @@
+use crate::{JsonLinesWriter, LineTerminator};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::{File, remove_file};
+use std::io::{BufWriter, Error, ErrorKind, Result, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A structure for writing JSON values as JSON Lines to a series of files,
+/// rolling over to a new file whenever the current one grows too large or too
+/// old.
+///
+/// A `RotatingJsonLinesWriter` is created with
+/// [`RotatingJsonLinesWriter::builder()`], which takes a pattern for the
+/// names of the files to create.  The following placeholders in the pattern
+/// are replaced when each file is created:
+///
+/// | Placeholder | Replacement                                            |
+/// | ----------- | ------------------------------------------------------ |
+/// | `%Y`        | The current year (UTC), as four digits                 |
+/// | `%m`        | The current month (UTC), as two digits                 |
+/// | `%d`        | The current day of the month (UTC), as two digits      |
+/// | `%H`        | The current hour (UTC), as two digits                  |
+/// | `%M`        | The current minute (UTC), as two digits                |
+/// | `%S`        | The current second (UTC), as two digits                |
+/// | `%n`        | A sequence number (required)                           |
+/// | `%%`        | A literal `%`                                          |
+///
+/// The sequence number starts at the lowest value for which the resulting
+/// file (or, if compression is enabled, its compressed form) does not already
+/// exist, and it increases by at least one with each rotation, skipping over
+/// any values that would result in existing files.  Whenever the rest of the
+/// file name changes (e.g., when the date in a `%Y%m%d` pattern rolls over),
+/// the sequence number restarts from zero.  Existing files are thus never
+/// overwritten.
+///
+/// Each value is written to a file in its entirety; a line is never split
+/// across two files.  The current file is rotated before writing a value if
+/// doing so would exceed any of the configured limits, but a file is never
+/// rotated while it is empty, and so a single line larger than the byte limit
+/// will be written to a file on its own.
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Serialize;
+/// use serde_jsonlines::RotatingJsonLinesWriter;
+/// use std::time::Duration;
+///
+/// #[derive(Serialize)]
+/// pub struct Event {
+///     pub id: u64,
+///     pub message: String,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut writer = RotatingJsonLinesWriter::builder("logs/events-%Y%m%d-%n.jsonl")
+///         .max_bytes(64 << 20)
+///         .max_age(Duration::from_secs(3600))
+///         .max_files(24)
+///         .open()?;
+///     for id in 0..1000 {
+///         writer.write(&Event {
+///             id,
+///             message: String::from("Something happened"),
+///         })?;
+///     }
+///     writer.flush()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RotatingJsonLinesWriter {
+    inner: JsonLinesWriter<BufWriter<File>>,
+    options: RotatingJsonLinesWriterBuilder,
+    path: PathBuf,
+    opened: Instant,
+    bytes: u64,
+    records: u64,
+    seq: u64,
+    /// The file name pattern expanded with a sequence number of zero at the
+    /// time the current file was created, used to detect when the date &
+    /// time placeholders change
+    stamp: String,
+    rotated: VecDeque<PathBuf>,
+}
+
+impl RotatingJsonLinesWriter {
+    /// Return a builder for a `RotatingJsonLinesWriter` that writes to files
+    /// with names created from `pattern`.
+    ///
+    /// See the [type-level documentation][RotatingJsonLinesWriter] for the
+    /// placeholders supported in `pattern`.
+    pub fn builder<S: Into<String>>(pattern: S) -> RotatingJsonLinesWriterBuilder {
+        RotatingJsonLinesWriterBuilder {
+            pattern: pattern.into(),
+            max_bytes: None,
+            max_records: None,
+            max_age: None,
+            max_files: None,
+            terminator: LineTerminator::default(),
+            #[cfg(feature = "gzip")]
+            compress: false,
+        }
+    }
+
+    /// Return the path to the file currently being written to
+    pub fn current_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the paths of the rotated files that are still present, from
+    /// oldest to newest.
+    ///
+    /// Only files rotated by this writer are included.
+    pub fn rotated_paths(&self) -> impl ExactSizeIterator<Item = &Path> + '_ {
+        self.rotated.iter().map(PathBuf::as_path)
+    }
+
+    /// Serialize a value as a line of JSON and write it to the current file,
+    /// followed by the line terminator, rotating first if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`serde_json::to_writer()`],
+    /// [`std::io::Write::write_all()`], and
+    /// [`rotate()`][RotatingJsonLinesWriter::rotate].
+    pub fn write<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut buf = serde_json::to_vec(value)?;
+        buf.extend_from_slice(self.inner.terminator.as_bytes());
+        let len = u64::try_from(buf.len()).unwrap_or(u64::MAX);
+        if self.should_rotate(len) {
+            self.rotate()?;
+        }
+        self.inner.get_mut().write_all(&buf)?;
+        self.bytes = self.bytes.saturating_add(len);
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Serialize each item in an iterator as a line of JSON, and write out
+    /// each one followed by the line terminator, rotating as necessary.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`write()`][RotatingJsonLinesWriter::write].
+    pub fn write_all<T, I>(&mut self, items: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: Serialize,
+    {
+        for value in items {
+            self.write(&value)?;
+        }
+        Ok(())
+    }
+
+    /// Flush the current file.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::io::Write::flush()`].
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+
+    /// Close the current file and start writing to a new one, regardless of
+    /// whether any limits have been reached.
+    ///
+    /// If compression is enabled, the closed file is then compressed.  If
+    /// the number of rotated files now exceeds the configured maximum, the
+    /// oldest rotated files are deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if flushing the current file, compressing it,
+    /// creating the new file, or deleting old files fails.  If compression
+    /// fails, the new file is still used for subsequent writes, and the
+    /// uncompressed file is counted as a rotated file.
+    pub fn rotate(&mut self) -> Result<()> {
+        self.inner.flush()?;
+        let (fp, path, seq, stamp) = self
+            .options
+            .create_file(SystemTime::now(), Some((&self.stamp, self.seq)))?;
+        // Dropping the old writer closes the old file
+        self.inner = self.options.writer(fp);
+        let old_path = std::mem::replace(&mut self.path, path);
+        self.seq = seq;
+        self.stamp = stamp;
+        self.opened = Instant::now();
+        self.bytes = 0;
+        self.records = 0;
+        let finished = self.options.finish_file(&old_path);
+        self.rotated
+            .push_back(finished.as_ref().map_or(old_path, Clone::clone));
+        finished?;
+        if let Some(max_files) = self.options.max_files {
+            while self.rotated.len() > max_files {
+                if let Some(p) = self.rotated.pop_front() {
+                    remove_file(p)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn should_rotate(&self, len: u64) -> bool {
+        if self.records == 0 {
+            return false;
+        }
+        self.options
+            .max_bytes
+            .is_some_and(|max| self.bytes.saturating_add(len) > max)
+            || self
+                .options
+                .max_records
+                .is_some_and(|max| self.records >= max)
+            || self
+                .options
+                .max_age
+                .is_some_and(|max| self.opened.elapsed() >= max)
+    }
+}
+
+/// A builder for configuring & opening a [`RotatingJsonLinesWriter`].
+///
+/// Builders are created by [`RotatingJsonLinesWriter::builder()`].  By
+/// default, no limits are set, and so files are only rotated by explicit
+/// calls to [`RotatingJsonLinesWriter::rotate()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RotatingJsonLinesWriterBuilder {
+    pattern: String,
+    max_bytes: Option<u64>,
+    max_records: Option<u64>,
+    max_age: Option<Duration>,
+    max_files: Option<usize>,
+    terminator: LineTerminator,
+    #[cfg(feature = "gzip")]
+    compress: bool,
+}
+
+impl RotatingJsonLinesWriterBuilder {
+    /// Rotate whenever writing a line would cause the current file to exceed
+    /// `max_bytes` bytes
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Rotate whenever the current file already contains `max_records` lines
+    pub fn max_records(mut self, max_records: u64) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
+    /// Rotate when writing to a file that was opened at least `max_age` ago.
+    ///
+    /// The age of a file is only checked when writing to it; no background
+    /// timer is used.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Keep at most `max_files` rotated files (not counting the current
+    /// file), deleting the oldest ones as new ones are rotated.
+    ///
+    /// Only files rotated by the same `RotatingJsonLinesWriter` are counted
+    /// or deleted; files left over from previous runs are never touched.
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// Set the line terminator written after each value.  The default is
+    /// [`LineTerminator::Lf`].
+    pub fn line_terminator(mut self, terminator: LineTerminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// If `compress` is true, compress each file with gzip after it is
+    /// rotated, replacing it with a file of the same name plus a `.gz`
+    /// extension.
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Create the first file and return a [`RotatingJsonLinesWriter`] for
+    /// writing to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if the
+    /// pattern does not contain `%n` or contains an unknown placeholder.
+    /// Also has the same error conditions as [`File::create_new()`].
+    pub fn open(self) -> Result<RotatingJsonLinesWriter> {
+        if !render(&self.pattern, SystemTime::now(), 0)?.1 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "rotating file name pattern must contain %n",
+            ));
+        }
+        let (fp, path, seq, stamp) = self.create_file(SystemTime::now(), None)?;
+        Ok(RotatingJsonLinesWriter {
+            inner: self.writer(fp),
+            options: self,
+            path,
+            opened: Instant::now(),
+            bytes: 0,
+            records: 0,
+            seq,
+            stamp,
+            rotated: VecDeque::new(),
+        })
+    }
+
+    fn writer(&self, fp: File) -> JsonLinesWriter<BufWriter<File>> {
+        JsonLinesWriter::new(BufWriter::new(fp)).line_terminator(self.terminator)
+    }
+
+    /// Create a new file named according to the pattern at time `now`.
+    ///
+    /// `prev` is the stamp & sequence number of the previous file, if any.
+    /// The sequence number continues on from the previous one if the stamp
+    /// is unchanged and otherwise restarts from zero.
+    fn create_file(
+        &self,
+        now: SystemTime,
+        prev: Option<(&str, u64)>,
+    ) -> Result<(File, PathBuf, u64, String)> {
+        let stamp = render(&self.pattern, now, 0)?.0;
+        let mut seq = match prev {
+            Some((prev_stamp, prev_seq)) if prev_stamp == stamp => prev_seq + 1,
+            _ => 0,
+        };
+        loop {
+            let path = PathBuf::from(render(&self.pattern, now, seq)?.0);
+            if !self.finished_path(&path).exists() {
+                match File::create_new(&path) {
+                    Ok(fp) => return Ok((fp, path, seq, stamp)),
+                    Err(e) if e.kind() == ErrorKind::AlreadyExists => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            seq += 1;
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    fn finished_path(&self, path: &Path) -> PathBuf {
+        if self.compress {
+            let mut s = path.as_os_str().to_owned();
+            s.push(".gz");
+            PathBuf::from(s)
+        } else {
+            path.to_path_buf()
+        }
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    fn finished_path(&self, path: &Path) -> PathBuf {
+        path.to_path_buf()
+    }
+
+    #[cfg(feature = "gzip")]
+    fn finish_file(&self, path: &Path) -> Result<PathBuf> {
+        if !self.compress {
+            return Ok(path.to_path_buf());
+        }
+        let gzpath = self.finished_path(path);
+        let src = File::open(path)?;
+        let dest = File::create_new(&gzpath)?;
+        if let Err(e) = gzip(src, dest) {
+            // Don't leave a truncated compressed file next to the original
+            let _ = remove_file(&gzpath);
+            return Err(e);
+        }
+        remove_file(path)?;
+        Ok(gzpath)
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[allow(clippy::unnecessary_wraps, clippy::unused_self)]
+    fn finish_file(&self, path: &Path) -> Result<PathBuf> {
+        Ok(path.to_path_buf())
+    }
+}
+
+/// Compress the contents of `src` with gzip and write them to `dest`
+#[cfg(feature = "gzip")]
+fn gzip(mut src: File, dest: File) -> Result<()> {
+    use flate2::{Compression, write::GzEncoder};
+    let mut encoder = GzEncoder::new(BufWriter::new(dest), Compression::default());
+    std::io::copy(&mut src, &mut encoder)?;
+    encoder.finish()?.flush()
+}
+
+/// Expand the placeholders in `pattern`.  The returned boolean indicates
+/// whether the pattern contains `%n`.
+fn render(pattern: &str, now: SystemTime, seq: u64) -> Result<(String, bool)> {
+    let secs = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let days = i64::try_from(secs / 86400).unwrap_or(i64::MAX);
+    let (year, month, day) = civil_from_days(days);
+    let tod = secs % 86400;
+    let mut out = String::with_capacity(pattern.len());
+    let mut has_seq = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let piece = match chars.next() {
+            Some('Y') => format!("{year:04}"),
+            Some('m') => format!("{month:02}"),
+            Some('d') => format!("{day:02}"),
+            Some('H') => format!("{:02}", tod / 3600),
+            Some('M') => format!("{:02}", tod / 60 % 60),
+            Some('S') => format!("{:02}", tod % 60),
+            Some('n') => {
+                has_seq = true;
+                seq.to_string()
+            }
+            Some('%') => String::from("%"),
+            Some(c) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("unknown placeholder %{c} in rotating file name pattern"),
+                ));
+            }
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "rotating file name pattern ends with a lone %",
+                ));
+            }
+        };
+        out.push_str(&piece);
+    }
+    Ok((out, has_seq))
+}
+
+/// Convert a number of days since the Unix epoch to a (year, month, day)
+/// triple in the proleptic Gregorian calendar.
+///
+/// Based on Howard Hinnant's `civil_from_days()` algorithm
+/// (<https://howardhinnant.github.io/date_algorithms.html>).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20379), (2025, 10, 18));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn test_render() {
+        let now = UNIX_EPOCH + Duration::from_secs(20379 * 86400 + 13 * 3600 + 5 * 60 + 9);
+        assert_eq!(
+            render("events-%Y%m%d-%H%M%S-%n.jsonl", now, 7).unwrap(),
+            (String::from("events-20251018-130509-7.jsonl"), true)
+        );
+        assert_eq!(
+            render("100%%-%Y.jsonl", now, 7).unwrap(),
+            (String::from("100%-2025.jsonl"), false)
+        );
+        assert_eq!(
+            render("bad-%q.jsonl", now, 7).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            render("bad-%", now, 7).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn test_seq_resets_on_new_stamp() {
+        let tmpdir = assert_fs::TempDir::new().unwrap();
+        let pattern = tmpdir.path().join("events-%Y%m%d-%n.jsonl");
+        let builder = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap());
+        let day1 = UNIX_EPOCH + Duration::from_secs(20379 * 86400);
+        let day2 = day1 + Duration::from_secs(86400);
+        let (_, path, seq, stamp) = builder.create_file(day1, None).unwrap();
+        assert_eq!(path, tmpdir.path().join("events-20251018-0.jsonl"));
+        assert_eq!(seq, 0);
+        let (_, path, seq, stamp) = builder.create_file(day1, Some((&stamp, seq))).unwrap();
+        assert_eq!(path, tmpdir.path().join("events-20251018-1.jsonl"));
+        assert_eq!(seq, 1);
+        let (_, path, seq, _) = builder.create_file(day2, Some((&stamp, seq))).unwrap();
+        assert_eq!(path, tmpdir.path().join("events-20251019-0.jsonl"));
+        assert_eq!(seq, 0);
+    }
+}
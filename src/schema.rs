@@ -0,0 +1,578 @@
+#![cfg_attr(docsrs, doc(cfg(feature = "jsonschema")))]
+use crate::{JsonLinesReader, JsonLinesWriter};
+use jsonschema::Validator;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::fmt;
+use std::io::{BufRead, Error, ErrorKind, Result, Write};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A compiled [JSON Schema](https://json-schema.org) against which values
+/// read or written as JSON Lines can be validated.
+///
+/// Schemas are compiled & evaluated with the
+/// [`jsonschema`](https://docs.rs/jsonschema) crate; the draft is detected
+/// from the schema's `$schema` keyword, defaulting to draft 2020-12.  Remote
+/// `$ref`s are not resolved.
+///
+/// How invalid values are handled is determined by the schema's
+/// [`SchemaMode`]:
+///
+/// - In [`SchemaMode::FailFast`] mode (the default), validation of each
+///   value stops at its first violation, and reading or writing an invalid
+///   value results in an error.
+///
+/// - In [`SchemaMode::CollectAll`] mode, every violation in each value is
+///   reported, and [`JsonLinesSchemaIter`] & [`JsonLinesSchemaWriter`] skip
+///   invalid values, recording their violations for later inspection instead
+///   of failing.
+///
+/// Schemas are used with [`JsonLinesReader::read_validated()`],
+/// [`JsonLinesReader::read_all_validated()`], and [`JsonLinesSchemaWriter`].
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_json::json;
+/// use serde_jsonlines::{JsonLinesReader, JsonSchema, SchemaLocation, SchemaMode};
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// pub struct Order {
+///     pub sku: String,
+///     pub qty: u32,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let schema = JsonSchema::compile(&json!({
+///         "type": "object",
+///         "properties": {
+///             "sku": {"type": "string", "pattern": "^[A-Z]{3}-[0-9]+$"},
+///             "qty": {"type": "integer", "minimum": 1, "maximum": 100},
+///         },
+///         "required": ["sku", "qty"],
+///     }))?
+///     .mode(SchemaMode::CollectAll);
+///     let input = concat!(
+///         "{\"sku\": \"ABC-1\", \"qty\": 5}\n",
+///         "{\"sku\": \"abc\", \"qty\": 500}\n",
+///         "{\"sku\": \"XYZ-22\", \"qty\": 1}\n",
+///     );
+///     let mut iter = JsonLinesReader::new(input.as_bytes()).read_all_validated::<Order>(schema);
+///     let orders = iter.by_ref().collect::<std::io::Result<Vec<_>>>()?;
+///     assert_eq!(
+///         orders,
+///         [
+///             Order {
+///                 sku: "ABC-1".into(),
+///                 qty: 5
+///             },
+///             Order {
+///                 sku: "XYZ-22".into(),
+///                 qty: 1
+///             },
+///         ]
+///     );
+///     let errors = iter.violations();
+///     assert_eq!(errors.len(), 1);
+///     assert_eq!(errors[0].location, SchemaLocation::Line(2));
+///     let paths = errors[0]
+///         .violations
+///         .iter()
+///         .map(|v| v.instance_path.as_str())
+///         .collect::<Vec<_>>();
+///     assert_eq!(paths, ["/qty", "/sku"]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct JsonSchema {
+    validator: Arc<Validator>,
+    mode: SchemaMode,
+}
+
+impl JsonSchema {
+    /// Compile a JSON Schema
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if
+    /// `schema` is not a valid JSON Schema.
+    pub fn compile(schema: &Value) -> Result<Self> {
+        let validator = jsonschema::validator_for(schema).map_err(|e| {
+            Error::new(ErrorKind::InvalidInput, format!("invalid JSON Schema: {e}"))
+        })?;
+        Ok(JsonSchema::from_validator(validator))
+    }
+
+    /// Construct a `JsonSchema` from a [`jsonschema::Validator`], e.g., one
+    /// built with custom options
+    pub fn from_validator(validator: Validator) -> Self {
+        JsonSchema {
+            validator: Arc::new(validator),
+            mode: SchemaMode::default(),
+        }
+    }
+
+    /// Set how invalid values are handled.  The default is
+    /// [`SchemaMode::FailFast`].
+    pub fn mode(mut self, mode: SchemaMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns `true` if `value` is valid under the schema
+    pub fn is_valid(&self, value: &Value) -> bool {
+        self.validator.is_valid(value)
+    }
+
+    /// Validate a value that was read from (or is to be written to)
+    /// `location`.
+    ///
+    /// # Errors
+    ///
+    /// If `value` is not valid under the schema, returns a [`SchemaError`]
+    /// describing its first violation (in [`SchemaMode::FailFast`] mode) or
+    /// all of its violations (in [`SchemaMode::CollectAll`] mode).
+    pub fn validate(
+        &self,
+        value: &Value,
+        location: SchemaLocation,
+    ) -> std::result::Result<(), SchemaError> {
+        let violations = match self.mode {
+            SchemaMode::FailFast => self
+                .validator
+                .validate(value)
+                .err()
+                .into_iter()
+                .map(|e| SchemaViolation::new(&e))
+                .collect::<Vec<_>>(),
+            SchemaMode::CollectAll => self
+                .validator
+                .iter_errors(value)
+                .map(|e| SchemaViolation::new(&e))
+                .collect(),
+        };
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(SchemaError {
+                location,
+                violations,
+            })
+        }
+    }
+}
+
+impl fmt::Debug for JsonSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonSchema")
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+/// How invalid values are handled when validating against a [`JsonSchema`]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SchemaMode {
+    /// Report only the first violation in each value, and fail on the first
+    /// invalid value
+    #[default]
+    FailFast,
+    /// Report every violation in each value, and skip invalid values while
+    /// recording their violations
+    CollectAll,
+}
+
+/// An error describing why a single value failed validation against a
+/// [`JsonSchema`].
+///
+/// When validation fails while reading or writing, the resulting
+/// [`std::io::Error`] has kind [`std::io::ErrorKind::InvalidData`] and a
+/// `SchemaError` as its payload, which can be retrieved with
+/// [`std::io::Error::get_ref()`] and `downcast_ref()`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchemaError {
+    /// Where the value was read from or was to be written
+    pub location: SchemaLocation,
+    /// The schema violations found in the value; never empty
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.location)?;
+        for (i, v) in self.violations.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{v}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<SchemaError> for Error {
+    fn from(e: SchemaError) -> Error {
+        Error::new(ErrorKind::InvalidData, e)
+    }
+}
+
+/// The position of a value that failed validation against a [`JsonSchema`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SchemaLocation {
+    /// The value was read from the line with the given (1-based) number,
+    /// counting from where the reader started
+    Line(u64),
+    /// The value was passed to a [`JsonLinesSchemaWriter`] as the item with
+    /// the given (1-based) index, counting all values passed to the writer,
+    /// including ones that were skipped for being invalid
+    Item(u64),
+}
+
+impl fmt::Display for SchemaLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaLocation::Line(n) => write!(f, "line {n}"),
+            SchemaLocation::Item(n) => write!(f, "item {n}"),
+        }
+    }
+}
+
+/// A single way in which a value failed to match a [`JsonSchema`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchemaViolation {
+    /// A JSON Pointer to the part of the value that failed validation.  This
+    /// is the empty string if the value as a whole failed.
+    pub instance_path: String,
+    /// A JSON Pointer to the schema keyword that the value failed
+    pub schema_path: String,
+    /// A human-readable description of the violation
+    pub message: String,
+}
+
+impl SchemaViolation {
+    fn new(e: &jsonschema::ValidationError<'_>) -> Self {
+        SchemaViolation {
+            instance_path: e.instance_path().as_str().to_owned(),
+            schema_path: e.schema_path().as_str().to_owned(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at {:?})", self.message, self.instance_path)
+    }
+}
+
+impl<W> JsonLinesWriter<W> {
+    /// Consume the `JsonLinesWriter` and return a [`JsonLinesSchemaWriter`]
+    /// that validates values against `schema` before writing them with this
+    /// writer's settings
+    pub fn with_schema(self, schema: JsonSchema) -> JsonLinesSchemaWriter<W> {
+        JsonLinesSchemaWriter {
+            inner: self,
+            schema,
+            items: 0,
+            violations: Vec::new(),
+        }
+    }
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    /// Read a line of JSON from the underlying reader, validate it against
+    /// `schema`, and deserialize it as a value of type `T`.
+    ///
+    /// If end-of-file is reached, this method returns `Ok(None)`.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`read()`][JsonLinesReader::read].
+    /// Also returns an error of kind [`std::io::ErrorKind::InvalidData`] with
+    /// a [`SchemaError`] payload if the line is not valid under `schema`.  As
+    /// with `read()`, reading can continue with the next line after such an
+    /// error.
+    pub fn read_validated<T>(&mut self, schema: &JsonSchema) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        match self.read_checked(schema)? {
+            Some(r) => Ok(Some(r?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Like `read_validated()`, but with schema violations returned
+    /// separately from other errors
+    fn read_checked<T>(
+        &mut self,
+        schema: &JsonSchema,
+    ) -> Result<Option<std::result::Result<T, SchemaError>>>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.read_line()? {
+            return Ok(None);
+        }
+        let r = self
+            .lines
+            .decode_seed(&self.buf, PhantomData::<Value>)
+            .and_then(|value| {
+                match schema.validate(&value, SchemaLocation::Line(self.lines.lineno)) {
+                    Ok(()) => self.lines.decode_from(&self.buf, value).map(Ok),
+                    Err(e) => Ok(Err(e)),
+                }
+            });
+        self.buf.clear();
+        r.map(Some)
+    }
+}
+
+impl<R> JsonLinesReader<R> {
+    /// Consume the `JsonLinesReader` and return an iterator over the values
+    /// of type `T` deserialized from each line after validating it against
+    /// `schema`.
+    ///
+    /// The returned iterator has an `Item` type of `std::io::Result<T>`.  Each
+    /// call to `next()` has the same error conditions as
+    /// [`read_validated()`][JsonLinesReader::read_validated], except that, if
+    /// the schema is in [`SchemaMode::CollectAll`] mode, lines that are not
+    /// valid under the schema are skipped, and their violations can be
+    /// retrieved with [`JsonLinesSchemaIter::violations()`].
+    pub fn read_all_validated<T>(self, schema: JsonSchema) -> JsonLinesSchemaIter<R, T> {
+        JsonLinesSchemaIter {
+            reader: self,
+            schema,
+            violations: Vec::new(),
+            _output: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`] value `R` that validates each
+/// line against a [`JsonSchema`] and decodes it as JSON of type `T`.
+///
+/// This iterator yields items of type `Result<T, std::io::Error>`.  Errors
+/// occur under the same conditions as for
+/// [`JsonLinesReader::read_validated()`], except that schema violations are
+/// collected rather than returned when the schema is in
+/// [`SchemaMode::CollectAll`] mode.
+///
+/// Iterators of this type are returned by
+/// [`JsonLinesReader::read_all_validated()`].
+#[derive(Clone, Debug)]
+pub struct JsonLinesSchemaIter<R, T> {
+    reader: JsonLinesReader<R>,
+    schema: JsonSchema,
+    violations: Vec<SchemaError>,
+    _output: PhantomData<T>,
+}
+
+impl<R, T> JsonLinesSchemaIter<R, T> {
+    /// Consume the `JsonLinesSchemaIter` and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+
+    /// Get a reference to the schema
+    pub fn schema(&self) -> &JsonSchema {
+        &self.schema
+    }
+
+    /// Return the errors for the lines that have been skipped so far for
+    /// failing validation.  This is always empty in [`SchemaMode::FailFast`]
+    /// mode.
+    pub fn violations(&self) -> &[SchemaError] {
+        &self.violations
+    }
+}
+
+impl<R, T> Iterator for JsonLinesSchemaIter<R, T>
+where
+    R: BufRead,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        loop {
+            match self.reader.read_checked(&self.schema) {
+                Ok(Some(Err(e))) if self.schema.mode == SchemaMode::CollectAll => {
+                    self.violations.push(e);
+                }
+                Ok(Some(r)) => return Some(r.map_err(Error::from)),
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// A structure for writing values as JSON Lines after validating them
+/// against a [`JsonSchema`].
+///
+/// A `JsonLinesSchemaWriter` is created either with
+/// [`JsonLinesSchemaWriter::new()`] or, in order to keep the settings of an
+/// already-configured [`JsonLinesWriter`] (such as its
+/// [line terminator][JsonLinesWriter::line_terminator]), with
+/// [`JsonLinesWriter::with_schema()`].
+///
+/// Each value passed to [`write()`][JsonLinesSchemaWriter::write] is
+/// serialized and validated before being written via the [`JsonLinesWriter`].
+/// In [`SchemaMode::FailFast`] mode, writing an invalid value results in an
+/// error; in [`SchemaMode::CollectAll`] mode, invalid values are skipped, and
+/// their violations can be retrieved with
+/// [`violations()`][JsonLinesSchemaWriter::violations].  The
+/// [`location`][SchemaError::location] of each error is a
+/// [`SchemaLocation::Item`] giving the (1-based) index of the offending value
+/// among all values passed to the writer.
+///
+/// # Example
+///
+/// ```
+/// use serde_json::json;
+/// use serde_jsonlines::{JsonLinesSchemaWriter, JsonSchema, SchemaError, SchemaLocation};
+///
+/// fn main() -> std::io::Result<()> {
+///     let schema = JsonSchema::compile(&json!({
+///         "type": "object",
+///         "properties": {"status": {"enum": ["open", "closed"]}},
+///     }))?;
+///     let mut writer = JsonLinesSchemaWriter::new(Vec::new(), schema);
+///     writer.write(&json!({"status": "open"}))?;
+///     let e = writer.write(&json!({"status": "pending"})).unwrap_err();
+///     let e = e.get_ref().unwrap().downcast_ref::<SchemaError>().unwrap();
+///     assert_eq!(e.location, SchemaLocation::Item(2));
+///     assert_eq!(e.violations[0].instance_path, "/status");
+///     assert_eq!(writer.into_inner(), b"{\"status\":\"open\"}\n");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct JsonLinesSchemaWriter<W> {
+    inner: JsonLinesWriter<W>,
+    schema: JsonSchema,
+    /// The number of values passed to `write()` so far
+    items: u64,
+    violations: Vec<SchemaError>,
+}
+
+impl<W> JsonLinesSchemaWriter<W> {
+    /// Construct a new `JsonLinesSchemaWriter` that writes values that are
+    /// valid under `schema` to `writer` with the default
+    /// [`JsonLinesWriter`] settings
+    pub fn new(writer: W, schema: JsonSchema) -> Self {
+        JsonLinesWriter::new(writer).with_schema(schema)
+    }
+
+    /// Consume the `JsonLinesSchemaWriter` and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.inner.into_inner()
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying writer
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.get_mut()
+    }
+
+    /// Get a reference to the schema
+    pub fn schema(&self) -> &JsonSchema {
+        &self.schema
+    }
+
+    /// Return the errors for the values that have been skipped so far for
+    /// failing validation.  This is always empty in [`SchemaMode::FailFast`]
+    /// mode.
+    pub fn violations(&self) -> &[SchemaError] {
+        &self.violations
+    }
+}
+
+impl<W: Write> JsonLinesSchemaWriter<W> {
+    /// Serialize a value, validate it against the schema, and, if it is
+    /// valid, write it as a line of JSON.  Returns `true` if the value was
+    /// written or `false` if it was skipped for being invalid.
+    ///
+    /// This method does not flush.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`serde_json::to_value()`] and
+    /// [`JsonLinesWriter::write()`].  In [`SchemaMode::FailFast`] mode, also
+    /// returns an error of kind [`std::io::ErrorKind::InvalidData`] with a
+    /// [`SchemaError`] payload if the value is not valid under the schema, in
+    /// which case nothing is written.
+    pub fn write<T>(&mut self, value: &T) -> Result<bool>
+    where
+        T: ?Sized + Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        self.items += 1;
+        match self
+            .schema
+            .validate(&value, SchemaLocation::Item(self.items))
+        {
+            Ok(()) => {
+                self.inner.write(&value)?;
+                Ok(true)
+            }
+            Err(e) if self.schema.mode == SchemaMode::CollectAll => {
+                self.violations.push(e);
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Validate each item in an iterator and write the valid ones as lines of
+    /// JSON, returning the number of lines written.
+    ///
+    /// This method does not flush.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as
+    /// [`write()`][JsonLinesSchemaWriter::write].
+    pub fn write_all<T, I>(&mut self, items: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = T>,
+        T: Serialize,
+    {
+        let mut written = 0;
+        for value in items {
+            if self.write(&value)? {
+                written += 1;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Flush the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::io::Write::flush()`].
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
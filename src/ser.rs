@@ -0,0 +1,312 @@
+use serde::Serialize;
+use serde::ser::{self, Impossible, SerializeSeq, SerializeTuple, SerializeTupleStruct};
+use std::io::Write;
+
+/// A [`serde::Serializer`] that serializes a sequence as a JSON Lines
+/// document, with each element serialized as one line.
+///
+/// This allows any value that serializes as a sequence, such as a `Vec<T>`,
+/// a slice, a set, or a tuple, to be written as JSON Lines, including via
+/// generic code that is parameterized over serde formats.  Attempting to
+/// serialize a value that is not a sequence (other than a newtype struct
+/// wrapping a sequence) results in an error.
+///
+/// Unlike [`JsonLinesWriter`][crate::JsonLinesWriter], this type's
+/// [`serde::Serializer`] implementation uses [`serde_json::Error`] as its
+/// error type; I/O errors are wrapped with [`serde_json::Error::io()`].
+///
+/// # Example
+///
+/// ```
+/// use serde::Serialize;
+/// use serde_jsonlines::Serializer;
+///
+/// #[derive(Serialize)]
+/// pub struct Point {
+///     pub x: i32,
+///     pub y: i32,
+/// }
+///
+/// fn main() -> Result<(), serde_json::Error> {
+///     let mut ser = Serializer::new(Vec::new());
+///     [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }].serialize(&mut ser)?;
+///     assert_eq!(ser.into_inner(), b"{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n");
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Serializer<W> {
+    inner: W,
+}
+
+impl<W> Serializer<W> {
+    /// Construct a new `Serializer` from a [`std::io::Write`] instance
+    pub fn new(writer: W) -> Self {
+        Serializer { inner: writer }
+    }
+
+    /// Consume the `Serializer` and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+macro_rules! not_a_sequence {
+    ($($method:ident($($arg:ty),*);)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<Self::Ok, Self::Error> {
+                Err(not_a_sequence())
+            }
+        )*
+    };
+}
+
+impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+    type SerializeSeq = SeqSerializer<'a, W>;
+    type SerializeTuple = SeqSerializer<'a, W>;
+    type SerializeTupleStruct = SeqSerializer<'a, W>;
+    type SerializeTupleVariant = Impossible<(), serde_json::Error>;
+    type SerializeMap = Impossible<(), serde_json::Error>;
+    type SerializeStruct = Impossible<(), serde_json::Error>;
+    type SerializeStructVariant = Impossible<(), serde_json::Error>;
+
+    not_a_sequence! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_a_sequence())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_a_sequence())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SeqSerializer { ser: self })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(SeqSerializer { ser: self })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(SeqSerializer { ser: self })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(not_a_sequence())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(not_a_sequence())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(not_a_sequence())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(not_a_sequence())
+    }
+}
+
+/// The type returned by [`Serializer`]'s [`serde::Serializer`] implementation
+/// for serializing the elements of a sequence
+#[derive(Debug)]
+pub struct SeqSerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
+}
+
+impl<W: Write> SeqSerializer<'_, W> {
+    fn write_line<T>(&mut self, value: &T) -> Result<(), serde_json::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        serde_json::to_writer(&mut self.ser.inner, value)?;
+        self.ser
+            .inner
+            .write_all(b"\n")
+            .map_err(serde_json::Error::io)
+    }
+}
+
+impl<W: Write> SerializeSeq for SeqSerializer<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.write_line(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<W: Write> SerializeTuple for SeqSerializer<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.write_line(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<W: Write> SerializeTupleStruct for SeqSerializer<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.write_line(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+fn not_a_sequence() -> serde_json::Error {
+    ser::Error::custom("only sequences can be serialized as JSON Lines documents")
+}
+
+/// Serialize a sequence as a JSON Lines document and write it to a
+/// [`std::io::Write`] instance.
+///
+/// See [`Serializer`] for more information.  This function does not flush.
+///
+/// # Errors
+///
+/// Fails if `value` is not a sequence or if serializing any element fails,
+/// in which case the returned [`std::io::Error`] has a [`serde_json::Error`]
+/// value as its payload.  Also has the same error conditions as
+/// [`std::io::Write::write_all()`].
+///
+/// Note that, if an error occurs partway through, the lines for the
+/// preceding elements will already have been written.
+pub fn to_writer<W, T>(writer: W, value: &T) -> std::io::Result<()>
+where
+    W: Write,
+    T: ?Sized + Serialize,
+{
+    let mut ser = Serializer::new(writer);
+    value.serialize(&mut ser).map_err(Into::into)
+}
+
+/// Serialize a sequence as a JSON Lines document in a byte vector.
+///
+/// See [`Serializer`] for more information.
+///
+/// # Errors
+///
+/// Fails if `value` is not a sequence or if serializing any element fails,
+/// in which case the returned [`std::io::Error`] has a [`serde_json::Error`]
+/// value as its payload.
+pub fn to_vec<T>(value: &T) -> std::io::Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let mut buf = Vec::new();
+    to_writer(&mut buf, value)?;
+    Ok(buf)
+}
+
+/// Serialize a sequence as a JSON Lines document in a string.
+///
+/// See [`Serializer`] for more information.
+///
+/// # Errors
+///
+/// Has the same error conditions as [`to_vec()`].
+///
+/// # Example
+///
+/// ```
+/// use serde_jsonlines::to_string;
+///
+/// fn main() -> std::io::Result<()> {
+///     assert_eq!(to_string(&(42, "foo"))?, "42\n\"foo\"\n");
+///     Ok(())
+/// }
+/// ```
+pub fn to_string<T>(value: &T) -> std::io::Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let buf = to_vec(value)?;
+    // serde_json only ever produces valid UTF-8
+    String::from_utf8(buf).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
@@ -0,0 +1,30 @@
+//! Looking up individual fields of a line of JSON without deserializing the
+//! rest of the line
+use crate::Projection;
+use serde_json::Value;
+use std::io::{Error, ErrorKind, Result};
+
+/// Extract the value at the sole pointer of `projection` from a line of JSON
+pub(crate) fn field(projection: &Projection, line: &str) -> Result<Option<Value>> {
+    Ok(projection.extract_values(line)?.pop().flatten())
+}
+
+/// Extract the schema version at the sole pointer of `projection` from a
+/// line of JSON, falling back to `default` if there is no value there
+pub(crate) fn version(projection: &Projection, line: &str, default: Option<u64>) -> Result<u64> {
+    let pointer = projection.pointers().first().map_or("", String::as_str);
+    match field(projection, line)? {
+        Some(v) => v.as_u64().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid schema version at {pointer}: {v}"),
+            )
+        }),
+        None => default.ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("no schema version found at {pointer}"),
+            )
+        }),
+    }
+}
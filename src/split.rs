@@ -0,0 +1,280 @@
+use crate::JsonLinesReader;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Result, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+/// Split the file at `path` into `n` contiguous byte ranges whose boundaries
+/// all fall at the starts of lines.
+///
+/// The file is first divided into `n` ranges of (nearly) equal size, and then
+/// each boundary between ranges is moved forwards to the start of the next
+/// line (or to the end of the file).  The resulting ranges cover the whole
+/// file without overlapping, and each line of the file starts in exactly one
+/// range.  Some ranges may be empty if the file contains fewer than `n`
+/// lines or if some lines are very long.
+///
+/// The ranges can then be passed to [`JsonLinesReader::with_range()`] in order
+/// to read each range's lines independently, e.g., in separate threads or
+/// processes.
+///
+/// # Errors
+///
+/// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if `n` is
+/// zero.  Also has the same error conditions as [`File::open()`] and
+/// [`split_reader()`].
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde_jsonlines::{split_file, JsonLinesReader};
+/// use std::fs::File;
+/// use std::io::BufReader;
+/// use std::thread;
+///
+/// #[derive(Deserialize)]
+/// pub struct Event {
+///     pub id: u64,
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let splits = split_file("events.jsonl", 4)?;
+///     let total = thread::scope(|s| {
+///         let workers = splits
+///             .into_iter()
+///             .map(|range| {
+///                 s.spawn(move || {
+///                     let fp = BufReader::new(File::open("events.jsonl")?);
+///                     let reader = JsonLinesReader::with_range(fp, range)?;
+///                     let mut count = 0;
+///                     for r in reader.read_all::<Event>() {
+///                         r?;
+///                         count += 1;
+///                     }
+///                     Ok::<usize, std::io::Error>(count)
+///                 })
+///             })
+///             .collect::<Vec<_>>();
+///         workers
+///             .into_iter()
+///             .map(|w| w.join().expect("worker should not panic"))
+///             .sum::<std::io::Result<usize>>()
+///     })?;
+///     println!("Read {total} events");
+///     Ok(())
+/// }
+/// ```
+pub fn split_file<P: AsRef<Path>>(path: P, n: usize) -> Result<Vec<Range<u64>>> {
+    if n == 0 {
+        // Check before opening the file so that the error is the same
+        // regardless of whether the file exists
+        return Err(zero_splits());
+    }
+    split_reader(&mut BufReader::new(File::open(path)?), n)
+}
+
+/// Split the contents of a seekable reader into `n` contiguous byte ranges
+/// whose boundaries all fall at the starts of lines.
+///
+/// See [`split_file()`] for more information.  The reader's position after
+/// this function returns is unspecified.
+///
+/// # Errors
+///
+/// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if `n` is
+/// zero.  Also has the same error conditions as
+/// [`std::io::Seek::seek()`] and [`std::io::BufRead::fill_buf()`].
+pub fn split_reader<R: BufRead + Seek>(reader: &mut R, n: usize) -> Result<Vec<Range<u64>>> {
+    if n == 0 {
+        return Err(zero_splits());
+    }
+    let size = reader.seek(SeekFrom::End(0))?;
+    let n64 =
+        u64::try_from(n).map_err(|_| Error::new(ErrorKind::InvalidInput, "too many splits"))?;
+    let mut boundaries = Vec::with_capacity(n + 1);
+    boundaries.push(0);
+    let mut prev = 0;
+    for i in 1..n64 {
+        let nominal =
+            u64::try_from(u128::from(size) * u128::from(i) / u128::from(n64)).unwrap_or(size);
+        let mut boundary = nominal.max(prev);
+        if boundary > 0 && boundary < size {
+            reader.seek(SeekFrom::Start(boundary - 1))?;
+            boundary = boundary - 1 + skip_line(reader)?;
+        }
+        boundaries.push(boundary);
+        prev = boundary;
+    }
+    boundaries.push(size);
+    Ok(boundaries
+        .iter()
+        .zip(boundaries.iter().skip(1))
+        .map(|(&start, &end)| start..end)
+        .collect())
+}
+
+impl<R: BufRead + Seek> JsonLinesReader<SplitReader<R>> {
+    /// Construct a new `JsonLinesReader` that only reads the lines of
+    /// `reader` that start within the byte range `range`.
+    ///
+    /// If `range.start` is not at the start of a line, the partial line at
+    /// the start of the range is skipped, as it belongs to the previous
+    /// range.  Conversely, the last line that starts within the range is read
+    /// in full, even if it extends past `range.end`.  Thus, if a file is
+    /// split into contiguous, non-overlapping ranges (such as those returned
+    /// by [`split_file()`]), every line of the file will be read by exactly
+    /// one of the ranges' readers.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`SplitReader::new()`].
+    pub fn with_range(reader: R, range: Range<u64>) -> Result<Self> {
+        // A byte order mark can only occur at the start of the input
+        let at_start = range.start == 0;
+        let mut jsonl = JsonLinesReader::new(SplitReader::new(reader, range)?);
+        jsonl.lines.at_start = at_start;
+        Ok(jsonl)
+    }
+}
+
+/// A [`std::io::BufRead`] adapter that yields only the lines of the
+/// underlying reader that start within a given byte range.
+///
+/// See [`JsonLinesReader::with_range()`] for more information.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitReader<R> {
+    inner: R,
+    pos: u64,
+    end: u64,
+    at_line_start: bool,
+}
+
+impl<R: BufRead + Seek> SplitReader<R> {
+    /// Construct a new `SplitReader` that yields the lines of `reader` that
+    /// start within the byte range `range`.
+    ///
+    /// This seeks `reader` to the first line starting at or after
+    /// `range.start`.
+    ///
+    /// # Errors
+    ///
+    /// Has the same error conditions as [`std::io::Seek::seek()`] and
+    /// [`std::io::BufRead::fill_buf()`].
+    pub fn new(mut reader: R, range: Range<u64>) -> Result<Self> {
+        let pos = if range.start > 0 {
+            // If the byte before the range is a newline, the range starts at
+            // the start of a line, and skip_line() will consume just that
+            // newline.
+            reader.seek(SeekFrom::Start(range.start - 1))?;
+            range.start - 1 + skip_line(&mut reader)?
+        } else {
+            reader.seek(SeekFrom::Start(0))?;
+            0
+        };
+        Ok(SplitReader {
+            inner: reader,
+            pos,
+            end: range.end,
+            at_line_start: true,
+        })
+    }
+}
+
+impl<R> SplitReader<R> {
+    /// Consume the `SplitReader` and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying reader
+    ///
+    /// Reading from or seeking the underlying reader directly will cause the
+    /// `SplitReader` to lose track of its position.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Return the current byte offset of the `SplitReader` in the underlying
+    /// reader
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+}
+
+impl<R: BufRead> Read for SplitReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for SplitReader<R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.pos >= self.end && self.at_line_start {
+            return Ok(&[]);
+        }
+        let (pos, end) = (self.pos, self.end);
+        let buf = self.inner.fill_buf()?;
+        if pos < end {
+            let remaining = usize::try_from(end - pos).unwrap_or(usize::MAX);
+            Ok(&buf[..buf.len().min(remaining)])
+        } else {
+            // Finish the line that started within the range
+            match buf.iter().position(|&b| b == b'\n') {
+                Some(i) => Ok(&buf[..=i]),
+                None => Ok(buf),
+            }
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if amt == 0 {
+            return;
+        }
+        // This does not perform any I/O, as `fill_buf()` must have been
+        // called beforehand in order for `amt` to be nonzero.
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.at_line_start = buf.get(amt - 1) == Some(&b'\n');
+        }
+        self.inner.consume(amt);
+        self.pos += u64::try_from(amt).unwrap_or(u64::MAX);
+    }
+}
+
+/// Consume bytes from `reader` up to & including the next newline (or up to
+/// end of file), returning the number of bytes consumed
+fn skip_line<R: BufRead>(reader: &mut R) -> Result<u64> {
+    let mut skipped = 0;
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if buf.is_empty() {
+            return Ok(skipped);
+        }
+        let (amt, done) = match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (buf.len(), false),
+        };
+        reader.consume(amt);
+        skipped += u64::try_from(amt).unwrap_or(u64::MAX);
+        if done {
+            return Ok(skipped);
+        }
+    }
+}
+
+fn zero_splits() -> Error {
+    Error::new(ErrorKind::InvalidInput, "number of splits must be nonzero")
+}
@@ -0,0 +1,238 @@
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Result};
+use std::path::Path;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Check that the contents of a reader conform to the [JSON Lines
+/// format](https://jsonlines.org) and return a report of all problems found.
+///
+/// The following rules are checked:
+///
+/// - The input must be encoded in UTF-8.
+/// - The input must not begin with a byte order mark.
+/// - Each line must contain exactly one JSON value; blank lines are not
+///   allowed.
+/// - Lines must be terminated by `\n`.  Lines terminated by `\r\n` are
+///   tolerated (as JSON parsers treat `\r` as whitespace) but are still
+///   reported, with a [`ViolationKind`] for which
+///   [`is_tolerated()`][ViolationKind::is_tolerated] returns `true`.
+/// - The final line may or may not be terminated; whether it is is recorded
+///   in [`ValidationReport::final_newline`].  An unterminated final line
+///   that ends in a bare `\r` is tolerated but reported.
+///
+/// Unlike reading the input with [`JsonLinesReader`][crate::JsonLinesReader],
+/// validation does not stop at the first bad line; every line is checked.
+///
+/// # Errors
+///
+/// Only I/O errors from reading `reader` are returned as errors; problems
+/// with the contents of the input are recorded in the report.
+///
+/// # Example
+///
+/// ```
+/// use serde_jsonlines::{validate, ViolationKind};
+///
+/// fn main() -> std::io::Result<()> {
+///     let input = &b"{\"x\": 1}\r\n\n[1, 2] 3\n"[..];
+///     let report = validate(input)?;
+///     assert!(!report.is_valid());
+///     assert_eq!(report.lines, 3);
+///     assert_eq!(report.values, 1);
+///     let kinds = report.violations.iter().map(|v| (v.line, v.kind)).collect::<Vec<_>>();
+///     assert_eq!(
+///         kinds,
+///         [
+///             (1, ViolationKind::CrLf),
+///             (2, ViolationKind::EmptyLine),
+///             (3, ViolationKind::TrailingCharacters),
+///         ]
+///     );
+///     Ok(())
+/// }
+/// ```
+pub fn validate<R: BufRead>(mut reader: R) -> Result<ValidationReport> {
+    let mut report = ValidationReport::default();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        report.lines += 1;
+        report.final_newline = buf.last() == Some(&b'\n');
+        report.check_line(&buf);
+    }
+    Ok(report)
+}
+
+/// Check that the file at the given path conforms to the [JSON Lines
+/// format](https://jsonlines.org) and return a report of all problems found.
+///
+/// See [`validate()`] for the rules that are checked.
+///
+/// # Errors
+///
+/// Has the same error conditions as [`File::open()`] and [`validate()`].
+pub fn validate_file<P: AsRef<Path>>(path: P) -> Result<ValidationReport> {
+    validate(BufReader::new(File::open(path)?))
+}
+
+/// A report of the problems found by [`validate()`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidationReport {
+    /// The total number of lines in the input
+    pub lines: u64,
+    /// The number of lines that contained a single valid JSON value
+    pub values: u64,
+    /// Whether the last line of the input was terminated by a newline.  This
+    /// is `false` if the input was empty.
+    pub final_newline: bool,
+    /// The problems found, in the order in which they occurred
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no violations were found other than tolerated ones
+    pub fn is_valid(&self) -> bool {
+        self.violations.iter().all(|v| v.kind.is_tolerated())
+    }
+
+    /// Return the number of violations of the given kind
+    pub fn count(&self, kind: ViolationKind) -> usize {
+        self.violations.iter().filter(|v| v.kind == kind).count()
+    }
+
+    fn check_line(&mut self, mut line: &[u8]) {
+        let lineno = self.lines;
+        // 1-based byte column of the first byte of `line` in the original
+        // line
+        let mut offset = 1;
+        if lineno == 1
+            && let Some(rest) = line.strip_prefix(BOM)
+        {
+            self.push(lineno, 1, ViolationKind::ByteOrderMark, None);
+            line = rest;
+            offset += BOM.len();
+        }
+        if let Some(rest) = line.strip_suffix(b"\n") {
+            line = rest;
+            if let Some(rest) = line.strip_suffix(b"\r") {
+                line = rest;
+                self.push(lineno, offset + line.len(), ViolationKind::CrLf, None);
+            }
+        } else if let Some(rest) = line.strip_suffix(b"\r") {
+            line = rest;
+            self.push(lineno, offset + line.len(), ViolationKind::LoneCr, None);
+        }
+        let text = match std::str::from_utf8(line) {
+            Ok(text) => text,
+            Err(e) => {
+                let column = offset + e.valid_up_to();
+                self.push(lineno, column, ViolationKind::InvalidUtf8, None);
+                return;
+            }
+        };
+        if text.trim_matches([' ', '\t', '\n', '\r']).is_empty() {
+            self.push(lineno, offset, ViolationKind::EmptyLine, None);
+            return;
+        }
+        let mut de = serde_json::Deserializer::from_str(text);
+        let (kind, e) = match IgnoredAny::deserialize(&mut de) {
+            Ok(_) => match de.end() {
+                Ok(()) => {
+                    self.values += 1;
+                    return;
+                }
+                Err(e) => (ViolationKind::TrailingCharacters, e),
+            },
+            Err(e) => (ViolationKind::InvalidJson, e),
+        };
+        let position = format!(" at line {} column {}", e.line(), e.column());
+        let msg = e.to_string();
+        let msg = msg.strip_suffix(&position).unwrap_or(&msg).to_owned();
+        self.push(lineno, offset - 1 + e.column().max(1), kind, Some(msg));
+    }
+
+    fn push(&mut self, line: u64, column: usize, kind: ViolationKind, message: Option<String>) {
+        self.violations.push(Violation {
+            line,
+            column,
+            kind,
+            message: message.unwrap_or_else(|| kind.to_string()),
+        });
+    }
+}
+
+/// A single problem found by [`validate()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Violation {
+    /// The (1-based) number of the line on which the problem occurred
+    pub line: u64,
+    /// The (1-based) byte offset within the line at which the problem
+    /// occurred
+    pub column: usize,
+    /// The kind of problem
+    pub kind: ViolationKind,
+    /// A human-readable description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// The kinds of problems that can be found by [`validate()`]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum ViolationKind {
+    /// The line is not valid UTF-8
+    InvalidUtf8,
+    /// The input begins with a UTF-8 byte order mark
+    ByteOrderMark,
+    /// The line is empty or contains only whitespace
+    EmptyLine,
+    /// The line is not valid JSON
+    InvalidJson,
+    /// The line contains a valid JSON value followed by further non-whitespace
+    /// characters, such as a second value
+    TrailingCharacters,
+    /// The line is terminated by `\r\n` rather than `\n`.  This is tolerated.
+    CrLf,
+    /// The final line ends in a `\r` that is not followed by `\n`.  This is
+    /// tolerated.
+    LoneCr,
+}
+
+impl ViolationKind {
+    /// Returns `true` if violations of this kind do not prevent the input
+    /// from being read by [`JsonLinesReader`][crate::JsonLinesReader]
+    pub fn is_tolerated(self) -> bool {
+        matches!(self, ViolationKind::CrLf | ViolationKind::LoneCr)
+    }
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ViolationKind::InvalidUtf8 => "invalid UTF-8",
+            ViolationKind::ByteOrderMark => "byte order mark at start of input",
+            ViolationKind::EmptyLine => "empty line",
+            ViolationKind::InvalidJson => "invalid JSON",
+            ViolationKind::TrailingCharacters => "trailing characters after JSON value",
+            ViolationKind::CrLf => "line terminated by CR LF",
+            ViolationKind::LoneCr => "line ends in a bare CR",
+        };
+        f.write_str(s)
+    }
+}
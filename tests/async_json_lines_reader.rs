@@ -269,3 +269,170 @@ async fn test_read_all_invalid_json() {
     );
     assert!(items.next().await.is_none());
 }
+
+#[tokio::test]
+async fn test_read_seed() {
+    let mut reader = AsyncJsonLinesReader::new(&b"{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n"[..]);
+    assert_eq!(
+        reader.read_seed(Scaled(10)).await.unwrap(),
+        Some(Point { x: 10, y: 20 })
+    );
+    assert_eq!(
+        reader.read_seed(Scaled(-1)).await.unwrap(),
+        Some(Point { x: -3, y: -4 })
+    );
+    assert_eq!(reader.read_seed(Scaled(10)).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_read_all_seeded() {
+    let text = "{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\nnot json\n{\"x\":5,\"y\":6}\n";
+    let mut factor = 0;
+    let mut stream = AsyncJsonLinesReader::new(text.as_bytes()).read_all_seeded(|| {
+        factor += 1;
+        Scaled(factor)
+    });
+    assert_eq!(stream.next().await.unwrap().unwrap(), Point { x: 1, y: 2 });
+    assert_eq!(stream.next().await.unwrap().unwrap(), Point { x: 6, y: 8 });
+    let e = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        stream.next().await.unwrap().unwrap(),
+        Point { x: 20, y: 24 }
+    );
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_read_into() {
+    let mut reader = AsyncJsonLinesReader::new(&b"[1,2,3,4,5,6,7,8]\n[9]\nnot json\n[10,11]\n"[..]);
+    let mut v = Vec::<i32>::new();
+    assert!(reader.read_into(&mut v).await.unwrap());
+    assert_eq!(v, [1, 2, 3, 4, 5, 6, 7, 8]);
+    let ptr = v.as_ptr();
+    assert!(reader.read_into(&mut v).await.unwrap());
+    assert_eq!(v, [9]);
+    let e = reader.read_into(&mut v).await.unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(reader.read_into(&mut v).await.unwrap());
+    assert_eq!(v, [10, 11]);
+    assert_eq!(v.as_ptr(), ptr);
+    assert!(!reader.read_into(&mut v).await.unwrap());
+    assert_eq!(v, [10, 11]);
+}
+
+#[tokio::test]
+async fn test_read_strip_bom() {
+    let input = &b"\xEF\xBB\xBF{\"x\": 1, \"y\": 2}\n\xEF\xBB\xBF{\"x\": 3, \"y\": 4}\n"[..];
+    let mut reader = AsyncJsonLinesReader::new(input).strip_bom(true);
+    assert_eq!(
+        reader.read::<Point>().await.unwrap(),
+        Some(Point { x: 1, y: 2 })
+    );
+    // Only a BOM at the start of the input is stripped
+    let e = reader.read::<Point>().await.unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(reader.read::<Point>().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_read_all_strip_bom() {
+    let input = &b"\xEF\xBB\xBF{\"x\": 1, \"y\": 2}\n{\"x\": 3, \"y\": 4}\n"[..];
+    let items = AsyncJsonLinesReader::new(input)
+        .strip_bom(true)
+        .read_all::<Point>()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(items, [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+}
+
+#[tokio::test]
+async fn test_read_strict_line_endings() {
+    let input = &b"{\"x\": 1, \"y\": 2}\r\n{\"x\": 3,\r \"y\": 4}\n{\"x\": 5, \"y\": 6}\n"[..];
+    let mut reader = AsyncJsonLinesReader::new(input).strict_line_endings(true);
+    let e = reader.read::<Point>().await.unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "line terminated by CR LF");
+    let e = reader.read::<Point>().await.unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "carriage return in line");
+    assert_eq!(
+        reader.read::<Point>().await.unwrap(),
+        Some(Point { x: 5, y: 6 })
+    );
+    assert_eq!(reader.read::<Point>().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_read_all_strict_line_endings() {
+    let input = &b"{\"x\": 1, \"y\": 2}\n{\"x\": 3, \"y\": 4}\r\n"[..];
+    let mut stream = AsyncJsonLinesReader::new(input)
+        .strict_line_endings(true)
+        .read_all::<Point>();
+    assert_eq!(stream.next().await.unwrap().unwrap(), Point { x: 1, y: 2 });
+    let e = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_read_all_crlf_small_buffer() {
+    let input = &b"{\"x\": 1, \"y\": 2}\r\n{\"x\": 3, \"y\": 4}\r\n{\"x\": 5, \"y\": 6}"[..];
+    let items = AsyncJsonLinesReader::new(BufReader::with_capacity(3, input))
+        .read_all::<Point>()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            Point { x: 1, y: 2 },
+            Point { x: 3, y: 4 },
+            Point { x: 5, y: 6 }
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_read_invalid_utf8() {
+    let mut reader = AsyncJsonLinesReader::new(&b"\"\xFF\"\n{\"x\": 1, \"y\": 2}\n"[..]);
+    let e = reader.read::<String>().await.unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        reader.read::<Point>().await.unwrap(),
+        Some(Point { x: 1, y: 2 })
+    );
+}
+
+#[tokio::test]
+async fn test_read_with_header() {
+    let input = concat!(
+        "{\"name\": \"Foo Bar\", \"size\": 42, \"on\": true}\n",
+        "{\"x\": 1, \"y\": 2}\n",
+        "{\"x\": 3, \"y\": 4}\n",
+    );
+    let (header, stream) = AsyncJsonLinesReader::new(input.as_bytes())
+        .read_with_header::<Structure, Point>()
+        .await
+        .unwrap();
+    assert_eq!(
+        header,
+        Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        }
+    );
+    let items = stream.try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(items, [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+}
+
+#[tokio::test]
+async fn test_read_with_header_empty() {
+    let e = AsyncJsonLinesReader::new(&b""[..])
+        .read_with_header::<Point, Structure>()
+        .await
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::UnexpectedEof);
+}
@@ -4,7 +4,7 @@ use crate::common::*;
 use assert_fs::NamedTempFile;
 use assert_fs::assert::PathAssert;
 use futures_util::{SinkExt, stream::empty};
-use serde_jsonlines::AsyncJsonLinesWriter;
+use serde_jsonlines::{AsyncJsonLinesWriter, LineTerminator};
 use std::io::SeekFrom;
 use std::pin::Pin;
 use tokio::fs::File;
@@ -204,3 +204,54 @@ async fn test_feed_into_sink() {
         "{\"name\":\"Gnusto Cleesh\",\"size\":17,\"on\":true}\n",
     ));
 }
+
+#[tokio::test]
+async fn test_write_crlf() {
+    let mut writer = AsyncJsonLinesWriter::new(Vec::new()).line_terminator(LineTerminator::CrLf);
+    writer
+        .write(&Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        })
+        .await
+        .unwrap();
+    writer.write(&[1, 2]).await.unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        b"{\"name\":\"Foo Bar\",\"size\":42,\"on\":true}\r\n[1,2]\r\n"
+    );
+}
+
+#[tokio::test]
+async fn test_into_sink_crlf() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    {
+        let fp = File::create(&tmpfile).await.unwrap();
+        let mut sink = AsyncJsonLinesWriter::new(fp)
+            .line_terminator(LineTerminator::CrLf)
+            .into_sink();
+        sink.send(1).await.unwrap();
+        sink.send(2).await.unwrap();
+        sink.close().await.unwrap();
+    }
+    tmpfile.assert("1\r\n2\r\n");
+}
+
+#[tokio::test]
+async fn test_with_header() {
+    let mut writer = AsyncJsonLinesWriter::new(Vec::new())
+        .with_header(&Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        })
+        .await
+        .unwrap();
+    writer.write(&[1, 2]).await.unwrap();
+    writer.write(&[3, 4]).await.unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        b"{\"name\":\"Foo Bar\",\"size\":42,\"on\":true}\n[1,2]\n[3,4]\n"
+    );
+}
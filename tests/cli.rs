@@ -0,0 +1,200 @@
+#![cfg(feature = "cli")]
+mod common;
+use crate::common::*;
+use assert_fs::NamedTempFile;
+use assert_fs::fixture::FileWriteStr;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+fn jsonl<I, S>(args: I, stdin: &[u8]) -> Output
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+{
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jsonl"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("jsonl should start");
+    child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(stdin)
+        .expect("writing to stdin should succeed");
+    child.wait_with_output().expect("jsonl should run")
+}
+
+fn sample01() -> PathBuf {
+    Path::new(DATA_DIR).join("sample01.jsonl")
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).expect("output should be UTF-8")
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).expect("output should be UTF-8")
+}
+
+#[test]
+fn test_validate_ok() {
+    let output = jsonl([Path::new("validate"), &sample01()], b"");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn test_validate_reports_every_bad_line() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    tmpfile
+        .write_str("{\"x\": 1}\nnot json\n{\"x\": 2}\n{\"x\": 3} 4\n")
+        .unwrap();
+    let output = jsonl([Path::new("validate"), tmpfile.path()], b"");
+    assert!(!output.status.success());
+    let name = tmpfile.path().display();
+    assert_eq!(
+        stderr(&output),
+        format!(
+            concat!(
+                "jsonl: {name}:2:2: expected ident\n",
+                "  |\n",
+                "2 | not json\n",
+                "  |  ^\n",
+                "\n",
+                "jsonl: {name}:4:10: trailing characters\n",
+                "  |\n",
+                "4 | {{\"x\": 3}} 4\n",
+                "  |          ^\n",
+                "\n",
+            ),
+            name = name
+        )
+    );
+}
+
+#[test]
+fn test_count_stdin() {
+    let output = jsonl(["count"], b"1\n2\n3\n");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "3\n");
+}
+
+#[test]
+fn test_count_multiple_files() {
+    let output = jsonl([Path::new("count"), &sample01(), &sample01()], b"");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "6\n");
+}
+
+#[test]
+fn test_count_error_has_line_number() {
+    let output = jsonl(["count"], b"1\n2\n[\n");
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).starts_with("jsonl: <stdin>:3:2: EOF while parsing a list\n"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_head() {
+    let output = jsonl([Path::new("head"), Path::new("-n2"), &sample01()], b"");
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        concat!(
+            "{\"name\": \"Foo Bar\", \"on\":true,\"size\": 42 }\n",
+            "{ \"name\":\"Quux\", \"on\" : false ,\"size\": 23}\n",
+        )
+    );
+}
+
+#[test]
+fn test_head_stops_before_bad_line() {
+    let output = jsonl(["head", "-n", "1"], b"1\nnot json\n");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "1\n");
+}
+
+#[test]
+fn test_tail() {
+    let output = jsonl(["tail", "-n", "2"], b"1\n2\n3\n4\n");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "3\n4\n");
+}
+
+#[test]
+fn test_tail_more_than_available() {
+    let output = jsonl(["tail", "-n", "20"], b"1\n2\n");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "1\n2\n");
+}
+
+#[test]
+fn test_cat_gzip() {
+    let tmpfile = NamedTempFile::new("test.jsonl.gz").unwrap();
+    let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+    enc.write_all(b"{\"x\": 1}\n{\"x\": 2}\n").unwrap();
+    std::fs::write(&tmpfile, enc.finish().unwrap()).unwrap();
+    let output = jsonl(["cat".as_ref(), tmpfile.path(), "-".as_ref()], b"[3]\n");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "{\"x\": 1}\n{\"x\": 2}\n[3]\n");
+}
+
+#[test]
+fn test_cat_missing_file() {
+    let output = jsonl(["cat", "does-not-exist.jsonl"], b"");
+    assert!(!output.status.success());
+    assert!(stderr(&output).starts_with("jsonl: does-not-exist.jsonl: "));
+}
+
+#[test]
+fn test_fmt() {
+    let output = jsonl([Path::new("fmt"), &sample01()], b"");
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        concat!(
+            "{\"name\":\"Foo Bar\",\"on\":true,\"size\":42}\n",
+            "{\"name\":\"Quux\",\"on\":false,\"size\":23}\n",
+            "{\"name\":\"Gnusto Cleesh\",\"on\":true,\"size\":17}\n",
+        )
+    );
+}
+
+#[test]
+fn test_fmt_preserves_strings() {
+    let output = jsonl(
+        ["fmt"],
+        b" [ \"a b\" , \"c\\\" d\" , { \"e\" : \" \" } ] \n",
+    );
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "[\"a b\",\"c\\\" d\",{\"e\":\" \"}]\n");
+}
+
+#[test]
+fn test_cat_roundtrip() {
+    let output = jsonl([Path::new("cat"), &sample01()], b"");
+    assert!(output.status.success());
+    let items = serde_jsonlines::JsonLinesReader::new(&output.stdout[..])
+        .read_all::<Structure>()
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(
+        items[2],
+        Structure {
+            name: "Gnusto Cleesh".into(),
+            size: 17,
+            on: true,
+        }
+    );
+}
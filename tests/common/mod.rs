@@ -16,3 +16,21 @@ pub(crate) struct Point {
 
 #[allow(dead_code)]
 pub(crate) static DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+
+/// A `DeserializeSeed` that deserializes a `Point` and multiplies its
+/// coordinates by a factor
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Scaled(pub(crate) i32);
+
+impl<'de> serde::de::DeserializeSeed<'de> for Scaled {
+    type Value = Point;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Point, D::Error> {
+        let p = Point::deserialize(deserializer)?;
+        Ok(Point {
+            x: p.x * self.0,
+            y: p.y * self.0,
+        })
+    }
+}
@@ -0,0 +1,273 @@
+#![cfg(feature = "csv")]
+mod common;
+use crate::common::*;
+use assert_fs::NamedTempFile;
+use assert_fs::assert::PathAssert;
+use assert_fs::fixture::FileWriteStr;
+use serde_json::{Value, json};
+use serde_jsonlines::{ColumnType, CsvToJsonLines, JsonLinesToCsv, json_lines};
+use std::io::{Cursor, ErrorKind};
+use std::path::Path;
+
+fn csv_to_values(converter: &CsvToJsonLines, csv: &str) -> Vec<Value> {
+    let mut out = Vec::new();
+    converter
+        .convert(csv.as_bytes(), &mut out)
+        .expect("conversion should succeed");
+    serde_json::Deserializer::from_slice(&out)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .expect("output should be JSON")
+}
+
+#[test]
+fn test_csv_to_jsonl_infer() {
+    let csv = concat!(
+        "name,size,ratio,on,zip,note\n",
+        "Foo Bar,42,0.5,TRUE,02134,\n",
+        "Quux,-23,1e3,false,90210,\"hello, world\"\n",
+        "\"Gnusto Cleesh\",17,NaN,yes,,null\n",
+    );
+    assert_eq!(
+        csv_to_values(&CsvToJsonLines::new(), csv),
+        [
+            json!({"name": "Foo Bar", "size": 42, "ratio": 0.5, "on": true, "zip": "02134", "note": null}),
+            json!({"name": "Quux", "size": -23, "ratio": 1000.0, "on": false, "zip": 90210, "note": "hello, world"}),
+            json!({"name": "Gnusto Cleesh", "size": 17, "ratio": "NaN", "on": "yes", "zip": null, "note": "null"}),
+        ]
+    );
+}
+
+#[test]
+fn test_csv_to_jsonl_preserves_column_order() {
+    let mut out = Vec::new();
+    CsvToJsonLines::new()
+        .convert(&b"b,a,c\n1,2,3\n"[..], &mut out)
+        .unwrap();
+    assert_eq!(out, b"{\"b\":1,\"a\":2,\"c\":3}\n");
+}
+
+#[test]
+fn test_csv_to_jsonl_typed_columns() {
+    let converter = CsvToJsonLines::new()
+        .infer_types(false)
+        .column_type("size", ColumnType::Integer)
+        .column_type("ratio", ColumnType::Number)
+        .column_type("on", ColumnType::Boolean)
+        .column_type("tags", ColumnType::Json);
+    let csv = concat!(
+        "name,size,ratio,on,tags,zip\n",
+        "42,42,2,True,\"[1, \"\"a\"\"]\",\n",
+        "Quux,,inf,,,07030\n",
+    );
+    let mut out = Vec::new();
+    let e = converter.convert(csv.as_bytes(), &mut out).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        e.to_string(),
+        "line 3, column \"ratio\": expected a number, got \"inf\""
+    );
+    let csv = csv.replace("inf", "1.5");
+    assert_eq!(
+        csv_to_values(&converter, &csv),
+        [
+            json!({"name": "42", "size": 42, "ratio": 2, "on": true, "tags": [1, "a"], "zip": ""}),
+            json!({"name": "Quux", "size": null, "ratio": 1.5, "on": null, "tags": null, "zip": "07030"}),
+        ]
+    );
+}
+
+#[test]
+fn test_csv_to_jsonl_structure() {
+    let csv = "name;size;on\nFoo Bar;42;true\nQuux;23;false\nGnusto Cleesh;17;true\n";
+    let mut out = Vec::new();
+    let n = CsvToJsonLines::new()
+        .delimiter(b';')
+        .column_type("name", ColumnType::String)
+        .convert(csv.as_bytes(), &mut out)
+        .unwrap();
+    assert_eq!(n, 3);
+    let expected = std::fs::read_to_string(Path::new(DATA_DIR).join("sample01.jsonl")).unwrap();
+    let values = serde_json::Deserializer::from_slice(&out)
+        .into_iter::<Structure>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let expected = serde_json::Deserializer::from_str(&expected)
+        .into_iter::<Structure>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(values, expected);
+}
+
+#[test]
+fn test_csv_to_jsonl_errors() {
+    let mut out = Vec::new();
+    let e = CsvToJsonLines::new()
+        .column_type("nonexistent", ColumnType::String)
+        .convert(&b"a,b\n1,2\n"[..], &mut out)
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    assert_eq!(
+        e.to_string(),
+        "column \"nonexistent\" does not occur in CSV header"
+    );
+    let e = CsvToJsonLines::new()
+        .convert(&b"a,b,a\n1,2,3\n"[..], &mut out)
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "duplicate column name \"a\" in CSV header");
+    let e = CsvToJsonLines::new()
+        .convert(&b"a,b\n1,2\n3\n"[..], &mut out)
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.get_ref().unwrap().is::<csv::Error>());
+}
+
+#[test]
+fn test_csv_to_jsonl_file() {
+    let src = NamedTempFile::new("test.csv").unwrap();
+    src.write_str("name,size,on\nFoo Bar,42,true\n").unwrap();
+    let dest = NamedTempFile::new("test.jsonl").unwrap();
+    assert_eq!(
+        CsvToJsonLines::new()
+            .convert_file(src.path(), dest.path())
+            .unwrap(),
+        1
+    );
+    dest.assert("{\"name\":\"Foo Bar\",\"size\":42,\"on\":true}\n");
+}
+
+const NESTED: &str = concat!(
+    "{\"id\": 1, \"user\": {\"name\": \"Alice\", \"address\": {\"city\": \"Paris\"}}, \"tags\": [\"a\", \"b\"]}\n",
+    "{\"id\": 2, \"user\": {\"name\": \"Bob, Jr.\"}, \"tags\": [], \"score\": null}\n",
+    "{\"id\": 3, \"tags\": [1, {\"x\": true}, [null]], \"score\": 9.5, \"extra\": \"x\"}\n",
+);
+
+#[test]
+fn test_jsonl_to_csv_scan_columns() {
+    let mut out = Vec::new();
+    let n = JsonLinesToCsv::new()
+        .convert(Cursor::new(NESTED), &mut out)
+        .unwrap();
+    assert_eq!(n, 3);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        concat!(
+            "id,tags,user.address.city,user.name,score,extra\n",
+            "1,a;b,Paris,Alice,,\n",
+            "2,,,\"Bob, Jr.\",,\n",
+            "3,\"1;{\"\"x\"\":true};[null]\",,,9.5,x\n",
+        )
+    );
+    assert_eq!(
+        JsonLinesToCsv::new()
+            .scan_columns(NESTED.as_bytes())
+            .unwrap(),
+        [
+            "id",
+            "tags",
+            "user.address.city",
+            "user.name",
+            "score",
+            "extra"
+        ]
+    );
+}
+
+#[test]
+fn test_jsonl_to_csv_explicit_columns() {
+    let converter = JsonLinesToCsv::new()
+        .columns(["user.name", "id", "tags", "missing"])
+        .array_separator(" | ")
+        .delimiter(b'\t');
+    let mut out = Vec::new();
+    assert_eq!(
+        converter
+            .convert_stream(NESTED.as_bytes(), &mut out)
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        concat!(
+            "user.name\tid\ttags\tmissing\n",
+            "Alice\t1\ta | b\t\n",
+            "Bob, Jr.\t2\t\t\n",
+            "\t3\t\"1 | {\"\"x\"\":true} | [null]\"\t\n",
+        )
+    );
+}
+
+#[test]
+fn test_jsonl_to_csv_stream_requires_columns() {
+    let mut out = Vec::new();
+    let e = JsonLinesToCsv::new()
+        .convert_stream(NESTED.as_bytes(), &mut out)
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_jsonl_to_csv_non_object() {
+    let mut out = Vec::new();
+    let e = JsonLinesToCsv::new()
+        .convert(Cursor::new("{\"a\": 1}\n[1, 2]\n"), &mut out)
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "line 2: expected a JSON object");
+}
+
+#[test]
+fn test_jsonl_to_csv_column_collision() {
+    let input = "{\"x\": 1}\n{\"a\": {\"b\": 1}, \"a.b\": 2}\n";
+    for converter in [
+        JsonLinesToCsv::new(),
+        JsonLinesToCsv::new().columns(["a.b"]),
+    ] {
+        let mut out = Vec::new();
+        let e = converter.convert(Cursor::new(input), &mut out).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            e.to_string(),
+            "line 2: multiple fields flatten to column \"a.b\""
+        );
+    }
+}
+
+#[test]
+fn test_round_trip_file() {
+    let csv = NamedTempFile::new("test.csv").unwrap();
+    let n = JsonLinesToCsv::new()
+        .convert_file(Path::new(DATA_DIR).join("sample01.jsonl"), csv.path())
+        .unwrap();
+    assert_eq!(n, 3);
+    csv.assert("name,on,size\nFoo Bar,true,42\nQuux,false,23\nGnusto Cleesh,true,17\n");
+    let jsonl = NamedTempFile::new("test.jsonl").unwrap();
+    CsvToJsonLines::new()
+        .convert_file(csv.path(), jsonl.path())
+        .unwrap();
+    assert_eq!(
+        json_lines(jsonl.path())
+            .unwrap()
+            .collect::<std::io::Result<Vec<Structure>>>()
+            .unwrap(),
+        [
+            Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            },
+            Structure {
+                name: "Quux".into(),
+                size: 23,
+                on: false,
+            },
+            Structure {
+                name: "Gnusto Cleesh".into(),
+                size: 17,
+                on: true,
+            },
+        ]
+    );
+}
@@ -0,0 +1,239 @@
+#![cfg(feature = "derive")]
+mod common;
+use crate::common::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use serde_jsonlines::{JsonLinesReader, JsonLinesRecord};
+use std::io::ErrorKind;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct EventV1 {
+    user: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct EventV2 {
+    user: String,
+    count: u32,
+}
+
+#[derive(Debug, Deserialize, JsonLinesRecord, PartialEq)]
+#[jsonlines(version = 3, default_version = 1, upgrade(1 = EventV1, 2 = EventV2))]
+struct Event {
+    user: String,
+    count: u32,
+    tags: Vec<String>,
+}
+
+impl From<EventV1> for Event {
+    fn from(v1: EventV1) -> Event {
+        EventV2 {
+            user: v1.user,
+            count: 1,
+        }
+        .into()
+    }
+}
+
+impl From<EventV2> for Event {
+    fn from(v2: EventV2) -> Event {
+        Event {
+            user: v2.user,
+            count: v2.count,
+            tags: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonLinesRecord, PartialEq)]
+#[jsonlines(version = 2, version_field = "v")]
+struct Strict {
+    v: u64,
+    x: i32,
+}
+
+#[derive(Debug, Deserialize, JsonLinesRecord, PartialEq)]
+struct Unversioned {
+    x: i32,
+}
+
+#[derive(Debug, JsonLinesRecord, PartialEq)]
+enum Record {
+    #[jsonlines(rename = "structure")]
+    Structure(Structure),
+    Point(Point),
+    #[jsonlines(rename = "event", record)]
+    Event(Event),
+    #[jsonlines(rename = "heartbeat")]
+    Heartbeat,
+    #[jsonlines(other)]
+    Other(Value),
+}
+
+#[derive(Debug, JsonLinesRecord, PartialEq)]
+#[jsonlines(tag = "kind")]
+enum Closed {
+    #[jsonlines(rename = "point")]
+    Point(Point),
+}
+
+#[test]
+fn test_versioned_struct() {
+    let input = concat!(
+        "{\"user\": \"alice\"}\n",
+        "{\"schema_version\": 1, \"user\": \"bob\"}\n",
+        "{\"user\": \"carol\", \"count\": 5, \"schema_version\": 2}\n",
+        "{\"schema_version\": 3, \"user\": \"dave\", \"count\": 7, \"tags\": [\"x\"]}\n",
+    );
+    let items = JsonLinesReader::new(input.as_bytes())
+        .read_all_records::<Event>()
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            Event {
+                user: "alice".into(),
+                count: 1,
+                tags: Vec::new(),
+            },
+            Event {
+                user: "bob".into(),
+                count: 1,
+                tags: Vec::new(),
+            },
+            Event {
+                user: "carol".into(),
+                count: 5,
+                tags: Vec::new(),
+            },
+            Event {
+                user: "dave".into(),
+                count: 7,
+                tags: vec!["x".into()],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_versioned_struct_errors() {
+    let input = concat!(
+        "{\"schema_version\": 4, \"user\": \"alice\"}\n",
+        "{\"schema_version\": \"2\", \"user\": \"bob\"}\n",
+        "{\"schema_version\": 2, \"user\": \"carol\"}\n",
+        "{\"schema_version\": 1, \"user\": \"dave\"}\n",
+    );
+    let mut reader = JsonLinesReader::new(input.as_bytes());
+    let e = reader.read_record::<Event>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        e.to_string(),
+        "unsupported schema version at /schema_version: 4"
+    );
+    let e = reader.read_record::<Event>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        e.to_string(),
+        "invalid schema version at /schema_version: \"2\""
+    );
+    let e = reader.read_record::<Event>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        reader.read_record::<Event>().unwrap(),
+        Some(Event {
+            user: "dave".into(),
+            count: 1,
+            tags: Vec::new(),
+        })
+    );
+    assert_eq!(reader.read_record::<Event>().unwrap(), None);
+}
+
+#[test]
+fn test_version_field_required() {
+    assert_eq!(
+        Strict::from_json_line("{\"x\": 1, \"v\": 2}").unwrap(),
+        Strict { v: 2, x: 1 }
+    );
+    let e = Strict::from_json_line("{\"x\": 1}").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "no schema version found at /v");
+    let e = Strict::from_json_line("{\"x\": 1, \"v\": 1}").unwrap_err();
+    assert_eq!(e.to_string(), "unsupported schema version at /v: 1");
+}
+
+#[test]
+fn test_unversioned_struct() {
+    assert_eq!(
+        Unversioned::from_json_line("{\"x\": 1, \"schema_version\": 42}").unwrap(),
+        Unversioned { x: 1 }
+    );
+    let e = Unversioned::from_json_line("{\"y\": 1}").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_enum_dispatch() {
+    let input = concat!(
+        "{\"name\": \"Foo Bar\", \"size\": 42, \"on\": true, \"type\": \"structure\"}\n",
+        "{\"type\": \"Point\", \"x\": 1, \"y\": 2}\n",
+        "{\"type\": \"event\", \"user\": \"alice\"}\n",
+        "{\"type\": \"heartbeat\", \"ts\": 123}\n",
+        "{\"type\": \"point\", \"x\": 1, \"y\": 2}\n",
+        "{\"x\": 3}\n",
+    );
+    let items = JsonLinesReader::new(input.as_bytes())
+        .read_all_records::<Record>()
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            Record::Structure(Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            }),
+            Record::Point(Point { x: 1, y: 2 }),
+            Record::Event(Event {
+                user: "alice".into(),
+                count: 1,
+                tags: Vec::new(),
+            }),
+            Record::Heartbeat,
+            Record::Other(json!({"type": "point", "x": 1, "y": 2})),
+            Record::Other(json!({"x": 3})),
+        ]
+    );
+}
+
+#[test]
+fn test_enum_unknown_kind() {
+    let mut reader = JsonLinesReader::new(
+        &b"{\"kind\": \"line\"}\n{\"x\": 1}\n{\"kind\": \"point\", \"x\": 1, \"y\": 2}\n"[..],
+    );
+    let e = reader.read_record::<Closed>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "unknown record type at /kind: \"line\"");
+    let e = reader.read_record::<Closed>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "no record type found at /kind");
+    assert_eq!(
+        reader.read_record::<Closed>().unwrap(),
+        Some(Closed::Point(Point { x: 1, y: 2 }))
+    );
+    assert_eq!(reader.read_record::<Closed>().unwrap(), None);
+}
+
+#[test]
+fn test_enum_invalid_json() {
+    let e = Record::from_json_line("{\"type\": \"Point\", \"x\": 1,}").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,369 @@
+mod common;
+use crate::common::*;
+use serde_jsonlines::{Diagnostic, Dispatcher, Filter, JsonLinesReader, Migrations, Projection};
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+
+fn diagnostic(e: &Error) -> &Diagnostic {
+    e.get_ref()
+        .and_then(|e| e.downcast_ref::<Diagnostic>())
+        .expect("error payload should be a Diagnostic")
+}
+
+#[test]
+fn test_read_diagnostics() {
+    let input = concat!(
+        "{\"name\": \"Foo Bar\", \"size\": 42, \"on\": true}\n",
+        "{\"name\": \"Quux\", \"size\": 23, \"on\": \"no\"}\n",
+        "{\"name\": \"Gnusto Cleesh\", \"size\": 17, \"on\": true}\n",
+        "{\"name\": \"Zork\",\n",
+    );
+    let mut reader = JsonLinesReader::new(input.as_bytes()).diagnostics(true);
+    assert_eq!(
+        reader.read::<Structure>().unwrap(),
+        Some(Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        })
+    );
+    let e = reader.read::<Structure>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    let diag = diagnostic(&e);
+    assert_eq!(
+        *diag,
+        Diagnostic {
+            file: None,
+            line: 2,
+            column: Some(39),
+            field: None,
+            message: String::from("invalid type: string \"no\", expected a boolean"),
+            source_line: String::from("{\"name\": \"Quux\", \"size\": 23, \"on\": \"no\"}"),
+        }
+    );
+    assert_eq!(
+        e.to_string(),
+        concat!(
+            "line 2, column 39: invalid type: string \"no\", expected a boolean\n",
+            "  |\n",
+            "2 | {\"name\": \"Quux\", \"size\": 23, \"on\": \"no\"}\n",
+            "  |                                       ^\n",
+        )
+    );
+    assert!(reader.read::<Structure>().unwrap().is_some());
+    let e = reader.read::<Structure>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(
+        diagnostic(&e).clone().file("sample.jsonl").to_string(),
+        concat!(
+            "sample.jsonl:4:17: EOF while parsing a value\n",
+            "  |\n",
+            "4 | {\"name\": \"Zork\",\n",
+            "  |                 ^\n",
+        )
+    );
+    assert_eq!(reader.read::<Structure>().unwrap(), None);
+}
+
+#[test]
+fn test_diagnostics_disabled() {
+    let mut reader = JsonLinesReader::new(&b"not json\n"[..]);
+    let e = reader.read::<Structure>().unwrap_err();
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+}
+
+#[test]
+fn test_read_all_diagnostics() {
+    let input = "1\n2\nthree\n4\n";
+    let lines = JsonLinesReader::new(input.as_bytes())
+        .diagnostics(true)
+        .read_all::<u32>()
+        .map(|r| r.map_err(|e| diagnostic(&e).line))
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [Ok(1), Ok(2), Err(3), Ok(4)]);
+}
+
+#[test]
+fn test_read_into_diagnostics() {
+    let mut reader = JsonLinesReader::new(&b"[1, 2]\n[3, \"4\"]\n"[..]).diagnostics(true);
+    let mut place = Vec::<u32>::new();
+    assert!(reader.read_into(&mut place).unwrap());
+    assert_eq!(place, [1, 2]);
+    let e = reader.read_into(&mut place).unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 2);
+    assert_eq!(diag.column, Some(7));
+}
+
+#[test]
+fn test_strict_line_endings_diagnostics() {
+    let mut reader = JsonLinesReader::new(&b"1\n2\r\n3\n"[..])
+        .strict_line_endings(true)
+        .diagnostics(true);
+    assert_eq!(reader.read::<u32>().unwrap(), Some(1));
+    let e = reader.read::<u32>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        e.to_string(),
+        concat!(
+            "line 2, column 2: line terminated by CR LF\n",
+            "  |\n",
+            "2 | 2\n",
+            "  |  ^\n",
+        )
+    );
+    assert_eq!(reader.read::<u32>().unwrap(), Some(3));
+}
+
+#[test]
+fn test_from_error_without_position() {
+    let e = Error::new(ErrorKind::InvalidData, "bad record");
+    let diag = Diagnostic::from_error(&e, "{\"x\":\t1}\r\n", 7).file(PathBuf::from("in.jsonl"));
+    assert_eq!(diag.column, None);
+    assert_eq!(diag.source_line, "{\"x\":\t1}");
+    assert_eq!(
+        diag.to_string(),
+        concat!("in.jsonl:7: bad record\n", "  |\n", "7 | {\"x\": 1}\n",)
+    );
+    let wrapped = Error::new(ErrorKind::InvalidData, diag.clone());
+    assert_eq!(Diagnostic::from_error(&wrapped, "", 1), diag);
+}
+
+#[test]
+fn test_render_truncation() {
+    let diag = Diagnostic {
+        file: None,
+        line: 10,
+        column: Some(21),
+        field: None,
+        message: String::from("oops"),
+        source_line: String::from("[\"ééé\", 1, 2, 3, 4, 5, 6, 7, 8, 9]"),
+    };
+    assert_eq!(
+        diag.render(10),
+        concat!(
+            "line 10, column 21: oops\n",
+            "   |\n",
+            "10 | …, 3, 4, 5,…\n",
+            "   |       ^\n",
+        )
+    );
+    let diag = Diagnostic {
+        column: Some(2),
+        ..diag
+    };
+    assert_eq!(
+        diag.render(10),
+        concat!(
+            "line 10, column 2: oops\n",
+            "   |\n",
+            "10 | [\"ééé\", 1,…\n",
+            "   |  ^\n",
+        )
+    );
+    let diag = Diagnostic {
+        column: Some(40),
+        ..diag
+    };
+    assert_eq!(
+        diag.render(10),
+        concat!(
+            "line 10, column 40: oops\n",
+            "   |\n",
+            "10 | …, 7, 8, 9]\n",
+            "   |            ^\n",
+        )
+    );
+}
+
+#[test]
+fn test_render_zero_column() {
+    let diag = Diagnostic {
+        file: None,
+        line: 3,
+        column: Some(0),
+        field: None,
+        message: String::from("oops"),
+        source_line: String::from("[1, 2]"),
+    };
+    assert_eq!(
+        diag.render(80),
+        concat!(
+            "line 3, column 0: oops\n",
+            "  |\n",
+            "3 | [1, 2]\n",
+            "  | ^\n",
+        )
+    );
+    #[cfg(feature = "miette")]
+    {
+        let report = diag.into_report();
+        let labels = report.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(labels[0].offset(), 0);
+    }
+}
+
+#[test]
+fn test_filtered_diagnostics() {
+    let input = concat!(
+        "{\"name\": \"Foo Bar\", \"size\": 42, \"on\": true}\n",
+        "{\"name\": \"Quux\", \"size\": 23, \"on\": \"no\"}\n",
+        "{\"name\": \"Gnusto\", \"size\": 17, \"on\": }\n",
+    );
+    let filter = Filter::new().range("/size", ..30.0).unwrap();
+    let mut reader = JsonLinesReader::new(input.as_bytes()).diagnostics(true);
+    let e = reader.read_filtered::<Structure>(&filter).unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 2);
+    assert_eq!(diag.column, Some(39));
+    let e = reader.read_filtered::<Structure>(&filter).unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 3);
+    assert_eq!(diag.column, Some(38));
+    assert_eq!(diag.message, "expected value");
+}
+
+#[test]
+fn test_projected_diagnostics() {
+    let projection = Projection::new(["/x"]).unwrap();
+    let mut reader =
+        JsonLinesReader::new(&b"{\"x\": 1}\n{\"x\": -1}\n{\"x\" 2}\n"[..]).diagnostics(true);
+    assert_eq!(
+        reader.read_projected::<(u32,)>(&projection).unwrap(),
+        Some((1,))
+    );
+    let e = reader.read_projected::<(u32,)>(&projection).unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 2);
+    assert_eq!(diag.column, None);
+    assert_eq!(diag.source_line, "{\"x\": -1}");
+    let e = reader.read_projected::<(u32,)>(&projection).unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 3);
+    assert_eq!(diag.column, Some(6));
+}
+
+#[test]
+fn test_dispatched_diagnostics() {
+    let dispatcher = Dispatcher::new("/kind")
+        .unwrap()
+        .register("point", |p: Point| p.x + p.y);
+    let input = "{\"kind\": \"point\", \"x\": 1, \"y\": \"2\"}\n{\"kind\": \"circle\"}\n";
+    let mut reader = JsonLinesReader::new(input.as_bytes()).diagnostics(true);
+    let e = reader.read_dispatched(&dispatcher).unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 1);
+    assert_eq!(diag.column, Some(34));
+    let e = reader.read_dispatched(&dispatcher).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        concat!(
+            "line 2: no decoder registered for /kind = \"circle\"\n",
+            "  |\n",
+            "2 | {\"kind\": \"circle\"}\n",
+        )
+    );
+}
+
+#[test]
+fn test_migrated_diagnostics() {
+    let migrations = Migrations::new("/v").unwrap().default_version(2);
+    let input = "{\"x\": 1, \"y\": 2}\n{\"v\": 1, \"x\": 1, \"y\": 2}\n{\"x\": 1, \"y\": null}\n";
+    let mut reader = JsonLinesReader::new(input.as_bytes()).diagnostics(true);
+    assert_eq!(
+        reader.read_migrated::<Point>(&migrations).unwrap(),
+        Some(Point { x: 1, y: 2 })
+    );
+    let e = reader.read_migrated::<Point>(&migrations).unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 2);
+    assert_eq!(diag.message, "no migration from schema version 1");
+    let e = reader.read_migrated::<Point>(&migrations).unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 3);
+    assert_eq!(diag.column, Some(18));
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_record_diagnostics() {
+    use serde_jsonlines::JsonLinesRecord;
+
+    #[derive(Debug, JsonLinesRecord, PartialEq)]
+    enum Shape {
+        #[jsonlines(rename = "point")]
+        Point(Point),
+    }
+
+    let input = "{\"type\": \"point\", \"x\": 1, \"y\": []}\n{\"type\": \"line\"}\n";
+    let mut reader = JsonLinesReader::new(input.as_bytes()).diagnostics(true);
+    let e = reader.read_record::<Shape>().unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 1);
+    assert_eq!(diag.column, Some(31));
+    let e = reader.read_record::<Shape>().unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.line, 2);
+    assert_eq!(diag.message, "unknown record type at /type: \"line\"");
+}
+
+#[cfg(feature = "path-to-error")]
+#[test]
+fn test_field_path_diagnostics() {
+    let input = "{\"name\": \"Quux\", \"size\": \"23\", \"on\": false}\n";
+    let mut reader = JsonLinesReader::new(input.as_bytes())
+        .field_paths(true)
+        .diagnostics(true);
+    let e = reader.read::<Structure>().unwrap_err();
+    let diag = diagnostic(&e);
+    assert_eq!(diag.field.as_deref(), Some("size"));
+    assert_eq!(diag.column, Some(29));
+    assert!(
+        e.to_string()
+            .starts_with("line 1, column 29: at size: invalid type: string \"23\"")
+    );
+}
+
+#[cfg(feature = "miette")]
+#[test]
+fn test_into_report() {
+    let mut reader = JsonLinesReader::new(&b"1\n2\n[3, x]\n"[..]).diagnostics(true);
+    assert_eq!(
+        reader.read::<Vec<u32>>().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+    reader.read::<u32>().unwrap();
+    let e = reader.read::<Vec<u32>>().unwrap_err();
+    let report = diagnostic(&e).clone().file("nums.jsonl").into_report();
+    assert_eq!(report.to_string(), "expected value");
+    let labels = report.labels().unwrap().collect::<Vec<_>>();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].offset(), 4);
+    assert_eq!(labels[0].label(), Some("here"));
+    let source = report.source_code().unwrap();
+    let contents = source.read_span(labels[0].inner(), 0, 0).unwrap();
+    assert_eq!(contents.name(), Some("nums.jsonl"));
+    assert_eq!(contents.line(), 2);
+}
+
+#[cfg(feature = "async")]
+mod asynclib {
+    use super::*;
+    use futures_util::StreamExt;
+    use serde_jsonlines::AsyncJsonLinesReader;
+
+    #[tokio::test]
+    async fn test_async_read_diagnostics() {
+        let mut stream = AsyncJsonLinesReader::new(&b"1\n[2\n3\n"[..])
+            .diagnostics(true)
+            .read_all::<u32>();
+        assert_eq!(stream.next().await.unwrap().unwrap(), 1);
+        let e = stream.next().await.unwrap().unwrap_err();
+        let diag = diagnostic(&e);
+        assert_eq!(diag.line, 2);
+        assert_eq!(diag.column, Some(1));
+        assert_eq!(diag.source_line, "[2");
+        assert_eq!(stream.next().await.unwrap().unwrap(), 3);
+        assert!(stream.next().await.is_none());
+    }
+}
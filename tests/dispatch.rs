@@ -0,0 +1,135 @@
+mod common;
+use crate::common::*;
+use serde_json::{Value, json};
+use serde_jsonlines::{Dispatcher, JsonLinesReader};
+use std::io::ErrorKind;
+
+#[derive(Debug, PartialEq)]
+enum Record {
+    Structure(Structure),
+    Point(Point),
+    Other(Value),
+}
+
+fn dispatcher() -> Dispatcher<Record> {
+    Dispatcher::new("/kind")
+        .expect("pointer should be valid")
+        .register("structure", Record::Structure)
+        .register("point", Record::Point)
+}
+
+#[test]
+fn test_dispatch_all() {
+    let input = concat!(
+        "{\"kind\": \"structure\", \"name\": \"Foo Bar\", \"size\": 42, \"on\": true}\n",
+        "{\"x\": 1, \"y\": 2, \"kind\": \"point\"}\n",
+        "{\"kind\": \"unknown\", \"data\": [1, 2, 3]}\n",
+        "{\"data\": null}\n",
+        "[1, 2, 3]\n",
+    );
+    let items = JsonLinesReader::new(input.as_bytes())
+        .dispatch_all(dispatcher().fallback(Record::Other))
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            Record::Structure(Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            }),
+            Record::Point(Point { x: 1, y: 2 }),
+            Record::Other(json!({"kind": "unknown", "data": [1, 2, 3]})),
+            Record::Other(json!({"data": null})),
+            Record::Other(json!([1, 2, 3])),
+        ]
+    );
+}
+
+#[test]
+fn test_dispatch_nested_discriminator() {
+    let dispatcher = Dispatcher::new("/meta/type")
+        .unwrap()
+        .register("point", |p: Point| p.x + p.y);
+    assert_eq!(
+        dispatcher
+            .decode("{\"x\": 3, \"meta\": {\"type\": \"point\"}, \"y\": 4}")
+            .unwrap(),
+        7
+    );
+}
+
+#[test]
+fn test_dispatch_no_fallback() {
+    let input = concat!(
+        "{\"kind\": \"unknown\"}\n",
+        "{\"x\": 1, \"y\": 2, \"kind\": \"point\"}\n",
+        "{\"x\": 1, \"y\": 2}\n",
+        "{\"x\": 1, \"y\": 2, \"kind\": 3}\n",
+        "{\"x\": 3, \"y\": 4, \"kind\": \"point\"}\n",
+    );
+    let mut reader = JsonLinesReader::new(input.as_bytes());
+    let dispatcher = dispatcher();
+    let e = reader.read_dispatched(&dispatcher).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        e.to_string(),
+        "no decoder registered for /kind = \"unknown\""
+    );
+    assert_eq!(
+        reader.read_dispatched(&dispatcher).unwrap(),
+        Some(Record::Point(Point { x: 1, y: 2 }))
+    );
+    let e = reader.read_dispatched(&dispatcher).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "no discriminator found at \"/kind\"");
+    let e = reader.read_dispatched(&dispatcher).unwrap_err();
+    assert_eq!(e.to_string(), "no decoder registered for /kind = 3");
+    assert_eq!(
+        reader.read_dispatched(&dispatcher).unwrap(),
+        Some(Record::Point(Point { x: 3, y: 4 }))
+    );
+    assert_eq!(reader.read_dispatched(&dispatcher).unwrap(), None);
+}
+
+#[test]
+fn test_dispatch_decode_error() {
+    let mut reader = JsonLinesReader::new(
+        &b"{\"kind\": \"point\", \"x\": 1}\nnot json\n{\"kind\": \"point\", \"x\": 1, \"y\": 2}\n"
+            [..],
+    );
+    let dispatcher = dispatcher().fallback(Record::Other);
+    let e = reader.read_dispatched(&dispatcher).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    let e = reader.read_dispatched(&dispatcher).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        reader.read_dispatched(&dispatcher).unwrap(),
+        Some(Record::Point(Point { x: 1, y: 2 }))
+    );
+}
+
+#[test]
+fn test_dispatch_reregister() {
+    let dispatcher = Dispatcher::new("/kind")
+        .unwrap()
+        .register("point", |p: Point| p.x)
+        .register("point", |p: Point| p.y);
+    assert_eq!(
+        dispatcher
+            .decode("{\"kind\": \"point\", \"x\": 1, \"y\": 2}")
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        format!("{dispatcher:?}"),
+        "Dispatcher { pointer: \"/kind\", kinds: [\"point\"], fallback: false }"
+    );
+}
+
+#[test]
+fn test_dispatcher_invalid_pointer() {
+    let e = Dispatcher::<Value>::new("kind").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+}
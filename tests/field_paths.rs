@@ -0,0 +1,321 @@
+#![cfg(feature = "path-to-error")]
+mod common;
+use crate::common::*;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_jsonlines::{
+    Dispatcher, FieldPathError, Filter, JsonLinesReader, Migrations, Projection,
+};
+use std::io::ErrorKind;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Order {
+    id: u32,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Item {
+    sku: String,
+    qty: u64,
+}
+
+const INPUT: &str = concat!(
+    "{\"id\": 1, \"items\": [{\"sku\": \"A\", \"qty\": 1}]}\n",
+    "{\"id\": 2, \"items\": [{\"sku\": \"B\", \"qty\": 2}, {\"sku\": \"C\", \"qty\": \"x\"}]}\n",
+    "{\"id\": 3, \"items\": []}\n",
+    "{\"id\": 4, \"items\": [{\"qty\": 4}]}\n",
+    "{\"id\": 5, \"items\": [] } 6\n",
+    "{\"id\": 7, \"items\": [{\"sku\": \"D\",\n",
+);
+
+fn field_path_error(e: &std::io::Error) -> &FieldPathError {
+    e.get_ref()
+        .and_then(|e| e.downcast_ref::<FieldPathError>())
+        .expect("error payload should be a FieldPathError")
+}
+
+#[test]
+fn test_read_field_paths() {
+    let mut reader = JsonLinesReader::new(INPUT.as_bytes()).field_paths(true);
+    assert_eq!(
+        reader.read::<Order>().unwrap(),
+        Some(Order {
+            id: 1,
+            items: vec![Item {
+                sku: "A".into(),
+                qty: 1
+            }]
+        })
+    );
+    let e = reader.read::<Order>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "items[1].qty");
+    assert!(
+        e.to_string()
+            .starts_with("line 2, at items[1].qty: invalid type: string \"x\", expected u64")
+    );
+    assert_eq!(
+        reader.read::<Order>().unwrap(),
+        Some(Order {
+            id: 3,
+            items: Vec::new()
+        })
+    );
+    let e = reader.read::<Order>().unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 4);
+    assert_eq!(err.path, "items[0]");
+    assert!(err.error.to_string().starts_with("missing field `sku`"));
+    let e = reader.read::<Order>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 5);
+    assert_eq!(err.path, ".");
+    let e = reader.read::<Order>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::UnexpectedEof);
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 6);
+    assert_eq!(err.path, "items[0].?");
+    assert_eq!(reader.read::<Order>().unwrap(), None);
+}
+
+#[test]
+fn test_field_paths_disabled() {
+    let mut reader = JsonLinesReader::new(INPUT.as_bytes());
+    assert!(reader.read::<Order>().unwrap().is_some());
+    let e = reader.read::<Order>().unwrap_err();
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+}
+
+#[test]
+fn test_read_all_field_paths() {
+    let input = concat!(
+        "{\"name\": \"Foo Bar\", \"size\": 42, \"on\": true}\n",
+        "{\"name\": \"Quux\", \"size\": 23, \"on\": \"no\"}\n",
+    );
+    let mut iter = JsonLinesReader::new(input.as_bytes())
+        .field_paths(true)
+        .read_all::<Structure>();
+    assert_eq!(
+        iter.next().unwrap().unwrap(),
+        Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        }
+    );
+    let e = iter.next().unwrap().unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "on");
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_read_into_field_paths() {
+    let mut reader = JsonLinesReader::new(INPUT.as_bytes()).field_paths(true);
+    let mut order = Order {
+        id: 0,
+        items: Vec::new(),
+    };
+    assert!(reader.read_into(&mut order).unwrap());
+    assert_eq!(order.id, 1);
+    let e = reader.read_into(&mut order).unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "items[1].qty");
+}
+
+#[test]
+fn test_read_seed_field_paths() {
+    let input = "{\"x\": 1, \"y\": 2}\n{\"x\": 3, \"y\": null}\n";
+    let mut reader = JsonLinesReader::new(input.as_bytes()).field_paths(true);
+    assert_eq!(
+        reader.read_seed(Scaled(2)).unwrap(),
+        Some(Point { x: 2, y: 4 })
+    );
+    let e = reader.read_seed(Scaled(2)).unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "y");
+}
+
+#[test]
+fn test_read_filtered_field_paths() {
+    let filter = Filter::new().range("/id", 2.0..).unwrap();
+    let mut reader = JsonLinesReader::new(INPUT.as_bytes()).field_paths(true);
+    let e = reader.read_filtered::<Order>(&filter).unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "items[1].qty");
+    assert_eq!(
+        reader.read_filtered::<Order>(&filter).unwrap(),
+        Some(Order {
+            id: 3,
+            items: Vec::new()
+        })
+    );
+    let e = reader.read_filtered::<Order>(&filter).unwrap_err();
+    assert_eq!(field_path_error(&e).path, "items[0]");
+}
+
+#[test]
+fn test_read_projected_field_paths() {
+    let projection = Projection::new(["/id", "/items"]).unwrap();
+    let mut reader = JsonLinesReader::new(INPUT.as_bytes()).field_paths(true);
+    assert!(
+        reader
+            .read_projected::<(u32, Vec<Item>)>(&projection)
+            .unwrap()
+            .is_some()
+    );
+    let e = reader
+        .read_projected::<(u32, Vec<Item>)>(&projection)
+        .unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "[1][1].qty");
+}
+
+#[test]
+fn test_read_dispatched_field_paths() {
+    let dispatcher = Dispatcher::new("/kind")
+        .unwrap()
+        .register("point", |p: Point| p.x + p.y);
+    let input =
+        "{\"kind\": \"point\", \"x\": 1, \"y\": 2}\n{\"kind\": \"point\", \"x\": 1, \"y\": true}\n";
+    let mut reader = JsonLinesReader::new(input.as_bytes()).field_paths(true);
+    assert_eq!(reader.read_dispatched(&dispatcher).unwrap(), Some(3));
+    let e = reader.read_dispatched(&dispatcher).unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "y");
+}
+
+#[test]
+fn test_read_migrated_field_paths() {
+    let migrations = Migrations::new("/v")
+        .unwrap()
+        .default_version(1)
+        .step(1, |mut v: Value| {
+            v["y"] = v["x"].clone();
+            Ok(v)
+        });
+    let input = "{\"x\": 1}\n{\"x\": \"1\"}\n{\"v\": 2, \"x\": 1, \"y\": [2]}\n";
+    let mut reader = JsonLinesReader::new(input.as_bytes()).field_paths(true);
+    assert_eq!(
+        reader.read_migrated::<Point>(&migrations).unwrap(),
+        Some(Point { x: 1, y: 1 })
+    );
+    let e = reader.read_migrated::<Point>(&migrations).unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "x");
+    let e = reader.read_migrated::<Point>(&migrations).unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 3);
+    assert_eq!(err.path, "y");
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_read_record_field_paths() {
+    use serde_jsonlines::JsonLinesRecord;
+
+    #[derive(Debug, JsonLinesRecord, PartialEq)]
+    enum Shape {
+        #[jsonlines(rename = "point")]
+        Point(Point),
+    }
+
+    let input =
+        "{\"type\": \"point\", \"x\": 1, \"y\": 2}\n{\"type\": \"point\", \"x\": 1, \"y\": {}}\n";
+    let mut reader = JsonLinesReader::new(input.as_bytes()).field_paths(true);
+    assert_eq!(
+        reader.read_record::<Shape>().unwrap(),
+        Some(Shape::Point(Point { x: 1, y: 2 }))
+    );
+    let e = reader.read_record::<Shape>().unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "y");
+}
+
+#[cfg(feature = "jsonschema")]
+#[test]
+fn test_read_validated_field_paths() {
+    let schema =
+        serde_jsonlines::JsonSchema::compile(&serde_json::json!({"type": "object"})).unwrap();
+    let mut reader = JsonLinesReader::new(INPUT.as_bytes()).field_paths(true);
+    assert!(reader.read_validated::<Order>(&schema).unwrap().is_some());
+    let e = reader.read_validated::<Order>(&schema).unwrap_err();
+    let err = field_path_error(&e);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.path, "items[1].qty");
+}
+
+#[cfg(feature = "async")]
+mod asynclib {
+    use super::*;
+    use futures_util::StreamExt;
+    use serde_jsonlines::AsyncJsonLinesReader;
+
+    #[tokio::test]
+    async fn test_async_read_field_paths() {
+        let mut reader = AsyncJsonLinesReader::new(INPUT.as_bytes()).field_paths(true);
+        assert!(reader.read::<Order>().await.unwrap().is_some());
+        let e = reader.read::<Order>().await.unwrap_err();
+        let err = field_path_error(&e);
+        assert_eq!(err.line, 2);
+        assert_eq!(err.path, "items[1].qty");
+        assert!(reader.read::<Order>().await.unwrap().is_some());
+        let mut place = Order {
+            id: 0,
+            items: Vec::new(),
+        };
+        let e = reader.read_into(&mut place).await.unwrap_err();
+        let err = field_path_error(&e);
+        assert_eq!(err.line, 4);
+        assert_eq!(err.path, "items[0]");
+    }
+
+    #[tokio::test]
+    async fn test_async_stream_field_paths() {
+        let mut stream = AsyncJsonLinesReader::new(INPUT.as_bytes())
+            .field_paths(true)
+            .read_all::<Order>();
+        let mut errors = Vec::new();
+        while let Some(r) = stream.next().await {
+            if let Err(e) = r {
+                let err = field_path_error(&e);
+                errors.push((err.line, err.path.clone()));
+            }
+        }
+        assert_eq!(
+            errors,
+            [
+                (2, String::from("items[1].qty")),
+                (4, String::from("items[0]")),
+                (5, String::from(".")),
+                (6, String::from("items[0].?")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_async_seed_stream_field_paths() {
+        let input = "{\"x\": 1, \"y\": 2}\n{\"x\": \"3\", \"y\": 4}\n";
+        let mut stream = AsyncJsonLinesReader::new(input.as_bytes())
+            .field_paths(true)
+            .read_all_seeded(|| Scaled(3));
+        assert_eq!(stream.next().await.unwrap().unwrap(), Point { x: 3, y: 6 });
+        let e = stream.next().await.unwrap().unwrap_err();
+        let err = field_path_error(&e);
+        assert_eq!(err.line, 2);
+        assert_eq!(err.path, "x");
+        assert!(stream.next().await.is_none());
+    }
+}
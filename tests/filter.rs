@@ -0,0 +1,170 @@
+mod common;
+use crate::common::*;
+use serde_json::Value;
+use serde_jsonlines::{Filter, JsonLinesReader};
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Result};
+use std::path::Path;
+
+static EVENTS: &str = concat!(
+    "{\"type\": \"click\", \"user\": {\"id\": 1}, \"x\": 1, \"y\": 2}\n",
+    "{\"type\": \"scroll\", \"user\": {\"id\": 2}, \"delta\": 5}\n",
+    "{\"type\": \"click\", \"user\": {\"id\": 3}, \"x\": 3, \"y\": 4}\n",
+    "{\"type\": \"click\", \"x\": 5, \"y\": 6}\n",
+    "{\"type\": \"click\", \"user\": {\"id\": \"4\"}, \"x\": 7, \"y\": 8}\n",
+);
+
+fn filter_points(filter: Filter) -> Vec<Point> {
+    JsonLinesReader::new(EVENTS.as_bytes())
+        .filter_all::<Point>(filter)
+        .collect::<Result<Vec<_>>>()
+        .expect("reading should succeed")
+}
+
+#[test]
+fn test_filter_equals() {
+    let filter = Filter::new().equals("/type", "click").unwrap();
+    assert_eq!(
+        filter_points(filter),
+        [
+            Point { x: 1, y: 2 },
+            Point { x: 3, y: 4 },
+            Point { x: 5, y: 6 },
+            Point { x: 7, y: 8 },
+        ]
+    );
+}
+
+#[test]
+fn test_filter_exists() {
+    let filter = Filter::new()
+        .equals("/type", "click")
+        .unwrap()
+        .exists("/user/id")
+        .unwrap();
+    assert_eq!(
+        filter_points(filter),
+        [
+            Point { x: 1, y: 2 },
+            Point { x: 3, y: 4 },
+            Point { x: 7, y: 8 },
+        ]
+    );
+}
+
+#[test]
+fn test_filter_range() {
+    let filter = Filter::new().range("/user/id", 2.0..=3.0).unwrap();
+    let items = JsonLinesReader::new(EVENTS.as_bytes())
+        .filter_all::<Value>(filter)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["type"], "scroll");
+    assert_eq!(items[1]["x"], 3);
+}
+
+#[test]
+fn test_filter_range_same_pointer_twice() {
+    let filter = Filter::new()
+        .range("/x", 2.0..)
+        .unwrap()
+        .range("/x", ..6.0)
+        .unwrap();
+    assert_eq!(
+        filter_points(filter),
+        [Point { x: 3, y: 4 }, Point { x: 5, y: 6 }]
+    );
+}
+
+#[test]
+fn test_filter_none_match() {
+    let filter = Filter::new().equals("/type", "keypress").unwrap();
+    let mut reader = JsonLinesReader::new(EVENTS.as_bytes());
+    assert_eq!(reader.read_filtered::<Point>(&filter).unwrap(), None);
+}
+
+#[test]
+fn test_filter_empty_matches_all() {
+    let items = JsonLinesReader::new(EVENTS.as_bytes())
+        .filter_all::<Value>(Filter::new())
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(items.len(), 5);
+}
+
+#[test]
+fn test_filter_skipped_lines_are_not_deserialized() {
+    // The second line cannot be deserialized as a Point, but it is skipped
+    // before that is attempted.
+    let filter = Filter::new().equals("/type", "click").unwrap();
+    let mut reader = JsonLinesReader::new(EVENTS.as_bytes());
+    assert_eq!(
+        reader.read_filtered::<Point>(&filter).unwrap(),
+        Some(Point { x: 1, y: 2 })
+    );
+    assert_eq!(
+        reader.read_filtered::<Point>(&filter).unwrap(),
+        Some(Point { x: 3, y: 4 })
+    );
+}
+
+#[test]
+fn test_filter_invalid_json() {
+    let filter = Filter::new().equals("/type", "click").unwrap();
+    let mut reader = JsonLinesReader::new(
+        &b"{\"type\": \"scroll\",}\n{\"type\": \"click\", \"x\": 1, \"y\": 2}\n"[..],
+    );
+    let e = reader.read_filtered::<Point>(&filter).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+    assert_eq!(
+        reader.read_filtered::<Point>(&filter).unwrap(),
+        Some(Point { x: 1, y: 2 })
+    );
+}
+
+#[test]
+fn test_filter_matches() {
+    let filter = Filter::new()
+        .equals("/a/0", true)
+        .unwrap()
+        .exists("/b")
+        .unwrap();
+    assert!(filter.matches("{\"a\": [true], \"b\": null}").unwrap());
+    assert!(!filter.matches("{\"a\": [false], \"b\": null}").unwrap());
+    assert!(!filter.matches("{\"a\": [true]}").unwrap());
+    assert!(!filter.matches("[1, 2]").unwrap());
+    assert!(filter.matches("{\"a\": [true], \"b\": 1").is_err());
+}
+
+#[test]
+fn test_filter_invalid_pointer() {
+    let e = Filter::new().exists("foo").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_filter_file() {
+    let fp = BufReader::new(File::open(Path::new(DATA_DIR).join("sample01.jsonl")).unwrap());
+    let filter = Filter::new().equals("/on", true).unwrap();
+    let items = JsonLinesReader::new(fp)
+        .filter_all::<Structure>(filter)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            },
+            Structure {
+                name: "Gnusto Cleesh".into(),
+                size: 17,
+                on: true,
+            },
+        ]
+    );
+}
@@ -3,7 +3,7 @@ use crate::common::*;
 use assert_fs::NamedTempFile;
 use assert_fs::assert::PathAssert;
 use assert_fs::fixture::FileTouch;
-use serde_jsonlines::{append_json_lines, json_lines, write_json_lines};
+use serde_jsonlines::{append_json_lines, append_json_lines_locked, json_lines, write_json_lines};
 use std::iter::empty;
 use std::path::Path;
 
@@ -189,3 +189,50 @@ fn test_no_json_lines() {
     assert!(items.next().is_none());
     assert!(items.next().is_none());
 }
+
+#[test]
+fn test_append_json_lines_locked() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    append_json_lines_locked(
+        &tmpfile,
+        [
+            Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            },
+            Structure {
+                name: "Quux".into(),
+                size: 23,
+                on: false,
+            },
+        ],
+    )
+    .unwrap();
+    tmpfile.assert(concat!(
+        "{\"name\":\"Foo Bar\",\"size\":42,\"on\":true}\n",
+        "{\"name\":\"Quux\",\"size\":23,\"on\":false}\n",
+    ));
+    append_json_lines_locked(&tmpfile, empty::<Structure>()).unwrap();
+    append_json_lines_locked(
+        &tmpfile,
+        [Structure {
+            name: "Gnusto Cleesh".into(),
+            size: 17,
+            on: true,
+        }],
+    )
+    .unwrap();
+    tmpfile.assert(concat!(
+        "{\"name\":\"Foo Bar\",\"size\":42,\"on\":true}\n",
+        "{\"name\":\"Quux\",\"size\":23,\"on\":false}\n",
+        "{\"name\":\"Gnusto Cleesh\",\"size\":17,\"on\":true}\n",
+    ));
+}
+
+#[test]
+fn test_append_json_lines_locked_none() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    append_json_lines_locked(&tmpfile, empty::<Structure>()).unwrap();
+    tmpfile.assert("");
+}
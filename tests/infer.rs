@@ -0,0 +1,271 @@
+mod common;
+use crate::common::*;
+use serde_json::json;
+use serde_jsonlines::{JsonLinesWriter, NumberStats, Shape, infer_schema, infer_schema_file};
+use std::io::ErrorKind;
+use std::path::Path;
+
+#[test]
+fn test_infer_schema_file() {
+    let shape = infer_schema_file(Path::new(DATA_DIR).join("sample01.jsonl"), None).unwrap();
+    assert_eq!(shape.count, 3);
+    assert_eq!(shape.types(), ["object"]);
+    let objects = shape.objects.as_ref().unwrap();
+    assert_eq!(objects.count, 3);
+    assert_eq!(
+        objects.fields.keys().collect::<Vec<_>>(),
+        ["name", "on", "size"]
+    );
+    assert_eq!(
+        objects.fields["size"].integers,
+        Some(NumberStats {
+            count: 3,
+            min: 17,
+            max: 42
+        })
+    );
+    assert_eq!(objects.fields["on"].booleans, 3);
+    assert_eq!(
+        shape.to_rust("Structure"),
+        concat!(
+            "use serde::{Deserialize, Serialize};\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct Structure {\n",
+            "    pub name: String,\n",
+            "    pub on: bool,\n",
+            "    pub size: i64,\n",
+            "}\n",
+        )
+    );
+}
+
+#[test]
+fn test_sample_limit() {
+    let mut writer = JsonLinesWriter::new(Vec::new());
+    for size in 0..10 {
+        writer
+            .write(&Structure {
+                name: format!("item{size}"),
+                size,
+                on: size % 2 == 0,
+            })
+            .unwrap();
+    }
+    writer.write(&json!("not a structure")).unwrap();
+    let buf = writer.into_inner();
+    let shape = infer_schema(buf.as_slice(), Some(4)).unwrap();
+    assert_eq!(shape.count, 4);
+    let size = &shape.objects.as_ref().unwrap().fields["size"];
+    assert_eq!(
+        size.integers,
+        Some(NumberStats {
+            count: 4,
+            min: 0,
+            max: 3
+        })
+    );
+    let shape = infer_schema(buf.as_slice(), None).unwrap();
+    assert_eq!(shape.count, 11);
+    assert_eq!(shape.types(), ["string", "object"]);
+    assert_eq!(shape.objects.as_ref().unwrap().count, 10);
+    let shape = infer_schema(buf.as_slice(), Some(0)).unwrap();
+    assert_eq!(shape, Shape::default());
+}
+
+#[test]
+fn test_infer_schema_invalid_json() {
+    let input = "{\"x\": 1}\nnot json\n";
+    let e = infer_schema(input.as_bytes(), None).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_presence_and_numbers() {
+    let input = concat!(
+        "{\"a\": 1, \"b\": 2.5, \"c\": 18446744073709551615}\n",
+        "{\"a\": -3, \"b\": 4}\n",
+        "{\"a\": null, \"c\": 0}\n",
+        "{\"a\": 7}\n",
+    );
+    let shape = infer_schema(input.as_bytes(), None).unwrap();
+    let objects = shape.objects.as_ref().unwrap();
+    assert!(objects.is_required("a"));
+    assert!(!objects.is_required("b"));
+    assert!(!objects.is_required("nonexistent"));
+    assert!((objects.presence("a") - 1.0).abs() < f64::EPSILON);
+    assert!((objects.presence("b") - 0.5).abs() < f64::EPSILON);
+    assert!(objects.presence("nonexistent").abs() < f64::EPSILON);
+    let a = &objects.fields["a"];
+    assert!(a.is_nullable());
+    assert_eq!(a.types(), ["null", "integer"]);
+    let b = &objects.fields["b"];
+    assert_eq!(b.floats(), 1);
+    assert_eq!(b.types(), ["number"]);
+    assert_eq!(
+        shape.to_json_schema(),
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "a": {"type": ["null", "integer"], "minimum": -3, "maximum": 7},
+                "b": {"type": "number", "minimum": 2.5, "maximum": 4.0},
+                "c": {"type": "integer", "minimum": 0, "maximum": 18446744073709551615u64},
+            },
+            "required": ["a"],
+        })
+    );
+    assert_eq!(
+        shape.to_rust("Numbers"),
+        concat!(
+            "use serde::{Deserialize, Serialize};\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct Numbers {\n",
+            "    pub a: Option<i64>,\n",
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n",
+            "    pub b: Option<f64>,\n",
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n",
+            "    pub c: Option<u64>,\n",
+            "}\n",
+        )
+    );
+}
+
+#[test]
+fn test_nested_to_rust() {
+    let input = concat!(
+        "{\"orderId\": \"A1\", \"type\": \"web\", \"customer\": {\"name\": \"Alice\"}, ",
+        "\"items\": [{\"sku\": \"X\", \"qty\": 2}, {\"sku\": \"Y\", \"qty\": 1, \"note\": \"gift\"}], ",
+        "\"extra\": 1}\n",
+        "{\"orderId\": \"A2\", \"type\": \"store\", \"customer\": {\"name\": \"Bob\"}, ",
+        "\"items\": [], \"extra\": \"one\", \"2nd-try\": true}\n",
+    );
+    let shape = infer_schema(input.as_bytes(), None).unwrap();
+    assert_eq!(
+        shape.to_rust("Order"),
+        concat!(
+            "use serde::{Deserialize, Serialize};\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct Order {\n",
+            "    #[serde(rename = \"2nd-try\", skip_serializing_if = \"Option::is_none\")]\n",
+            "    pub _2nd_try: Option<bool>,\n",
+            "    pub customer: Customer,\n",
+            "    pub extra: serde_json::Value,\n",
+            "    pub items: Vec<Item>,\n",
+            "    #[serde(rename = \"orderId\")]\n",
+            "    pub order_id: String,\n",
+            "    pub r#type: String,\n",
+            "}\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct Customer {\n",
+            "    pub name: String,\n",
+            "}\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct Item {\n",
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n",
+            "    pub note: Option<String>,\n",
+            "    pub qty: i64,\n",
+            "    pub sku: String,\n",
+            "}\n",
+        )
+    );
+    let schema = shape.to_json_schema();
+    assert_eq!(
+        schema["properties"]["items"],
+        json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "note": {"type": "string"},
+                    "qty": {"type": "integer", "minimum": 1, "maximum": 2},
+                    "sku": {"type": "string"},
+                },
+                "required": ["qty", "sku"],
+            },
+            "minItems": 0,
+            "maxItems": 2,
+        })
+    );
+    assert_eq!(
+        schema["properties"]["extra"],
+        json!({"type": ["integer", "string"], "minimum": 1, "maximum": 1})
+    );
+}
+
+#[test]
+fn test_non_object_to_rust() {
+    let input = "[1, 2]\n[3, null]\n";
+    let shape = infer_schema(input.as_bytes(), None).unwrap();
+    assert_eq!(
+        shape.to_rust("Pair"),
+        concat!(
+            "use serde::{Deserialize, Serialize};\n",
+            "\n",
+            "pub type Pair = Vec<Option<i64>>;\n",
+        )
+    );
+    let shape = infer_schema(&b""[..], None).unwrap();
+    assert_eq!(
+        shape.to_json_schema(),
+        json!({"$schema": "https://json-schema.org/draft/2020-12/schema"})
+    );
+    assert_eq!(
+        shape.to_rust("Empty"),
+        concat!(
+            "use serde::{Deserialize, Serialize};\n",
+            "\n",
+            "pub type Empty = serde_json::Value;\n",
+        )
+    );
+}
+
+#[test]
+fn test_to_rust_reserved_names() {
+    let input = concat!(
+        "{\"option\": {\"a\": 1}, \"string\": {\"b\": true}, \"vec\": [{\"c\": \"x\"}], ",
+        "\"self\": {}, \"big\": 18446744073709551615, \"wide\": -1}\n",
+        "{\"option\": {\"a\": 2}, \"string\": {\"b\": false}, \"vec\": [], ",
+        "\"self\": {}, \"big\": 0, \"wide\": 18446744073709551615}\n",
+    );
+    let shape = infer_schema(input.as_bytes(), None).unwrap();
+    assert_eq!(
+        shape.to_rust("Record"),
+        concat!(
+            "use serde::{Deserialize, Serialize};\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct Record {\n",
+            "    pub big: u64,\n",
+            "    pub option: Option2,\n",
+            "    #[serde(rename = \"self\")]\n",
+            "    pub self_: Self2,\n",
+            "    pub string: String2,\n",
+            "    pub vec: Vec<VecItem>,\n",
+            "    pub wide: i128,\n",
+            "}\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct Option2 {\n",
+            "    pub a: i64,\n",
+            "}\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct Self2 {}\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct String2 {\n",
+            "    pub b: bool,\n",
+            "}\n",
+            "\n",
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]\n",
+            "pub struct VecItem {\n",
+            "    pub c: String,\n",
+            "}\n",
+        )
+    );
+}
@@ -0,0 +1,125 @@
+#![cfg(feature = "jq")]
+mod common;
+use crate::common::*;
+use serde_json::{Value, json};
+use serde_jsonlines::{JqFilter, JsonLinesJqWriter, JsonLinesReader};
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Result};
+use std::path::Path;
+
+#[test]
+fn test_apply() {
+    let filter = JqFilter::compile(".a[] | . * 2").unwrap();
+    assert_eq!(
+        filter.apply(json!({"a": [1, 2.5, 3]})).unwrap(),
+        [json!(2), json!(5.0), json!(6)]
+    );
+    assert_eq!(filter.apply(json!({"a": []})).unwrap(), Vec::<Value>::new());
+}
+
+#[test]
+fn test_apply_std_functions() {
+    let filter = JqFilter::compile("[.[] | ascii_upcase] | join(\",\")").unwrap();
+    assert_eq!(filter.apply(json!(["a", "b"])).unwrap(), [json!("A,B")]);
+}
+
+#[test]
+fn test_apply_runtime_error() {
+    let filter = JqFilter::compile(".foo").unwrap();
+    let e = filter.apply(json!(42)).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_compile_parse_error() {
+    let e = JqFilter::compile(".foo | (").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    assert!(e.to_string().contains("offset"), "{e}");
+}
+
+#[test]
+fn test_compile_undefined() {
+    let e = JqFilter::compile(".foo | no_such_function").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    assert!(e.to_string().contains("no_such_function"), "{e}");
+}
+
+#[test]
+fn test_code() {
+    let filter = JqFilter::compile(".name").unwrap();
+    assert_eq!(filter.code(), ".name");
+}
+
+#[test]
+fn test_jq_all_select() {
+    let fp = BufReader::new(File::open(Path::new(DATA_DIR).join("sample01.jsonl")).unwrap());
+    let filter = JqFilter::compile("select(.on) | .name |= ascii_upcase").unwrap();
+    let items = JsonLinesReader::new(fp)
+        .jq_all::<Structure>(filter)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            Structure {
+                name: "FOO BAR".into(),
+                size: 42,
+                on: true,
+            },
+            Structure {
+                name: "GNUSTO CLEESH".into(),
+                size: 17,
+                on: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_jq_all_errors_continue() {
+    let input = "{\"x\": [1, 2]}\n{\"x\": 3}\nnot json\n{\"x\": [4]}\n";
+    let filter = JqFilter::compile(".x[]").unwrap();
+    let mut iter = JsonLinesReader::new(input.as_bytes()).jq_all::<i32>(filter);
+    assert_eq!(iter.next().unwrap().unwrap(), 1);
+    assert_eq!(iter.next().unwrap().unwrap(), 2);
+    let e = iter.next().unwrap().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    let e = iter.next().unwrap().unwrap_err();
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+    assert_eq!(iter.next().unwrap().unwrap(), 4);
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_read_jq() {
+    let filter = JqFilter::compile("empty").unwrap();
+    let mut reader = JsonLinesReader::new(&b"1\n2\n"[..]);
+    assert_eq!(reader.read_jq(&filter).unwrap(), Some(Vec::new()));
+    assert_eq!(reader.read_jq(&filter).unwrap(), Some(Vec::new()));
+    assert_eq!(reader.read_jq(&filter).unwrap(), None);
+}
+
+#[test]
+fn test_jq_writer() {
+    let filter = JqFilter::compile(".x, .y").unwrap();
+    let mut writer = JsonLinesJqWriter::new(Vec::new(), filter);
+    assert_eq!(writer.write(&Point { x: 1, y: 2 }).unwrap(), 2);
+    assert_eq!(
+        writer
+            .write_all([Point { x: 3, y: 4 }, Point { x: 5, y: 6 }])
+            .unwrap(),
+        4
+    );
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), b"1\n2\n3\n4\n5\n6\n");
+}
+
+#[test]
+fn test_jq_writer_error_writes_nothing() {
+    let filter = JqFilter::compile(".x, error(\"boom\")").unwrap();
+    let mut writer = JsonLinesJqWriter::new(Vec::new(), filter);
+    let e = writer.write(&Point { x: 1, y: 2 }).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.to_string().contains("boom"), "{e}");
+    assert!(writer.get_ref().is_empty());
+}
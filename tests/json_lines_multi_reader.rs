@@ -0,0 +1,249 @@
+mod common;
+use crate::common::*;
+use assert_fs::TempDir;
+use assert_fs::fixture::{FileWriteStr, PathChild, PathCreateDir};
+use serde_jsonlines::{JsonLinesMultiReader, SourcedError, SourcedValue};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+fn summarize(
+    items: impl Iterator<Item = Result<SourcedValue<Point>, SourcedError>>,
+) -> Vec<(PathBuf, Option<u64>, Result<Point, ErrorKind>)> {
+    items
+        .map(|r| match r {
+            Ok(sv) => (sv.path.to_path_buf(), Some(sv.line), Ok(sv.value)),
+            Err(e) => (e.path.to_path_buf(), e.line, Err(e.source.kind())),
+        })
+        .collect()
+}
+
+fn setup() -> TempDir {
+    let tmpdir = TempDir::new().expect("creating temporary directory should succeed");
+    tmpdir
+        .child("a.jsonl")
+        .write_str("{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n")
+        .expect("writing file should succeed");
+    tmpdir
+        .child("b.jsonl")
+        .write_str("{\"x\":5,\"y\":6}\nNot JSON\n{\"x\":7,\"y\":8}\n")
+        .expect("writing file should succeed");
+    tmpdir
+        .child("c.ndjson")
+        .write_str("{\"x\":9,\"y\":10}\n")
+        .expect("writing file should succeed");
+    tmpdir
+        .child("notes.txt")
+        .write_str("Not JSON Lines\n")
+        .expect("writing file should succeed");
+    tmpdir
+}
+
+#[test]
+fn test_read_paths_in_order() {
+    let tmpdir = setup();
+    let reader = JsonLinesMultiReader::<Point>::new([
+        tmpdir.child("c.ndjson").path(),
+        tmpdir.child("a.jsonl").path(),
+    ]);
+    assert_eq!(
+        summarize(reader),
+        [
+            (
+                tmpdir.child("c.ndjson").to_path_buf(),
+                Some(1),
+                Ok(Point { x: 9, y: 10 })
+            ),
+            (
+                tmpdir.child("a.jsonl").to_path_buf(),
+                Some(1),
+                Ok(Point { x: 1, y: 2 })
+            ),
+            (
+                tmpdir.child("a.jsonl").to_path_buf(),
+                Some(2),
+                Ok(Point { x: 3, y: 4 })
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_read_dir() {
+    let tmpdir = setup();
+    let reader = JsonLinesMultiReader::<Point>::from_dir(&tmpdir).unwrap();
+    let a = tmpdir.child("a.jsonl").to_path_buf();
+    let b = tmpdir.child("b.jsonl").to_path_buf();
+    let c = tmpdir.child("c.ndjson").to_path_buf();
+    assert_eq!(
+        summarize(reader),
+        [
+            (a.clone(), Some(1), Ok(Point { x: 1, y: 2 })),
+            (a, Some(2), Ok(Point { x: 3, y: 4 })),
+            (b.clone(), Some(1), Ok(Point { x: 5, y: 6 })),
+            (b.clone(), Some(2), Err(ErrorKind::InvalidData)),
+            (b, Some(3), Ok(Point { x: 7, y: 8 })),
+            (c, Some(1), Ok(Point { x: 9, y: 10 })),
+        ]
+    );
+}
+
+#[test]
+fn test_dir_in_paths() {
+    let tmpdir = setup();
+    let subdir = tmpdir.child("sub");
+    subdir.create_dir_all().unwrap();
+    subdir
+        .child("z.jsonl")
+        .write_str("{\"x\":0,\"y\":0}\n")
+        .unwrap();
+    let reader =
+        JsonLinesMultiReader::<Point>::new([subdir.path(), tmpdir.child("c.ndjson").path()]);
+    assert_eq!(
+        summarize(reader),
+        [
+            (
+                subdir.child("z.jsonl").to_path_buf(),
+                Some(1),
+                Ok(Point { x: 0, y: 0 })
+            ),
+            (
+                tmpdir.child("c.ndjson").to_path_buf(),
+                Some(1),
+                Ok(Point { x: 9, y: 10 })
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_missing_file_continues() {
+    let tmpdir = setup();
+    let reader = JsonLinesMultiReader::<Point>::new([
+        tmpdir.child("nonexistent.jsonl").path(),
+        tmpdir.child("c.ndjson").path(),
+    ]);
+    assert_eq!(
+        summarize(reader),
+        [
+            (
+                tmpdir.child("nonexistent.jsonl").to_path_buf(),
+                None,
+                Err(ErrorKind::NotFound)
+            ),
+            (
+                tmpdir.child("c.ndjson").to_path_buf(),
+                Some(1),
+                Ok(Point { x: 9, y: 10 })
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_fail_fast() {
+    let tmpdir = setup();
+    let mut reader = JsonLinesMultiReader::<Point>::new([
+        tmpdir.child("b.jsonl").path(),
+        tmpdir.child("c.ndjson").path(),
+    ])
+    .fail_fast(true);
+    assert_eq!(reader.next().unwrap().unwrap().value, Point { x: 5, y: 6 });
+    let e = reader.next().unwrap().unwrap_err();
+    assert_eq!(e.line, Some(2));
+    assert_eq!(
+        e.to_string(),
+        format!(
+            "{}:2: expected value at line 1 column 1",
+            tmpdir.child("b.jsonl").display()
+        )
+    );
+    let ioerr = std::io::Error::from(e);
+    assert_eq!(ioerr.kind(), ErrorKind::InvalidData);
+    assert!(reader.next().is_none());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_sample04() {
+    let path = Path::new(DATA_DIR).join("sample04.txt");
+    let reader = JsonLinesMultiReader::<Structure>::new([&path]);
+    let lines = reader
+        .map(|r| match r {
+            Ok(sv) => Ok(sv.line),
+            Err(e) => Err(e.line),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            Ok(1),
+            Err(Some(2)),
+            Ok(3),
+            Err(Some(4)),
+            Err(Some(5)),
+            Ok(6)
+        ]
+    );
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_read_gzip() {
+    use flate2::{Compression, write::GzEncoder};
+    use std::fs::File;
+    use std::io::Write;
+    let tmpdir = setup();
+    let gzpath = tmpdir.child("b2.jsonl.gz");
+    {
+        let mut encoder = GzEncoder::new(File::create(&gzpath).unwrap(), Compression::default());
+        encoder.write_all(b"{\"x\":11,\"y\":12}\n").unwrap();
+        encoder.finish().unwrap();
+    }
+    let values = JsonLinesMultiReader::<Point>::from_dir(&tmpdir)
+        .unwrap()
+        .filter_map(Result::ok)
+        .map(|sv| (sv.path.file_name().unwrap().to_owned(), sv.value))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        [
+            ("a.jsonl".into(), Point { x: 1, y: 2 }),
+            ("a.jsonl".into(), Point { x: 3, y: 4 }),
+            ("b.jsonl".into(), Point { x: 5, y: 6 }),
+            ("b.jsonl".into(), Point { x: 7, y: 8 }),
+            ("b2.jsonl.gz".into(), Point { x: 11, y: 12 }),
+            ("c.ndjson".into(), Point { x: 9, y: 10 }),
+        ]
+    );
+}
+
+#[cfg(feature = "glob")]
+#[test]
+fn test_read_glob() {
+    let tmpdir = setup();
+    let pattern = format!("{}/*.jsonl", tmpdir.path().display());
+    let lines = JsonLinesMultiReader::<Point>::from_glob(&pattern)
+        .unwrap()
+        .map(|r| match r {
+            Ok(sv) => (sv.path.file_name().unwrap().to_owned(), sv.line),
+            Err(e) => (e.path.file_name().unwrap().to_owned(), e.line.unwrap()),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            ("a.jsonl".into(), 1),
+            ("a.jsonl".into(), 2),
+            ("b.jsonl".into(), 1),
+            ("b.jsonl".into(), 2),
+            ("b.jsonl".into(), 3),
+        ]
+    );
+}
+
+#[cfg(feature = "glob")]
+#[test]
+fn test_bad_glob() {
+    let e = JsonLinesMultiReader::<Point>::from_glob("[").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+}
@@ -215,3 +215,256 @@ fn test_read_then_write_then_read() {
     );
     assert_eq!(reader.read::<Structure>().unwrap(), None);
 }
+
+#[test]
+fn test_read_seed() {
+    let mut reader = JsonLinesReader::new(&b"{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n"[..]);
+    assert_eq!(
+        reader.read_seed(Scaled(10)).unwrap(),
+        Some(Point { x: 10, y: 20 })
+    );
+    assert_eq!(
+        reader.read_seed(Scaled(-1)).unwrap(),
+        Some(Point { x: -3, y: -4 })
+    );
+    assert_eq!(reader.read_seed(Scaled(10)).unwrap(), None);
+}
+
+#[test]
+fn test_read_seed_invalid_json() {
+    let mut reader = JsonLinesReader::new(&b"{\"x\":1,\"y\":2} 3\n{\"x\":3,\"y\":4}\n"[..]);
+    let e = reader.read_seed(Scaled(2)).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+    assert_eq!(
+        reader.read_seed(Scaled(2)).unwrap(),
+        Some(Point { x: 6, y: 8 })
+    );
+}
+
+#[test]
+fn test_read_all_seeded() {
+    let text = "{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n{\"x\":5,\"y\":6}\n";
+    let mut factor = 0;
+    let items = JsonLinesReader::new(text.as_bytes())
+        .read_all_seeded(|| {
+            factor += 1;
+            Scaled(factor)
+        })
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            Point { x: 1, y: 2 },
+            Point { x: 6, y: 8 },
+            Point { x: 15, y: 18 },
+        ]
+    );
+}
+
+#[test]
+fn test_read_all_seeded_phantom() {
+    let items = JsonLinesReader::new(&b"{\"x\":1,\"y\":2}\n"[..])
+        .read_all_seeded(std::marker::PhantomData::<Point>::default)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(items, [Point { x: 1, y: 2 }]);
+}
+
+#[test]
+fn test_read_all_seeded_debug() {
+    let iter = JsonLinesReader::new(&b""[..]).read_all_seeded(|| Scaled(2));
+    assert!(format!("{iter:?}").contains("factory: \"<function>\""));
+}
+
+#[test]
+fn test_read_into() {
+    let fp = BufReader::new(File::open(Path::new(DATA_DIR).join("sample01.jsonl")).unwrap());
+    let mut reader = JsonLinesReader::new(fp);
+    let mut item = Structure {
+        name: String::new(),
+        size: 0,
+        on: false,
+    };
+    assert!(reader.read_into(&mut item).unwrap());
+    assert_eq!(
+        item,
+        Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        }
+    );
+    assert!(reader.read_into(&mut item).unwrap());
+    assert_eq!(
+        item,
+        Structure {
+            name: "Quux".into(),
+            size: 23,
+            on: false,
+        }
+    );
+    assert!(reader.read_into(&mut item).unwrap());
+    assert!(!reader.read_into(&mut item).unwrap());
+    assert_eq!(
+        item,
+        Structure {
+            name: "Gnusto Cleesh".into(),
+            size: 17,
+            on: true,
+        }
+    );
+}
+
+#[test]
+fn test_read_into_reuses_allocation() {
+    let mut reader = JsonLinesReader::new(&b"[1,2,3,4,5,6,7,8]\n[9]\n[10,11]\n"[..]);
+    let mut v = Vec::<i32>::new();
+    assert!(reader.read_into(&mut v).unwrap());
+    assert_eq!(v, [1, 2, 3, 4, 5, 6, 7, 8]);
+    let ptr = v.as_ptr();
+    let cap = v.capacity();
+    assert!(reader.read_into(&mut v).unwrap());
+    assert_eq!(v, [9]);
+    assert!(reader.read_into(&mut v).unwrap());
+    assert_eq!(v, [10, 11]);
+    assert_eq!(v.as_ptr(), ptr);
+    assert_eq!(v.capacity(), cap);
+}
+
+#[test]
+fn test_read_into_invalid_json() {
+    let mut reader = JsonLinesReader::new(&b"\"foo\"\n[1]\n\"bar\"\n"[..]);
+    let mut s = String::new();
+    assert!(reader.read_into(&mut s).unwrap());
+    assert_eq!(s, "foo");
+    let e = reader.read_into(&mut s).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+    assert!(reader.read_into(&mut s).unwrap());
+    assert_eq!(s, "bar");
+}
+
+#[test]
+fn test_read_into_then_read() {
+    let mut reader = JsonLinesReader::new(&b"{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n"[..]);
+    let mut p = Point { x: 0, y: 0 };
+    assert!(reader.read_into(&mut p).unwrap());
+    assert_eq!(p, Point { x: 1, y: 2 });
+    assert_eq!(reader.read::<Point>().unwrap(), Some(Point { x: 3, y: 4 }));
+    assert_eq!(reader.read::<Point>().unwrap(), None);
+}
+
+#[test]
+fn test_read_bom_not_stripped_by_default() {
+    let mut reader = JsonLinesReader::new(&b"\xEF\xBB\xBF{\"x\": 1, \"y\": 2}\n"[..]);
+    let e = reader.read::<Point>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_read_strip_bom() {
+    let input = &b"\xEF\xBB\xBF{\"x\": 1, \"y\": 2}\n\xEF\xBB\xBF{\"x\": 3, \"y\": 4}\n"[..];
+    let mut reader = JsonLinesReader::new(input).strip_bom(true);
+    assert_eq!(reader.read::<Point>().unwrap(), Some(Point { x: 1, y: 2 }));
+    // Only a BOM at the start of the input is stripped
+    let e = reader.read::<Point>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(reader.read::<Point>().unwrap(), None);
+}
+
+#[test]
+fn test_read_strip_bom_absent() {
+    let mut reader = JsonLinesReader::new(&b"{\"x\": 1, \"y\": 2}\n"[..]).strip_bom(true);
+    assert_eq!(reader.read::<Point>().unwrap(), Some(Point { x: 1, y: 2 }));
+    assert_eq!(reader.read::<Point>().unwrap(), None);
+}
+
+#[test]
+fn test_read_crlf_tolerated_by_default() {
+    let reader = JsonLinesReader::new(&b"{\"x\": 1, \"y\": 2}\r\n{\"x\": 3, \"y\": 4}\r\n"[..]);
+    let items = reader
+        .read_all::<Point>()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(items, [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+}
+
+#[test]
+fn test_read_strict_line_endings() {
+    let input = &b"{\"x\": 1, \"y\": 2}\r\n{\"x\": 3,\r \"y\": 4}\n{\"x\": 5, \"y\": 6}\n"[..];
+    let mut reader = JsonLinesReader::new(input).strict_line_endings(true);
+    let e = reader.read::<Point>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "line terminated by CR LF");
+    let e = reader.read::<Point>().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "carriage return in line");
+    assert_eq!(reader.read::<Point>().unwrap(), Some(Point { x: 5, y: 6 }));
+    assert_eq!(reader.read::<Point>().unwrap(), None);
+}
+
+#[test]
+fn test_read_strict_line_endings_and_strip_bom() {
+    let input = &b"\xEF\xBB\xBF{\"name\": \"Foo Bar\", \"size\": 42, \"on\": true}\n"[..];
+    let mut reader = JsonLinesReader::new(input)
+        .strip_bom(true)
+        .strict_line_endings(true);
+    assert_eq!(
+        reader.read::<Structure>().unwrap(),
+        Some(Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        })
+    );
+    assert_eq!(reader.read::<Structure>().unwrap(), None);
+}
+
+#[test]
+fn test_read_with_header() {
+    let input = concat!(
+        "{\"name\": \"Foo Bar\", \"size\": 42, \"on\": true}\n",
+        "{\"x\": 1, \"y\": 2}\n",
+        "{\"x\": 3, \"y\": 4}\n",
+    );
+    let (header, iter) = JsonLinesReader::new(input.as_bytes())
+        .read_with_header::<Structure, Point>()
+        .unwrap();
+    assert_eq!(
+        header,
+        Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        }
+    );
+    let items = iter.collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(items, [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+}
+
+#[test]
+fn test_read_with_header_only() {
+    let (header, mut iter) = JsonLinesReader::new(&b"{\"x\": 1, \"y\": 2}\n"[..])
+        .read_with_header::<Point, Structure>()
+        .unwrap();
+    assert_eq!(header, Point { x: 1, y: 2 });
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_read_with_header_empty() {
+    let e = JsonLinesReader::new(&b""[..])
+        .read_with_header::<Point, Structure>()
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_read_with_header_invalid() {
+    let e = JsonLinesReader::new(&b"{\"x\": 1}\n{\"x\": 1, \"y\": 2}\n"[..])
+        .read_with_header::<Point, Point>()
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
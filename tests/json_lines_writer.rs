@@ -2,7 +2,7 @@ mod common;
 use crate::common::*;
 use assert_fs::NamedTempFile;
 use assert_fs::assert::PathAssert;
-use serde_jsonlines::JsonLinesWriter;
+use serde_jsonlines::{JsonLinesWriter, LineTerminator};
 use std::fs::File;
 use std::io::{Seek, Write};
 use std::iter::empty;
@@ -137,3 +137,52 @@ fn test_write_then_back_up_then_write() {
     }
     tmpfile.assert("{\"name\":\"Gnusto Cleesh\",\"size\":17,\"on\":true}\n");
 }
+
+#[test]
+fn test_write_crlf() {
+    let mut writer = JsonLinesWriter::new(Vec::new()).line_terminator(LineTerminator::CrLf);
+    writer
+        .write(&Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        })
+        .unwrap();
+    writer.write_all([1, 2]).unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        b"{\"name\":\"Foo Bar\",\"size\":42,\"on\":true}\r\n1\r\n2\r\n"
+    );
+}
+
+#[test]
+fn test_with_header() {
+    let mut writer = JsonLinesWriter::new(Vec::new())
+        .line_terminator(LineTerminator::CrLf)
+        .with_header(&Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        })
+        .unwrap();
+    writer.write_all([[1, 2], [3, 4]]).unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        b"{\"name\":\"Foo Bar\",\"size\":42,\"on\":true}\r\n[1,2]\r\n[3,4]\r\n"
+    );
+}
+
+#[test]
+fn test_with_header_no_records() {
+    let writer = JsonLinesWriter::new(Vec::new())
+        .with_header(&Structure {
+            name: "Quux".into(),
+            size: 23,
+            on: false,
+        })
+        .unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        b"{\"name\":\"Quux\",\"size\":23,\"on\":false}\n"
+    );
+}
@@ -0,0 +1,235 @@
+mod common;
+use crate::common::*;
+use assert_fs::NamedTempFile;
+use assert_fs::assert::PathAssert;
+use serde_jsonlines::{JsonLinesWriterHandle, json_lines};
+use std::fs::File;
+use std::io::{BufWriter, ErrorKind, Result, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+fn require_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_handle_is_send_sync() {
+    // `Rc` is neither `Send` nor `Sync`, but the handle should still be
+    require_send_sync::<JsonLinesWriterHandle<std::rc::Rc<Point>>>();
+}
+
+#[test]
+fn test_write_and_shutdown() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    let fp = BufWriter::new(File::create(&tmpfile).unwrap());
+    let handle = JsonLinesWriterHandle::spawn(fp, 4).unwrap();
+    handle
+        .write_all([
+            Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            },
+            Structure {
+                name: "Quux".into(),
+                size: 23,
+                on: false,
+            },
+            Structure {
+                name: "Gnusto Cleesh".into(),
+                size: 17,
+                on: true,
+            },
+        ])
+        .unwrap();
+    handle.shutdown().unwrap();
+    tmpfile.assert(concat!(
+        "{\"name\":\"Foo Bar\",\"size\":42,\"on\":true}\n",
+        "{\"name\":\"Quux\",\"size\":23,\"on\":false}\n",
+        "{\"name\":\"Gnusto Cleesh\",\"size\":17,\"on\":true}\n",
+    ));
+}
+
+#[test]
+fn test_flush_barrier() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    let fp = BufWriter::new(File::create(&tmpfile).unwrap());
+    let handle = JsonLinesWriterHandle::spawn(fp, 16).unwrap();
+    handle.write(&Point { x: 1, y: 2 }).unwrap();
+    handle.flush().unwrap();
+    tmpfile.assert("{\"x\":1,\"y\":2}\n");
+    handle.write(&Point { x: 3, y: 4 }).unwrap();
+    handle.flush().unwrap();
+    tmpfile.assert("{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n");
+    handle.shutdown().unwrap();
+}
+
+#[test]
+fn test_many_threads() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    let fp = BufWriter::new(File::create(&tmpfile).unwrap());
+    let handle = JsonLinesWriterHandle::spawn(fp, 0).unwrap();
+    thread::scope(|s| {
+        for x in 0..8 {
+            let handle = handle.clone();
+            s.spawn(move || {
+                for y in 0..100 {
+                    handle.write(&Point { x, y }).unwrap();
+                }
+            });
+        }
+    });
+    handle.shutdown().unwrap();
+    let mut points = json_lines::<Point, _>(&tmpfile)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(points.len(), 800);
+    points.sort_by_key(|p| (p.x, p.y));
+    let expected = (0..8)
+        .flat_map(|x| (0..100).map(move |y| Point { x, y }))
+        .collect::<Vec<_>>();
+    assert_eq!(points, expected);
+}
+
+#[test]
+fn test_use_after_shutdown() {
+    let handle = JsonLinesWriterHandle::spawn(std::io::sink(), 1).unwrap();
+    let handle2 = handle.clone();
+    handle.shutdown().unwrap();
+    let e = handle2.write(&Point { x: 1, y: 2 }).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::BrokenPipe);
+    let e = handle2.flush().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::BrokenPipe);
+    handle2.shutdown().unwrap();
+}
+
+/// A writer that is slow to flush and that records when it has been dropped
+struct SlowWriter {
+    dropped: Arc<AtomicBool>,
+}
+
+impl Write for SlowWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        thread::sleep(Duration::from_millis(200));
+        Ok(())
+    }
+}
+
+impl Drop for SlowWriter {
+    fn drop(&mut self) {
+        self.dropped.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_concurrent_shutdown() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let writer = SlowWriter {
+        dropped: Arc::clone(&dropped),
+    };
+    let handle = JsonLinesWriterHandle::spawn(writer, 4).unwrap();
+    handle.write(&Point { x: 1, y: 2 }).unwrap();
+    thread::scope(|s| {
+        for _ in 0..4 {
+            let handle = handle.clone();
+            let dropped = &dropped;
+            s.spawn(move || {
+                handle.shutdown().unwrap();
+                assert!(dropped.load(Ordering::SeqCst));
+            });
+        }
+    });
+}
+
+struct FailingWriter {
+    writes: usize,
+}
+
+impl Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.writes += 1;
+        if self.writes == 2 {
+            Err(std::io::Error::other("Write number two failed"))
+        } else {
+            Ok(buf.len())
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_error_reported_on_flush() {
+    let handle = JsonLinesWriterHandle::spawn(FailingWriter { writes: 0 }, 4).unwrap();
+    handle.write(&Point { x: 1, y: 2 }).unwrap();
+    handle.write(&Point { x: 3, y: 4 }).unwrap();
+    handle.write(&Point { x: 5, y: 6 }).unwrap();
+    let e = handle.flush().unwrap_err();
+    assert_eq!(e.to_string(), "Write number two failed");
+    handle.write(&Point { x: 7, y: 8 }).unwrap();
+    handle.flush().unwrap();
+    handle.shutdown().unwrap();
+}
+
+#[test]
+fn test_error_reported_on_shutdown() {
+    let handle = JsonLinesWriterHandle::spawn(FailingWriter { writes: 0 }, 4).unwrap();
+    handle.write(&Point { x: 1, y: 2 }).unwrap();
+    handle.write(&Point { x: 3, y: 4 }).unwrap();
+    let e = handle.shutdown().unwrap_err();
+    assert_eq!(e.to_string(), "Write number two failed");
+}
+
+/// A writer that accepts `limit` bytes, fails once (leaving any line that
+/// straddles the limit only partially written), and then accepts everything
+struct TruncatingWriter {
+    output: Arc<std::sync::Mutex<Vec<u8>>>,
+    limit: Option<usize>,
+}
+
+impl Write for TruncatingWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut output = self.output.lock().expect("mutex should not be poisoned");
+        let room = self.limit.map_or(usize::MAX, |limit| limit - output.len());
+        if room == 0 {
+            self.limit = None;
+            return Err(std::io::Error::other("Out of room"));
+        }
+        let n = buf.len().min(room);
+        output.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_lines_after_partial_write_discarded() {
+    let output = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let writer = TruncatingWriter {
+        output: Arc::clone(&output),
+        limit: Some(20),
+    };
+    let handle = JsonLinesWriterHandle::spawn(writer, 4).unwrap();
+    handle.write(&Point { x: 1, y: 2 }).unwrap();
+    handle.write(&Point { x: 3, y: 4 }).unwrap();
+    handle.write(&Point { x: 5, y: 6 }).unwrap();
+    let e = handle.flush().unwrap_err();
+    assert_eq!(e.to_string(), "Out of room");
+    assert_eq!(*output.lock().unwrap(), b"{\"x\":1,\"y\":2}\n{\"x\":3");
+    handle.write(&Point { x: 7, y: 8 }).unwrap();
+    handle.shutdown().unwrap();
+    assert_eq!(
+        *output.lock().unwrap(),
+        b"{\"x\":1,\"y\":2}\n{\"x\":3\n{\"x\":7,\"y\":8}\n"
+    );
+}
@@ -0,0 +1,79 @@
+//! Tests that concurrent calls to `append_json_lines_locked()` from separate
+//! processes do not interleave their output.
+//!
+//! The parent test re-executes this test binary several times, running only
+//! `child_append` in each child process; the location of the shared output
+//! file and the ID of each child are passed via environment variables.
+use assert_fs::NamedTempFile;
+use serde::{Deserialize, Serialize};
+use serde_jsonlines::{append_json_lines_locked, json_lines};
+use std::collections::BTreeMap;
+use std::env;
+use std::process::{Command, Stdio};
+
+const PATH_VAR: &str = "SERDE_JSONLINES_TEST_LOCK_PATH";
+const WORKER_VAR: &str = "SERDE_JSONLINES_TEST_LOCK_WORKER";
+const WORKERS: u32 = 8;
+const RECORDS: u32 = 50;
+// Large enough that a single record cannot be written in one `write(2)` by a
+// `BufWriter` with its default capacity
+const PAYLOAD_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct Record {
+    worker: u32,
+    seq: u32,
+    payload: String,
+}
+
+#[test]
+fn child_append() {
+    let (Ok(path), Ok(worker)) = (env::var(PATH_VAR), env::var(WORKER_VAR)) else {
+        return;
+    };
+    let worker = worker.parse::<u32>().unwrap();
+    for seq in 0..RECORDS {
+        let payload = char::from(b'a' + u8::try_from(worker % 26).unwrap())
+            .to_string()
+            .repeat(PAYLOAD_SIZE);
+        append_json_lines_locked(
+            &path,
+            [Record {
+                worker,
+                seq,
+                payload,
+            }],
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_concurrent_locked_appends() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    let exe = env::current_exe().unwrap();
+    let children = (0..WORKERS)
+        .map(|worker| {
+            Command::new(&exe)
+                .args(["child_append", "--exact", "--quiet", "--test-threads=1"])
+                .env(PATH_VAR, tmpfile.path())
+                .env(WORKER_VAR, worker.to_string())
+                .stdout(Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    for mut child in children {
+        assert!(child.wait().unwrap().success(), "child process failed");
+    }
+    let mut next_seq = BTreeMap::new();
+    for r in json_lines::<Record, _>(&tmpfile).unwrap() {
+        let record = r.unwrap();
+        let expected = next_seq.entry(record.worker).or_insert(0);
+        assert_eq!(record.seq, *expected, "records out of order");
+        *expected += 1;
+        assert_eq!(record.payload.len(), PAYLOAD_SIZE);
+    }
+    assert_eq!(next_seq.len(), usize::try_from(WORKERS).unwrap());
+    assert!(next_seq.values().all(|&n| n == RECORDS));
+}
@@ -0,0 +1,247 @@
+mod common;
+use crate::common::*;
+use assert_fs::assert::PathAssert;
+use assert_fs::fixture::{FileWriteStr, PathChild};
+use assert_fs::{NamedTempFile, TempDir};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use serde_jsonlines::{JsonLinesReader, Migrations, migrate_file};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+
+#[derive(Deserialize)]
+struct StructureV2 {
+    name: String,
+    size: i32,
+}
+
+#[derive(Serialize)]
+struct StructureV3 {
+    name: String,
+    size: i32,
+    on: bool,
+}
+
+fn migrations() -> Migrations {
+    Migrations::new("/v")
+        .expect("pointer should be valid")
+        .default_version(1)
+        .step(1, |mut v: Value| {
+            if let Some(obj) = v.as_object_mut()
+                && let Some(label) = obj.remove("label")
+            {
+                obj.insert("name".into(), label);
+            }
+            Ok(v)
+        })
+        .typed_step(2, |old: StructureV2| StructureV3 {
+            name: old.name,
+            size: old.size,
+            on: false,
+        })
+}
+
+const INPUT: &str = concat!(
+    "{\"label\": \"Foo Bar\", \"size\": 42}\n",
+    "{\"v\": 2, \"name\": \"Quux\", \"size\": 23}\n",
+    "{\"v\": 3, \"name\": \"Gnusto Cleesh\", \"size\": 17, \"on\": true}\n",
+    "{\"v\": 1, \"label\": \"Frotz\", \"size\": 5}\n",
+);
+
+#[test]
+fn test_latest_version() {
+    assert_eq!(migrations().latest_version(), 3);
+    let empty = Migrations::new("/v").unwrap();
+    assert_eq!(empty.latest_version(), 0);
+    assert_eq!(empty.default_version(4).latest_version(), 4);
+}
+
+#[test]
+fn test_invalid_pointer() {
+    let e = Migrations::new("v").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    let e = Migrations::new("").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_migrate_value() {
+    let value = migrations()
+        .migrate(json!({"label": "Foo Bar", "size": 42}), 1)
+        .unwrap();
+    assert_eq!(
+        value,
+        json!({"name": "Foo Bar", "size": 42, "on": false, "v": 3})
+    );
+}
+
+#[test]
+fn test_migrate_nested_version() {
+    let migrations = Migrations::new("/meta/version")
+        .unwrap()
+        .step(0, |mut v: Value| {
+            v["size"] = json!(0);
+            Ok(v)
+        });
+    let value = migrations
+        .migrate(json!({"meta": {"version": 0}, "name": "x"}), 0)
+        .unwrap();
+    assert_eq!(
+        value,
+        json!({"meta": {"version": 1}, "name": "x", "size": 0})
+    );
+    let e = migrations.migrate(json!({"name": "x"}), 0).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "cannot set schema version at /meta/version");
+}
+
+#[test]
+fn test_migrate_all() {
+    let items = JsonLinesReader::new(INPUT.as_bytes())
+        .migrate_all::<Structure>(migrations())
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: false,
+            },
+            Structure {
+                name: "Quux".into(),
+                size: 23,
+                on: false,
+            },
+            Structure {
+                name: "Gnusto Cleesh".into(),
+                size: 17,
+                on: true,
+            },
+            Structure {
+                name: "Frotz".into(),
+                size: 5,
+                on: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_read_migrated_errors() {
+    let input = concat!(
+        "{\"v\": 4, \"name\": \"Future\", \"size\": 1, \"on\": true}\n",
+        "{\"v\": \"two\", \"name\": \"Quux\", \"size\": 23}\n",
+        "{\"v\": 2, \"size\": 23}\n",
+        "{\"v\": 3, \"name\": \"Gnusto Cleesh\", \"size\": 17, \"on\": true}\n",
+    );
+    let migrations = migrations();
+    let mut reader = JsonLinesReader::new(input.as_bytes());
+    let e = reader.read_migrated::<Structure>(&migrations).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        e.to_string(),
+        "schema version 4 is newer than latest version 3"
+    );
+    let e = reader.read_migrated::<Structure>(&migrations).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "invalid schema version at /v: \"two\"");
+    let e = reader.read_migrated::<Structure>(&migrations).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        reader.read_migrated::<Structure>(&migrations).unwrap(),
+        Some(Structure {
+            name: "Gnusto Cleesh".into(),
+            size: 17,
+            on: true,
+        })
+    );
+    assert_eq!(
+        reader.read_migrated::<Structure>(&migrations).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_missing_step() {
+    let migrations = Migrations::new("/v").unwrap().step(2, Ok);
+    let e = migrations.migrate(json!({"v": 1}), 1).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "no migration from schema version 1");
+}
+
+#[test]
+fn test_migrate_file() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    tmpfile.write_str(INPUT).unwrap();
+    let report = migrate_file(&tmpfile, &migrations()).unwrap();
+    assert_eq!(report.lines, 4);
+    assert_eq!(report.latest_version, 3);
+    assert_eq!(report.counts, BTreeMap::from([(1, 2), (2, 1), (3, 1)]));
+    assert_eq!(report.migrated(), 3);
+    tmpfile.assert(concat!(
+        "{\"name\":\"Foo Bar\",\"on\":false,\"size\":42,\"v\":3}\n",
+        "{\"name\":\"Quux\",\"on\":false,\"size\":23,\"v\":3}\n",
+        "{\"v\": 3, \"name\": \"Gnusto Cleesh\", \"size\": 17, \"on\": true}\n",
+        "{\"name\":\"Frotz\",\"on\":false,\"size\":5,\"v\":3}\n",
+    ));
+    let report = migrate_file(&tmpfile, &migrations()).unwrap();
+    assert_eq!(report.counts, BTreeMap::from([(3, 4)]));
+    assert_eq!(report.migrated(), 0);
+}
+
+#[test]
+fn test_migrate_file_error() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    let input = concat!(
+        "{\"label\": \"Foo Bar\", \"size\": 42}\n",
+        "{\"v\": 2, \"label\": \"Quux\", \"size\": 23}\n",
+    );
+    tmpfile.write_str(input).unwrap();
+    let e = migrate_file(&tmpfile, &migrations()).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.to_string().starts_with("line 2: missing field `name`"));
+    tmpfile.assert(input);
+    let parent = tmpfile.path().parent().unwrap();
+    assert_eq!(std::fs::read_dir(parent).unwrap().count(), 1);
+}
+
+#[test]
+fn test_migrate_file_preserves_terminators() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    tmpfile
+        .write_str("{\"label\": \"Foo Bar\", \"size\": 42}\r\n{\"v\": 3, \"name\": \"Quux\", \"size\": 23, \"on\": true}\n{\"v\": 2, \"name\": \"Frotz\", \"size\": 5}")
+        .unwrap();
+    migrate_file(&tmpfile, &migrations()).unwrap();
+    tmpfile.assert(concat!(
+        "{\"name\":\"Foo Bar\",\"on\":false,\"size\":42,\"v\":3}\r\n",
+        "{\"v\": 3, \"name\": \"Quux\", \"size\": 23, \"on\": true}\n",
+        "{\"name\":\"Frotz\",\"on\":false,\"size\":5,\"v\":3}",
+    ));
+}
+
+#[test]
+fn test_migrate_file_stale_temp_file() {
+    let tmpdir = TempDir::new().unwrap();
+    let file = tmpdir.child("test.jsonl");
+    file.write_str(INPUT).unwrap();
+    let stale = tmpdir.child(format!("test.jsonl.migrate-{}-0.tmp", std::process::id()));
+    stale.write_str("leftover\n").unwrap();
+    let report = migrate_file(&file, &migrations()).unwrap();
+    assert_eq!(report.migrated(), 3);
+    stale.assert("leftover\n");
+    assert_eq!(std::fs::read_dir(tmpdir.path()).unwrap().count(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_migrate_file_preserves_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    tmpfile.write_str(INPUT).unwrap();
+    std::fs::set_permissions(&tmpfile, std::fs::Permissions::from_mode(0o640)).unwrap();
+    migrate_file(&tmpfile, &migrations()).unwrap();
+    let mode = std::fs::metadata(&tmpfile).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+}
@@ -0,0 +1,207 @@
+mod common;
+use crate::common::*;
+use assert_fs::TempDir;
+use assert_fs::fixture::{FileWriteStr, PathChild};
+use serde_jsonlines::PartitionedJsonLinesWriter;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::read_to_string;
+use std::io::ErrorKind;
+
+fn structures() -> Vec<Structure> {
+    vec![
+        Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        },
+        Structure {
+            name: "Quux".into(),
+            size: 23,
+            on: false,
+        },
+        Structure {
+            name: "Gnusto Cleesh".into(),
+            size: 17,
+            on: true,
+        },
+    ]
+}
+
+fn require_send<T: Send>() {}
+
+#[test]
+fn test_writer_is_send() {
+    // `Rc` is not `Send`, but the writer should still be, as values are only
+    // borrowed
+    require_send::<PartitionedJsonLinesWriter<String, std::rc::Rc<Point>>>();
+}
+
+#[test]
+fn test_partition_by_string() {
+    let tmpdir = TempDir::new().unwrap();
+    let template = format!("{}/on={{on}}/part.jsonl", tmpdir.path().display());
+    let mut writer = PartitionedJsonLinesWriter::new(template, |s: &Structure| s.on.to_string());
+    writer.write_all(structures()).unwrap();
+    let paths = writer.finish().unwrap();
+    assert_eq!(
+        paths,
+        HashMap::from([
+            (
+                String::from("true"),
+                tmpdir.child("on=true").child("part.jsonl").to_path_buf()
+            ),
+            (
+                String::from("false"),
+                tmpdir.child("on=false").child("part.jsonl").to_path_buf()
+            ),
+        ])
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("on=true").child("part.jsonl")).unwrap(),
+        concat!(
+            "{\"name\":\"Foo Bar\",\"size\":42,\"on\":true}\n",
+            "{\"name\":\"Gnusto Cleesh\",\"size\":17,\"on\":true}\n",
+        )
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("on=false").child("part.jsonl")).unwrap(),
+        "{\"name\":\"Quux\",\"size\":23,\"on\":false}\n"
+    );
+}
+
+#[test]
+fn test_partition_by_map() {
+    let tmpdir = TempDir::new().unwrap();
+    let template = format!("{}/{{x}}/{{y}}.jsonl", tmpdir.path().display());
+    let mut writer = PartitionedJsonLinesWriter::new(template, |p: &Point| {
+        BTreeMap::from([
+            (String::from("x"), (p.x % 2).to_string()),
+            (String::from("y"), (p.y % 3).to_string()),
+        ])
+    });
+    for x in 0..4 {
+        for y in 0..3 {
+            writer.write(&Point { x, y }).unwrap();
+        }
+    }
+    let paths = writer.finish().unwrap();
+    assert_eq!(paths.len(), 6);
+    assert_eq!(
+        read_to_string(tmpdir.child("1").child("2.jsonl")).unwrap(),
+        "{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":2}\n"
+    );
+}
+
+#[test]
+fn test_max_open_files() {
+    let tmpdir = TempDir::new().unwrap();
+    let template = format!("{}/{{key}}.jsonl", tmpdir.path().display());
+    let mut writer =
+        PartitionedJsonLinesWriter::new(template, |p: &Point| p.x.to_string()).max_open_files(2);
+    for y in 0..3 {
+        for x in 0..5 {
+            writer.write(&Point { x, y }).unwrap();
+            assert!(writer.open_files() <= 2);
+        }
+    }
+    writer.finish().unwrap();
+    for x in 0..5 {
+        assert_eq!(
+            read_to_string(tmpdir.child(format!("{x}.jsonl"))).unwrap(),
+            format!("{{\"x\":{x},\"y\":0}}\n{{\"x\":{x},\"y\":1}}\n{{\"x\":{x},\"y\":2}}\n")
+        );
+    }
+}
+
+#[test]
+fn test_existing_file_truncated() {
+    let tmpdir = TempDir::new().unwrap();
+    tmpdir.child("a.jsonl").write_str("Old data\n").unwrap();
+    let template = format!("{}/{{key}}.jsonl", tmpdir.path().display());
+    let mut writer = PartitionedJsonLinesWriter::new(template, |_: &Point| String::from("a"));
+    writer.write(&Point { x: 1, y: 2 }).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        read_to_string(tmpdir.child("a.jsonl")).unwrap(),
+        "{\"x\":1,\"y\":2}\n"
+    );
+}
+
+#[test]
+fn test_escaped_braces() {
+    let tmpdir = TempDir::new().unwrap();
+    let template = format!("{}/{{{{{{key}}}}}}.jsonl", tmpdir.path().display());
+    let mut writer = PartitionedJsonLinesWriter::new(template, |_: &Point| String::from("a"));
+    writer.write(&Point { x: 1, y: 2 }).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        read_to_string(tmpdir.child("{a}.jsonl")).unwrap(),
+        "{\"x\":1,\"y\":2}\n"
+    );
+}
+
+#[test]
+fn test_missing_field() {
+    let tmpdir = TempDir::new().unwrap();
+    let template = format!("{}/{{x}}/{{z}}.jsonl", tmpdir.path().display());
+    let mut writer = PartitionedJsonLinesWriter::new(template, |p: &Point| {
+        BTreeMap::from([(String::from("x"), p.x.to_string())])
+    });
+    let e = writer.write(&Point { x: 1, y: 2 }).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    assert_eq!(e.to_string(), "partition key has no field \"z\"");
+}
+
+#[test]
+fn test_bad_template() {
+    for template in ["{key", "key}.jsonl"] {
+        let mut writer = PartitionedJsonLinesWriter::new(template, |_: &Point| String::from("a"));
+        let e = writer.write(&Point { x: 1, y: 2 }).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn test_unsafe_field_values() {
+    let tmpdir = TempDir::new().unwrap();
+    let template = format!("{}/{{key}}/part.jsonl", tmpdir.path().display());
+    for bad in ["..", ".", "", "a/b", "../../etc/x"] {
+        let mut writer =
+            PartitionedJsonLinesWriter::new(template.clone(), move |_: &Point| bad.to_string());
+        let e = writer.write(&Point { x: 1, y: 2 }).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        assert!(writer.finish().unwrap().is_empty());
+    }
+    let e = PartitionedJsonLinesWriter::new(template, |_: &Point| String::from(".."))
+        .write(&Point { x: 1, y: 2 })
+        .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "partition key field \"key\" has invalid path component \"..\""
+    );
+    assert_eq!(std::fs::read_dir(tmpdir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_keys_sharing_path() {
+    let tmpdir = TempDir::new().unwrap();
+    let template = format!("{}/{{x}}.jsonl", tmpdir.path().display());
+    let mut writer = PartitionedJsonLinesWriter::new(template, |p: &Point| {
+        BTreeMap::from([
+            (String::from("x"), (p.x % 2).to_string()),
+            (String::from("y"), p.y.to_string()),
+        ])
+    });
+    for y in 0..3 {
+        for x in 0..2 {
+            writer.write(&Point { x, y }).unwrap();
+        }
+    }
+    assert_eq!(writer.open_files(), 2);
+    let paths = writer.finish().unwrap();
+    assert_eq!(paths.len(), 6);
+    assert_eq!(
+        read_to_string(tmpdir.child("1.jsonl")).unwrap(),
+        "{\"x\":1,\"y\":0}\n{\"x\":1,\"y\":1}\n{\"x\":1,\"y\":2}\n"
+    );
+}
@@ -0,0 +1,156 @@
+use serde::Deserialize;
+use serde_json::{Value, json};
+use serde_jsonlines::{JsonLinesReader, Projection};
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Result};
+
+static EVENTS: &str = concat!(
+    "{\"ts\": 1, \"user\": {\"id\": 42, \"name\": \"Alice\"}, \"tags\": [\"a\", \"b\"]}\n",
+    "{\"ts\": 2, \"user\": {\"name\": \"Bob\"}, \"payload\": {\"big\": [1, 2, {\"x\": null}]}}\n",
+    "{\"ts\": 3, \"user\": null, \"tags\": []}\n",
+);
+
+#[test]
+fn test_project_tuple() {
+    let projection = Projection::new(["/ts", "/user/id"]).unwrap();
+    let items = JsonLinesReader::new(EVENTS.as_bytes())
+        .project_all::<(u64, Option<u64>)>(projection)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(items, [(1, Some(42)), (2, None), (3, None)]);
+}
+
+#[test]
+fn test_project_map() {
+    let projection = Projection::new(["/user/name", "/tags/1"]).unwrap();
+    let items = JsonLinesReader::new(EVENTS.as_bytes())
+        .project_all::<BTreeMap<String, Value>>(projection)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            BTreeMap::from([
+                ("/user/name".into(), json!("Alice")),
+                ("/tags/1".into(), json!("b")),
+            ]),
+            BTreeMap::from([("/user/name".into(), json!("Bob"))]),
+            BTreeMap::new(),
+        ]
+    );
+}
+
+#[test]
+fn test_project_struct() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Event {
+        #[serde(rename = "/ts")]
+        ts: u64,
+        #[serde(rename = "/user/name")]
+        name: Option<String>,
+    }
+
+    let projection = Projection::new(["/ts", "/user/name"]).unwrap();
+    let mut reader = JsonLinesReader::new(EVENTS.as_bytes());
+    assert_eq!(
+        reader.read_projected::<Event>(&projection).unwrap(),
+        Some(Event {
+            ts: 1,
+            name: Some("Alice".into()),
+        })
+    );
+    assert_eq!(
+        reader.read_projected::<Event>(&projection).unwrap(),
+        Some(Event {
+            ts: 2,
+            name: Some("Bob".into()),
+        })
+    );
+    assert_eq!(
+        reader.read_projected::<Event>(&projection).unwrap(),
+        Some(Event { ts: 3, name: None })
+    );
+    assert_eq!(reader.read_projected::<Event>(&projection).unwrap(), None);
+}
+
+#[test]
+fn test_project_nested_pointers() {
+    let projection = Projection::new(["/user", "/user/id", "", "/payload/big/2/x"]).unwrap();
+    let mut reader = JsonLinesReader::new(EVENTS.as_bytes());
+    let (user, id, _, x) = reader
+        .read_projected::<(Value, Value, Value, Value)>(&projection)
+        .unwrap()
+        .unwrap();
+    assert_eq!(user, json!({"id": 42, "name": "Alice"}));
+    assert_eq!(id, json!(42));
+    assert_eq!(x, Value::Null);
+    let (_, _, whole, x) = reader
+        .read_projected::<(Value, Value, Value, Value)>(&projection)
+        .unwrap()
+        .unwrap();
+    assert_eq!(whole["ts"], json!(2));
+    assert_eq!(x, Value::Null);
+    let fields = reader
+        .read_projected::<BTreeMap<String, Value>>(&projection)
+        .unwrap()
+        .unwrap();
+    assert_eq!(fields.get("/payload/big/2/x"), None);
+    assert_eq!(fields.get("/user"), Some(&Value::Null));
+}
+
+#[test]
+fn test_project_escaped_pointer() {
+    let projection = Projection::new(["/a~1b", "/c~0d"]).unwrap();
+    let mut reader = JsonLinesReader::new(&b"{\"a/b\": 1, \"c~d\": 2, \"ab\": 3}\n"[..]);
+    assert_eq!(
+        reader.read_projected::<(i32, i32)>(&projection).unwrap(),
+        Some((1, 2))
+    );
+}
+
+#[test]
+fn test_project_array_index() {
+    let projection = Projection::new(["/0/1", "/01", "/2"]).unwrap();
+    let mut reader = JsonLinesReader::new(&b"[[1, 2], \"x\", \"y\"]\n"[..]);
+    assert_eq!(
+        reader
+            .read_projected::<(i32, Option<String>, String)>(&projection)
+            .unwrap(),
+        Some((2, None, "y".into()))
+    );
+}
+
+#[test]
+fn test_project_invalid_json_in_skipped_subtree() {
+    let projection = Projection::new(["/ts"]).unwrap();
+    let mut reader = JsonLinesReader::new(&b"{\"ts\": 1, \"junk\": [1, 2,]}\n{\"ts\": 2}\n"[..]);
+    let e = reader.read_projected::<(u64,)>(&projection).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+    assert_eq!(
+        reader.read_projected::<(u64,)>(&projection).unwrap(),
+        Some((2,))
+    );
+}
+
+#[test]
+fn test_project_wrong_type() {
+    let projection = Projection::new(["/ts"]).unwrap();
+    let mut reader = JsonLinesReader::new(&b"{\"ts\": \"one\"}\n"[..]);
+    let e = reader.read_projected::<(u64,)>(&projection).unwrap_err();
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+}
+
+#[test]
+fn test_invalid_pointers() {
+    for ptr in ["ts", "/a~2", "/a~"] {
+        let e = Projection::new([ptr]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn test_pointers() {
+    let projection = Projection::new(["/a", "/b/c"]).unwrap();
+    assert_eq!(projection.pointers(), ["/a", "/b/c"]);
+}
@@ -0,0 +1,251 @@
+#![cfg(feature = "arrow")]
+mod common;
+use crate::common::*;
+use arrow::array::{AsArray, RecordBatch};
+use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+use assert_fs::NamedTempFile;
+use assert_fs::assert::PathAssert;
+use assert_fs::fixture::FileWriteStr;
+use serde_jsonlines::{
+    BatchOptions, JsonLinesReader, JsonLinesWriter, LineTerminator, json_lines,
+    json_lines_to_parquet, parquet_to_json_lines,
+};
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+use std::path::Path;
+use std::sync::Arc;
+
+fn sample01() -> JsonLinesReader<BufReader<File>> {
+    let fp =
+        File::open(Path::new(DATA_DIR).join("sample01.jsonl")).expect("sample file should open");
+    JsonLinesReader::new(BufReader::new(fp))
+}
+
+#[test]
+fn test_read_batches_inferred() {
+    let mut iter = sample01().read_batches(BatchOptions::new().batch_size(2));
+    assert!(iter.schema().is_none());
+    let batch = iter.next().unwrap().unwrap();
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(
+        **iter.schema().unwrap(),
+        Schema::new(vec![
+            Field::new("name", DataType::Utf8, true),
+            Field::new("on", DataType::Boolean, true),
+            Field::new("size", DataType::Int64, true),
+        ])
+    );
+    let names = batch.column_by_name("name").unwrap().as_string::<i32>();
+    assert_eq!(names.value(0), "Foo Bar");
+    assert_eq!(names.value(1), "Quux");
+    let batch = iter.next().unwrap().unwrap();
+    assert_eq!(batch.num_rows(), 1);
+    let sizes = batch
+        .column_by_name("size")
+        .unwrap()
+        .as_primitive::<Int64Type>();
+    assert_eq!(sizes.value(0), 17);
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_read_batches_supplied_schema() {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("size", DataType::Float64, false),
+        Field::new("name", DataType::Utf8, true),
+    ]));
+    let batches = sample01()
+        .read_batches(BatchOptions::new().schema(Arc::clone(&schema)))
+        .collect::<std::io::Result<Vec<RecordBatch>>>()
+        .unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].schema(), schema);
+    assert_eq!(batches[0].num_rows(), 3);
+    let input = "{\"size\": 1}\n{\"size\": 2.5}\n{\"name\": \"x\"}\n";
+    let mut iter =
+        JsonLinesReader::new(input.as_bytes()).read_batches(BatchOptions::new().schema(schema));
+    let e = iter.next().unwrap().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.to_string().starts_with("lines 1-3: "), "{e}");
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_read_batches_infer_limit() {
+    let input = "{\"a\": 1}\n{\"a\": 2, \"b\": true}\n{\"a\": \"three\"}\n";
+    let mut iter =
+        JsonLinesReader::new(input.as_bytes()).read_batches(BatchOptions::new().infer_limit(1));
+    let e = iter.next().unwrap().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(iter.schema().unwrap().fields().len(), 1);
+    let batches = JsonLinesReader::new(input.as_bytes())
+        .read_batches(BatchOptions::new().infer_limit(2).batch_size(2))
+        .take(1)
+        .collect::<std::io::Result<Vec<RecordBatch>>>()
+        .unwrap();
+    assert_eq!(batches[0].num_columns(), 2);
+}
+
+#[test]
+fn test_read_batches_bad_lines() {
+    let input = "{\"a\": 1}\nnot json\n";
+    let mut iter = JsonLinesReader::new(input.as_bytes()).read_batches(BatchOptions::new());
+    let e = iter.next().unwrap().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+    assert!(iter.next().is_none());
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    for input in [
+        "{\"a\": 1}\n{\"a\": 2} {\"a\": 3}\n",
+        "{\"a\": 1}\n{\"a\":\n2}\n",
+    ] {
+        let mut iter = JsonLinesReader::new(input.as_bytes())
+            .read_batches(BatchOptions::new().schema(Arc::clone(&schema)));
+        let e = iter.next().unwrap().unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        assert_eq!(e.to_string(), "line 2: expected a single JSON object");
+    }
+}
+
+#[test]
+fn test_read_batches_zero_batch_size() {
+    let mut iter = sample01().read_batches(BatchOptions::new().batch_size(0));
+    let e = iter.next().unwrap().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_read_batches_zero_infer_limit() {
+    let mut iter = sample01().read_batches(BatchOptions::new().infer_limit(0));
+    let e = iter.next().unwrap().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    assert!(iter.next().is_none());
+    let schema = Arc::new(Schema::new(vec![Field::new("size", DataType::Int64, true)]));
+    let batches = sample01()
+        .read_batches(BatchOptions::new().infer_limit(0).schema(schema))
+        .collect::<std::io::Result<Vec<RecordBatch>>>()
+        .unwrap();
+    assert_eq!(batches[0].num_rows(), 3);
+}
+
+#[test]
+fn test_read_batches_empty() {
+    let mut iter = JsonLinesReader::new(&b""[..]).read_batches(BatchOptions::new());
+    assert!(iter.next().is_none());
+    assert_eq!(iter.schema().unwrap().fields().len(), 0);
+}
+
+#[test]
+fn test_write_batch() {
+    let batches = sample01()
+        .read_batches(BatchOptions::new().batch_size(2))
+        .collect::<std::io::Result<Vec<RecordBatch>>>()
+        .unwrap();
+    let mut writer = JsonLinesWriter::new(Vec::new()).line_terminator(LineTerminator::CrLf);
+    for batch in &batches {
+        writer.write_batch(batch).unwrap();
+    }
+    assert_eq!(
+        String::from_utf8(writer.into_inner()).unwrap(),
+        concat!(
+            "{\"name\":\"Foo Bar\",\"on\":true,\"size\":42}\r\n",
+            "{\"name\":\"Quux\",\"on\":false,\"size\":23}\r\n",
+            "{\"name\":\"Gnusto Cleesh\",\"on\":true,\"size\":17}\r\n",
+        )
+    );
+}
+
+#[test]
+fn test_parquet_round_trip() {
+    let parquet = NamedTempFile::new("test.parquet").unwrap();
+    let rows = json_lines_to_parquet(
+        Path::new(DATA_DIR).join("sample01.jsonl"),
+        parquet.path(),
+        BatchOptions::new().batch_size(2),
+    )
+    .unwrap();
+    assert_eq!(rows, 3);
+    let jsonl = NamedTempFile::new("test.jsonl").unwrap();
+    assert_eq!(
+        parquet_to_json_lines(parquet.path(), jsonl.path()).unwrap(),
+        3
+    );
+    assert_eq!(
+        json_lines(jsonl.path())
+            .unwrap()
+            .collect::<std::io::Result<Vec<Structure>>>()
+            .unwrap(),
+        [
+            Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            },
+            Structure {
+                name: "Quux".into(),
+                size: 23,
+                on: false,
+            },
+            Structure {
+                name: "Gnusto Cleesh".into(),
+                size: 17,
+                on: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_json_lines_to_parquet_bad_input() {
+    let src = NamedTempFile::new("test.jsonl").unwrap();
+    src.write_str("[1, 2, 3]\n").unwrap();
+    let dest = NamedTempFile::new("test.parquet").unwrap();
+    let e = json_lines_to_parquet(src.path(), dest.path(), BatchOptions::new()).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(!dest.path().exists());
+}
+
+#[test]
+fn test_json_lines_to_parquet_error_keeps_dest() {
+    let src = NamedTempFile::new("test.jsonl").unwrap();
+    src.write_str("{\"a\": 1}\n{\"a\": 2}\n{\"a\": \"three\"}\n")
+        .unwrap();
+    let dest = NamedTempFile::new("test.parquet").unwrap();
+    dest.write_str("old contents").unwrap();
+    let e = json_lines_to_parquet(
+        src.path(),
+        dest.path(),
+        BatchOptions::new().batch_size(1).infer_limit(1),
+    )
+    .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    dest.assert("old contents");
+    let parent = dest.path().parent().unwrap();
+    assert_eq!(std::fs::read_dir(parent).unwrap().count(), 1);
+}
+
+#[test]
+fn test_parquet_to_json_lines_not_parquet() {
+    let dest = NamedTempFile::new("test.jsonl").unwrap();
+    let e =
+        parquet_to_json_lines(Path::new(DATA_DIR).join("sample01.jsonl"), dest.path()).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_json_lines_to_parquet_empty() {
+    let src = NamedTempFile::new("test.jsonl").unwrap();
+    src.write_str("").unwrap();
+    let parquet = NamedTempFile::new("test.parquet").unwrap();
+    assert_eq!(
+        json_lines_to_parquet(src.path(), parquet.path(), BatchOptions::new()).unwrap(),
+        0
+    );
+    let jsonl = NamedTempFile::new("test.jsonl").unwrap();
+    assert_eq!(
+        parquet_to_json_lines(parquet.path(), jsonl.path()).unwrap(),
+        0
+    );
+    jsonl.assert("");
+}
@@ -0,0 +1,321 @@
+mod common;
+use crate::common::*;
+use assert_fs::TempDir;
+use assert_fs::fixture::{FileWriteStr, PathChild};
+use serde_jsonlines::{LineTerminator, RotatingJsonLinesWriter};
+use std::fs::{read_dir, read_to_string};
+use std::io::ErrorKind;
+use std::thread::sleep;
+use std::time::Duration;
+
+fn listdir(dir: &TempDir) -> Vec<String> {
+    let mut names = read_dir(dir)
+        .expect("reading directory should succeed")
+        .map(|e| {
+            e.expect("reading directory entry should succeed")
+                .file_name()
+                .into_string()
+                .expect("file name should be UTF-8")
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+fn points(n: i32) -> impl Iterator<Item = Point> {
+    (0..n).map(|x| Point { x, y: x * 10 })
+}
+
+#[test]
+fn test_rotate_by_records() {
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("part-%n.jsonl");
+    let mut writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .max_records(2)
+        .open()
+        .unwrap();
+    writer.write_all(points(5)).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(
+        listdir(&tmpdir),
+        ["part-0.jsonl", "part-1.jsonl", "part-2.jsonl"]
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-0.jsonl")).unwrap(),
+        "{\"x\":0,\"y\":0}\n{\"x\":1,\"y\":10}\n"
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-1.jsonl")).unwrap(),
+        "{\"x\":2,\"y\":20}\n{\"x\":3,\"y\":30}\n"
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-2.jsonl")).unwrap(),
+        "{\"x\":4,\"y\":40}\n"
+    );
+    assert_eq!(writer.current_path(), tmpdir.child("part-2.jsonl").path());
+    assert_eq!(
+        writer.rotated_paths().collect::<Vec<_>>(),
+        [
+            tmpdir.child("part-0.jsonl").path(),
+            tmpdir.child("part-1.jsonl").path()
+        ]
+    );
+}
+
+#[test]
+fn test_rotate_by_bytes() {
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("part-%n.jsonl");
+    // Each line of `points(4)` is 14 or 15 bytes long
+    let mut writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .max_bytes(30)
+        .open()
+        .unwrap();
+    writer.write_all(points(4)).unwrap();
+    writer
+        .write(&Structure {
+            name: "This line is longer than the limit".into(),
+            size: 42,
+            on: true,
+        })
+        .unwrap();
+    writer.write(&Point { x: 5, y: 5 }).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(
+        listdir(&tmpdir),
+        [
+            "part-0.jsonl",
+            "part-1.jsonl",
+            "part-2.jsonl",
+            "part-3.jsonl"
+        ]
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-0.jsonl")).unwrap(),
+        "{\"x\":0,\"y\":0}\n{\"x\":1,\"y\":10}\n"
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-1.jsonl")).unwrap(),
+        "{\"x\":2,\"y\":20}\n{\"x\":3,\"y\":30}\n"
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-2.jsonl")).unwrap(),
+        "{\"name\":\"This line is longer than the limit\",\"size\":42,\"on\":true}\n"
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-3.jsonl")).unwrap(),
+        "{\"x\":5,\"y\":5}\n"
+    );
+}
+
+#[test]
+fn test_rotate_by_age() {
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("part-%n.jsonl");
+    let mut writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .max_age(Duration::from_millis(50))
+        .open()
+        .unwrap();
+    writer.write_all(points(2)).unwrap();
+    sleep(Duration::from_millis(100));
+    writer.write(&Point { x: 2, y: 20 }).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(listdir(&tmpdir), ["part-0.jsonl", "part-1.jsonl"]);
+    assert_eq!(
+        read_to_string(tmpdir.child("part-1.jsonl")).unwrap(),
+        "{\"x\":2,\"y\":20}\n"
+    );
+}
+
+#[test]
+fn test_max_files() {
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("part-%n.jsonl");
+    let mut writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .max_records(1)
+        .max_files(2)
+        .open()
+        .unwrap();
+    writer.write_all(points(5)).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(
+        listdir(&tmpdir),
+        ["part-2.jsonl", "part-3.jsonl", "part-4.jsonl"]
+    );
+}
+
+#[test]
+fn test_skip_existing_files() {
+    let tmpdir = TempDir::new().unwrap();
+    tmpdir
+        .child("part-0.jsonl")
+        .write_str("Old data\n")
+        .unwrap();
+    tmpdir
+        .child("part-2.jsonl")
+        .write_str("Old data\n")
+        .unwrap();
+    let pattern = tmpdir.child("part-%n.jsonl");
+    let mut writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .max_records(1)
+        .open()
+        .unwrap();
+    writer.write_all(points(2)).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(
+        listdir(&tmpdir),
+        [
+            "part-0.jsonl",
+            "part-1.jsonl",
+            "part-2.jsonl",
+            "part-3.jsonl"
+        ]
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-0.jsonl")).unwrap(),
+        "Old data\n"
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-2.jsonl")).unwrap(),
+        "Old data\n"
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-3.jsonl")).unwrap(),
+        "{\"x\":1,\"y\":10}\n"
+    );
+}
+
+#[test]
+fn test_manual_rotate() {
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("part-%n.jsonl");
+    let mut writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .open()
+        .unwrap();
+    writer.write_all(points(3)).unwrap();
+    writer.rotate().unwrap();
+    writer.write_all(points(1)).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(listdir(&tmpdir), ["part-0.jsonl", "part-1.jsonl"]);
+    assert_eq!(
+        read_to_string(tmpdir.child("part-1.jsonl")).unwrap(),
+        "{\"x\":0,\"y\":0}\n"
+    );
+}
+
+#[test]
+fn test_date_pattern() {
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("events-%Y%m%d-%n.jsonl");
+    let writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .open()
+        .unwrap();
+    let name = writer.current_path().file_name().unwrap().to_str().unwrap();
+    assert_eq!(name.len(), "events-YYYYMMDD-0.jsonl".len());
+    assert!(name.starts_with("events-"));
+    assert!(
+        name["events-".len()..][..8]
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+    );
+    assert!(name.ends_with("-0.jsonl"));
+}
+
+#[test]
+fn test_pattern_without_seq() {
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("events-%Y%m%d.jsonl");
+    let e = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .open()
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    assert!(listdir(&tmpdir).is_empty());
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_compress() {
+    use flate2::read::GzDecoder;
+    use std::fs::File;
+    use std::io::Read;
+    use std::path::PathBuf;
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("part-%n.jsonl");
+    let mut writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .max_records(2)
+        .compress(true)
+        .open()
+        .unwrap();
+    writer.write_all(points(3)).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(listdir(&tmpdir), ["part-0.jsonl.gz", "part-1.jsonl"]);
+    assert_eq!(
+        writer.rotated_paths().collect::<Vec<_>>(),
+        [PathBuf::from(tmpdir.child("part-0.jsonl.gz").path())]
+    );
+    let mut s = String::new();
+    GzDecoder::new(File::open(tmpdir.child("part-0.jsonl.gz")).unwrap())
+        .read_to_string(&mut s)
+        .unwrap();
+    assert_eq!(s, "{\"x\":0,\"y\":0}\n{\"x\":1,\"y\":10}\n");
+}
+
+#[test]
+fn test_line_terminator() {
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("part-%n.jsonl");
+    let mut writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .max_bytes(31)
+        .line_terminator(LineTerminator::CrLf)
+        .open()
+        .unwrap();
+    writer.write_all(points(3)).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(listdir(&tmpdir), ["part-0.jsonl", "part-1.jsonl"]);
+    assert_eq!(
+        read_to_string(tmpdir.child("part-0.jsonl")).unwrap(),
+        "{\"x\":0,\"y\":0}\r\n{\"x\":1,\"y\":10}\r\n"
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-1.jsonl")).unwrap(),
+        "{\"x\":2,\"y\":20}\r\n"
+    );
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_compress_failure_keeps_tracking() {
+    use std::path::PathBuf;
+    let tmpdir = TempDir::new().unwrap();
+    let pattern = tmpdir.child("part-%n.jsonl");
+    let mut writer = RotatingJsonLinesWriter::builder(pattern.to_str().unwrap())
+        .compress(true)
+        .max_files(1)
+        .open()
+        .unwrap();
+    writer.write_all(points(1)).unwrap();
+    tmpdir
+        .child("part-0.jsonl.gz")
+        .write_str("In the way\n")
+        .unwrap();
+    let e = writer.rotate().unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::AlreadyExists);
+    assert_eq!(
+        writer.rotated_paths().collect::<Vec<_>>(),
+        [PathBuf::from(tmpdir.child("part-0.jsonl").path())]
+    );
+    writer.write_all(points(1)).unwrap();
+    writer.rotate().unwrap();
+    assert_eq!(
+        writer.rotated_paths().collect::<Vec<_>>(),
+        [PathBuf::from(tmpdir.child("part-1.jsonl.gz").path())]
+    );
+    assert_eq!(
+        listdir(&tmpdir),
+        ["part-0.jsonl.gz", "part-1.jsonl.gz", "part-2.jsonl"]
+    );
+    assert_eq!(
+        read_to_string(tmpdir.child("part-0.jsonl.gz")).unwrap(),
+        "In the way\n"
+    );
+}
@@ -0,0 +1,266 @@
+#![cfg(feature = "jsonschema")]
+mod common;
+use crate::common::*;
+use serde_json::{Value, json};
+use serde_jsonlines::{
+    JsonLinesReader, JsonLinesSchemaWriter, JsonLinesWriter, JsonSchema, LineTerminator,
+    SchemaError, SchemaLocation, SchemaMode, SchemaViolation,
+};
+use std::io::ErrorKind;
+
+fn schema() -> JsonSchema {
+    JsonSchema::compile(&json!({
+        "type": "object",
+        "properties": {
+            "name": {"type": "string", "minLength": 1},
+            "size": {"type": "integer", "minimum": 0, "maximum": 100},
+            "on": {"type": "boolean"},
+        },
+        "required": ["name", "size", "on"],
+    }))
+    .expect("schema should be valid")
+}
+
+const INPUT: &str = concat!(
+    "{\"name\": \"Foo Bar\", \"size\": 42, \"on\": true}\n",
+    "{\"name\": \"\", \"size\": 420, \"on\": false}\n",
+    "{\"name\": \"Quux\", \"size\": 23, \"on\": false}\n",
+    "{\"name\": \"Gnusto Cleesh\", \"size\": -1}\n",
+);
+
+fn schema_error(e: &std::io::Error) -> &SchemaError {
+    e.get_ref()
+        .and_then(|e| e.downcast_ref::<SchemaError>())
+        .expect("error payload should be a SchemaError")
+}
+
+#[test]
+fn test_invalid_schema() {
+    let e = JsonSchema::compile(&json!({"type": 42})).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    assert!(e.to_string().starts_with("invalid JSON Schema: "));
+}
+
+#[test]
+fn test_read_validated_fail_fast() {
+    let schema = schema();
+    let mut reader = JsonLinesReader::new(INPUT.as_bytes());
+    assert_eq!(
+        reader.read_validated::<Structure>(&schema).unwrap(),
+        Some(Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        })
+    );
+    let e = reader.read_validated::<Structure>(&schema).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    let err = schema_error(&e);
+    assert_eq!(err.location, SchemaLocation::Line(2));
+    assert_eq!(err.violations.len(), 1);
+    assert_eq!(
+        reader.read_validated::<Structure>(&schema).unwrap(),
+        Some(Structure {
+            name: "Quux".into(),
+            size: 23,
+            on: false,
+        })
+    );
+    let e = reader.read_validated::<Structure>(&schema).unwrap_err();
+    assert_eq!(schema_error(&e).location, SchemaLocation::Line(4));
+    assert_eq!(reader.read_validated::<Structure>(&schema).unwrap(), None);
+}
+
+#[test]
+fn test_read_validated_invalid_json() {
+    let input = "{\"name\": \"Foo Bar\", \"size\": 42, \"on\": true}\nnot json\n";
+    let schema = schema();
+    let mut reader = JsonLinesReader::new(input.as_bytes());
+    assert!(
+        reader
+            .read_validated::<Structure>(&schema)
+            .unwrap()
+            .is_some()
+    );
+    let e = reader.read_validated::<Structure>(&schema).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+}
+
+#[test]
+fn test_read_all_validated_collect_all() {
+    let mut iter = JsonLinesReader::new(INPUT.as_bytes())
+        .read_all_validated::<Structure>(schema().mode(SchemaMode::CollectAll));
+    let items = iter.by_ref().collect::<std::io::Result<Vec<_>>>().unwrap();
+    assert_eq!(
+        items,
+        [
+            Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            },
+            Structure {
+                name: "Quux".into(),
+                size: 23,
+                on: false,
+            },
+        ]
+    );
+    let violations = iter.violations();
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0].location, SchemaLocation::Line(2));
+    assert_eq!(
+        violations[0]
+            .violations
+            .iter()
+            .map(|v| (v.instance_path.as_str(), v.schema_path.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            ("/name", "/properties/name/minLength"),
+            ("/size", "/properties/size/maximum"),
+        ]
+    );
+    assert_eq!(violations[1].location, SchemaLocation::Line(4));
+    assert_eq!(
+        violations[1]
+            .violations
+            .iter()
+            .map(|v| (v.instance_path.as_str(), v.schema_path.as_str()))
+            .collect::<Vec<_>>(),
+        [("", "/required"), ("/size", "/properties/size/minimum")]
+    );
+}
+
+#[test]
+fn test_read_all_validated_fail_fast() {
+    let mut iter = JsonLinesReader::new(INPUT.as_bytes()).read_all_validated::<Structure>(schema());
+    assert!(iter.next().unwrap().is_ok());
+    let e = iter.next().unwrap().unwrap_err();
+    assert_eq!(schema_error(&e).location, SchemaLocation::Line(2));
+    assert!(iter.next().unwrap().is_ok());
+    let e = iter.next().unwrap().unwrap_err();
+    assert_eq!(schema_error(&e).location, SchemaLocation::Line(4));
+    assert!(iter.next().is_none());
+    assert!(iter.violations().is_empty());
+}
+
+#[test]
+fn test_schema_error_display() {
+    let e = SchemaError {
+        location: SchemaLocation::Line(3),
+        violations: vec![
+            SchemaViolation {
+                instance_path: "/size".into(),
+                schema_path: "/properties/size/maximum".into(),
+                message: "420 is greater than the maximum of 100".into(),
+            },
+            SchemaViolation {
+                instance_path: String::new(),
+                schema_path: "/required".into(),
+                message: "\"on\" is a required property".into(),
+            },
+        ],
+    };
+    assert_eq!(
+        e.to_string(),
+        concat!(
+            "line 3: 420 is greater than the maximum of 100 (at \"/size\"); ",
+            "\"on\" is a required property (at \"\")"
+        )
+    );
+}
+
+#[test]
+fn test_schema_error_display_item() {
+    let e = SchemaError {
+        location: SchemaLocation::Item(7),
+        violations: vec![SchemaViolation {
+            instance_path: "/on".into(),
+            schema_path: "/properties/on/type".into(),
+            message: "1 is not of type \"boolean\"".into(),
+        }],
+    };
+    assert_eq!(
+        e.to_string(),
+        "item 7: 1 is not of type \"boolean\" (at \"/on\")"
+    );
+}
+
+#[test]
+fn test_schema_writer_fail_fast() {
+    let mut writer = JsonLinesSchemaWriter::new(Vec::new(), schema());
+    assert!(
+        writer
+            .write(&Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            })
+            .unwrap()
+    );
+    let e = writer
+        .write(&Structure {
+            name: "Quux".into(),
+            size: 230,
+            on: false,
+        })
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    let err = schema_error(&e);
+    assert_eq!(err.location, SchemaLocation::Item(2));
+    assert_eq!(err.violations[0].instance_path, "/size");
+    assert_eq!(
+        writer.into_inner(),
+        b"{\"name\":\"Foo Bar\",\"on\":true,\"size\":42}\n"
+    );
+}
+
+#[test]
+fn test_schema_writer_collect_all() {
+    let mut writer = JsonLinesSchemaWriter::new(Vec::new(), schema().mode(SchemaMode::CollectAll));
+    let values: Vec<Value> = vec![
+        json!({"name": "Foo Bar", "size": 42, "on": true}),
+        json!({"name": "Quux", "size": "big", "on": false}),
+        json!({"name": "Gnusto Cleesh", "size": 17, "on": true}),
+        json!([1, 2, 3]),
+    ];
+    assert_eq!(writer.write_all(&values).unwrap(), 2);
+    let violations = writer.violations();
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0].location, SchemaLocation::Item(2));
+    assert_eq!(violations[0].violations[0].instance_path, "/size");
+    assert_eq!(violations[1].location, SchemaLocation::Item(4));
+    assert_eq!(violations[1].violations[0].instance_path, "");
+    assert_eq!(
+        writer.into_inner(),
+        concat!(
+            "{\"name\":\"Foo Bar\",\"on\":true,\"size\":42}\n",
+            "{\"name\":\"Gnusto Cleesh\",\"on\":true,\"size\":17}\n",
+        )
+        .as_bytes()
+    );
+}
+
+#[test]
+fn test_is_valid() {
+    let schema = schema();
+    assert!(schema.is_valid(&json!({"name": "x", "size": 0, "on": true})));
+    assert!(!schema.is_valid(&json!({"name": "x", "size": 0})));
+}
+
+#[test]
+fn test_with_schema_keeps_writer_settings() {
+    let mut writer = JsonLinesWriter::new(Vec::new())
+        .line_terminator(LineTerminator::CrLf)
+        .with_schema(schema());
+    assert!(
+        writer
+            .write(&json!({"name": "Foo Bar", "size": 42, "on": true}))
+            .unwrap()
+    );
+    assert_eq!(
+        writer.into_inner(),
+        b"{\"name\":\"Foo Bar\",\"on\":true,\"size\":42}\r\n"
+    );
+}
@@ -0,0 +1,181 @@
+mod common;
+use crate::common::*;
+use serde::{Deserialize, Serialize};
+use serde_jsonlines::{
+    Deserializer, Serializer, from_reader, from_slice, from_str, to_string, to_vec, to_writer,
+};
+use std::collections::{BTreeSet, VecDeque};
+use std::io::ErrorKind;
+use std::path::Path;
+
+#[test]
+fn test_from_str_vec() {
+    let items = from_str::<Vec<Structure>>(concat!(
+        "{\"name\": \"Foo Bar\", \"on\": true, \"size\": 42}\n",
+        "{\"name\": \"Quux\", \"on\": false, \"size\": 23}\n",
+    ))
+    .unwrap();
+    assert_eq!(
+        items,
+        [
+            Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            },
+            Structure {
+                name: "Quux".into(),
+                size: 23,
+                on: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_from_str_no_final_newline() {
+    let items = from_str::<VecDeque<Point>>("{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}").unwrap();
+    assert_eq!(items, [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+}
+
+#[test]
+fn test_from_str_empty() {
+    let items = from_str::<Vec<Point>>("").unwrap();
+    assert!(items.is_empty());
+}
+
+#[test]
+fn test_from_slice_set() {
+    let items = from_slice::<BTreeSet<i32>>(b"3\n1\n2\n1\n").unwrap();
+    assert_eq!(items, BTreeSet::from([1, 2, 3]));
+}
+
+#[test]
+fn test_from_str_tuple() {
+    let (a, b, c) = from_str::<(i32, String, Point)>("42\n\"foo\"\n{\"x\":1,\"y\":2}\n").unwrap();
+    assert_eq!(a, 42);
+    assert_eq!(b, "foo");
+    assert_eq!(c, Point { x: 1, y: 2 });
+}
+
+#[test]
+fn test_from_str_tuple_trailing_lines() {
+    let e = from_str::<(i32, i32)>("1\n2\n3\n").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    let inner = e.get_ref().unwrap();
+    assert!(inner.is::<serde_json::Error>());
+    assert!(inner.to_string().contains("trailing lines"));
+}
+
+#[test]
+fn test_from_str_tuple_too_few_lines() {
+    let e = from_str::<(i32, i32, i32)>("1\n2\n").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+}
+
+#[test]
+fn test_from_str_invalid_line() {
+    let e = from_str::<Vec<Point>>("{\"x\":1,\"y\":2}\n{\"x\":3,\n").unwrap_err();
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+}
+
+#[test]
+fn test_from_str_two_values_on_one_line() {
+    let e = from_str::<Vec<i32>>("1 2\n").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_from_str_not_a_sequence() {
+    let e = from_str::<Point>("{\"x\":1,\"y\":2}\n").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_from_reader_file() {
+    let path = Path::new(DATA_DIR).join("sample01.jsonl");
+    let fp = std::fs::File::open(path).unwrap();
+    let items = from_reader::<_, Vec<Structure>>(fp).unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].name, "Foo Bar");
+}
+
+#[test]
+fn test_deserializer_newtype() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Points(Vec<Point>);
+
+    let mut de = Deserializer::from_str("{\"x\":1,\"y\":2}\n");
+    let points = Points::deserialize(&mut de).unwrap();
+    assert_eq!(points, Points(vec![Point { x: 1, y: 2 }]));
+}
+
+#[test]
+fn test_to_string_vec() {
+    let s = to_string(&vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]).unwrap();
+    assert_eq!(s, "{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n");
+}
+
+#[test]
+fn test_to_string_empty() {
+    let s = to_string::<[Point]>(&[]).unwrap();
+    assert_eq!(s, "");
+}
+
+#[test]
+fn test_to_vec_tuple() {
+    let v = to_vec(&(42, "foo", Point { x: 1, y: 2 })).unwrap();
+    assert_eq!(v, b"42\n\"foo\"\n{\"x\":1,\"y\":2}\n");
+}
+
+#[test]
+fn test_to_writer_tuple_struct() {
+    #[derive(Serialize)]
+    struct Pair(i32, Point);
+
+    let mut buf = Vec::new();
+    to_writer(&mut buf, &Pair(1, Point { x: 2, y: 3 })).unwrap();
+    assert_eq!(buf, b"1\n{\"x\":2,\"y\":3}\n");
+}
+
+#[test]
+fn test_to_string_not_a_sequence() {
+    let e = to_string(&Point { x: 1, y: 2 }).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert!(e.get_ref().unwrap().is::<serde_json::Error>());
+    let e = to_string(&42).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_serializer_newtype() {
+    #[derive(Serialize)]
+    struct Points(Vec<Point>);
+
+    let mut ser = Serializer::new(Vec::new());
+    Points(vec![Point { x: 1, y: 2 }])
+        .serialize(&mut ser)
+        .unwrap();
+    assert_eq!(ser.into_inner(), b"{\"x\":1,\"y\":2}\n");
+}
+
+#[test]
+fn test_round_trip() {
+    let items = vec![
+        Structure {
+            name: "Foo Bar".into(),
+            size: 42,
+            on: true,
+        },
+        Structure {
+            name: "Quux\nwith newline".into(),
+            size: -1,
+            on: false,
+        },
+    ];
+    let s = to_string(&items).unwrap();
+    assert_eq!(s.lines().count(), 2);
+    let back = from_str::<Vec<Structure>>(&s).unwrap();
+    assert_eq!(back, items);
+}
@@ -0,0 +1,173 @@
+mod common;
+use crate::common::*;
+use assert_fs::NamedTempFile;
+use assert_fs::fixture::FileWriteStr;
+use serde_jsonlines::{JsonLinesReader, split_file, split_reader};
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, ErrorKind, Result};
+use std::path::Path;
+
+fn points_text(n: i32) -> String {
+    let mut s = String::new();
+    for i in 0..n {
+        writeln!(s, "{{\"x\":{i},\"y\":{}}}", i * i * i)
+            .expect("writing to a String should not fail");
+    }
+    s
+}
+
+fn read_range(text: &str, start: u64, end: u64) -> Vec<Point> {
+    JsonLinesReader::with_range(Cursor::new(text), start..end)
+        .expect("creating reader should succeed")
+        .read_all::<Point>()
+        .collect::<Result<Vec<_>>>()
+        .expect("reading should succeed")
+}
+
+#[test]
+fn test_split_file_boundaries_at_line_starts() {
+    let text = points_text(100);
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    tmpfile.write_str(&text).unwrap();
+    for n in 1..=12 {
+        let ranges = split_file(&tmpfile, n).unwrap();
+        assert_eq!(ranges.len(), n);
+        assert_eq!(ranges[0].start, 0);
+        assert_eq!(ranges[n - 1].end, u64::try_from(text.len()).unwrap());
+        for (a, b) in ranges.iter().zip(ranges.iter().skip(1)) {
+            assert_eq!(a.end, b.start);
+            let b = usize::try_from(a.end).unwrap();
+            assert_eq!(text.as_bytes()[b - 1], b'\n');
+        }
+    }
+}
+
+#[test]
+fn test_split_file_all_records_once() {
+    let text = points_text(100);
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    tmpfile.write_str(&text).unwrap();
+    for n in 1..=12 {
+        let mut points = Vec::new();
+        for range in split_file(&tmpfile, n).unwrap() {
+            let fp = BufReader::new(File::open(&tmpfile).unwrap());
+            let reader = JsonLinesReader::with_range(fp, range).unwrap();
+            for r in reader.read_all::<Point>() {
+                points.push(r.unwrap());
+            }
+        }
+        assert_eq!(
+            points,
+            (0..100)
+                .map(|x| Point { x, y: x * x * x })
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn test_more_splits_than_lines() {
+    let text = "{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n";
+    let ranges = split_reader(&mut Cursor::new(text), 5).unwrap();
+    assert_eq!(ranges.len(), 5);
+    assert_eq!(ranges.iter().filter(|r| !r.is_empty()).count(), 2);
+    let points = ranges
+        .into_iter()
+        .flat_map(|r| read_range(text, r.start, r.end))
+        .collect::<Vec<_>>();
+    assert_eq!(points, [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+}
+
+#[test]
+fn test_split_empty() {
+    let ranges = split_reader(&mut Cursor::new(""), 3).unwrap();
+    assert_eq!(ranges, [0..0, 0..0, 0..0]);
+    assert_eq!(read_range("", 0, 0), []);
+}
+
+#[test]
+fn test_zero_splits() {
+    let e = split_reader(&mut Cursor::new("{}\n"), 0).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    let e = split_file(Path::new(DATA_DIR).join("nonexistent.jsonl"), 0).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_with_range_straddling_record() {
+    // Line starts: 0, 14, 28
+    let text = "{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n{\"x\":5,\"y\":6}\n";
+    // The second line starts inside 0..20 and so is read in full
+    assert_eq!(
+        read_range(text, 0, 20),
+        [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]
+    );
+    // The partial second line at the start of 20..42 is skipped
+    assert_eq!(read_range(text, 20, 42), [Point { x: 5, y: 6 }]);
+}
+
+#[test]
+fn test_with_range_exact_boundaries() {
+    let text = "{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n{\"x\":5,\"y\":6}\n";
+    assert_eq!(read_range(text, 0, 14), [Point { x: 1, y: 2 }]);
+    assert_eq!(read_range(text, 14, 28), [Point { x: 3, y: 4 }]);
+    assert_eq!(read_range(text, 28, 42), [Point { x: 5, y: 6 }]);
+    assert_eq!(read_range(text, 14, 15), [Point { x: 3, y: 4 }]);
+    assert_eq!(read_range(text, 15, 28), []);
+}
+
+#[test]
+fn test_with_range_no_final_newline() {
+    let text = "{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}";
+    assert_eq!(read_range(text, 10, 100), [Point { x: 3, y: 4 }]);
+    assert_eq!(read_range(text, 20, 100), []);
+}
+
+#[test]
+fn test_with_range_position() {
+    let text = "{\"x\":1,\"y\":2}\n{\"x\":3,\"y\":4}\n";
+    let mut reader = JsonLinesReader::with_range(Cursor::new(text), 5..20).unwrap();
+    assert_eq!(reader.get_ref().position(), 14);
+    assert_eq!(reader.read::<Point>().unwrap(), Some(Point { x: 3, y: 4 }));
+    assert_eq!(reader.get_ref().position(), 28);
+    assert_eq!(reader.read::<Point>().unwrap(), None);
+    assert!(reader.get_mut().fill_buf().unwrap().is_empty());
+}
+
+#[test]
+fn test_split_sample01() {
+    let path = Path::new(DATA_DIR).join("sample01.jsonl");
+    let ranges = split_file(&path, 2).unwrap();
+    let mut items = Vec::new();
+    for range in ranges {
+        let fp = BufReader::new(File::open(&path).unwrap());
+        let reader = JsonLinesReader::with_range(fp, range).unwrap();
+        let part = reader
+            .read_all::<Structure>()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(!part.is_empty());
+        items.extend(part);
+    }
+    assert_eq!(
+        items,
+        [
+            Structure {
+                name: "Foo Bar".into(),
+                size: 42,
+                on: true,
+            },
+            Structure {
+                name: "Quux".into(),
+                size: 23,
+                on: false,
+            },
+            Structure {
+                name: "Gnusto Cleesh".into(),
+                size: 17,
+                on: true,
+            },
+        ]
+    );
+}
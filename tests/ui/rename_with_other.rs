@@ -0,0 +1,16 @@
+use serde::Deserialize;
+use serde_jsonlines::JsonLinesRecord;
+
+#[derive(Deserialize)]
+struct Login {
+    user: String,
+}
+
+#[derive(JsonLinesRecord)]
+enum Event {
+    Login(Login),
+    #[jsonlines(other, rename = "unknown")]
+    Unknown,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use serde_jsonlines::JsonLinesRecord;
+
+#[derive(Deserialize, JsonLinesRecord)]
+#[jsonlines(tag = "kind")]
+struct Event {
+    user: String,
+}
+
+fn main() {}
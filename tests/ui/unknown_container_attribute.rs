@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use serde_jsonlines::JsonLinesRecord;
+
+#[derive(Deserialize, JsonLinesRecord)]
+#[jsonlines(version = 2, flavor = "strict")]
+struct Event {
+    user: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use serde::Deserialize;
+use serde_jsonlines::JsonLinesRecord;
+
+#[derive(Deserialize)]
+struct Login {
+    user: String,
+}
+
+#[derive(JsonLinesRecord)]
+enum Event {
+    #[jsonlines(alias = "signin")]
+    Login(Login),
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use serde::Deserialize;
+use serde_jsonlines::JsonLinesRecord;
+
+#[derive(Deserialize)]
+struct EventV1 {
+    user: String,
+}
+
+#[derive(Deserialize, JsonLinesRecord)]
+#[jsonlines(upgrade(1 = EventV1))]
+struct Event {
+    user: String,
+}
+
+impl From<EventV1> for Event {
+    fn from(v1: EventV1) -> Event {
+        Event { user: v1.user }
+    }
+}
+
+fn main() {}
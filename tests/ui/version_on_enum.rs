@@ -0,0 +1,15 @@
+use serde::Deserialize;
+use serde_jsonlines::JsonLinesRecord;
+
+#[derive(Deserialize)]
+struct Login {
+    user: String,
+}
+
+#[derive(JsonLinesRecord)]
+#[jsonlines(version = 2)]
+enum Event {
+    Login(Login),
+}
+
+fn main() {}
@@ -0,0 +1,145 @@
+use assert_fs::NamedTempFile;
+use assert_fs::fixture::FileWriteStr;
+use serde_jsonlines::{ValidationReport, Violation, ViolationKind, validate, validate_file};
+use std::path::Path;
+
+fn violations(report: &ValidationReport) -> Vec<(u64, usize, ViolationKind)> {
+    report
+        .violations
+        .iter()
+        .map(|v| (v.line, v.column, v.kind))
+        .collect()
+}
+
+#[test]
+fn test_validate_valid() {
+    let report = validate(&b"{\"x\": 1}\n[1, 2]\n\"foo\"\n"[..]).unwrap();
+    assert_eq!(
+        report,
+        ValidationReport {
+            lines: 3,
+            values: 3,
+            final_newline: true,
+            violations: Vec::new(),
+        }
+    );
+    assert!(report.is_valid());
+}
+
+#[test]
+fn test_validate_empty() {
+    let report = validate(&b""[..]).unwrap();
+    assert_eq!(report, ValidationReport::default());
+    assert!(report.is_valid());
+}
+
+#[test]
+fn test_validate_no_final_newline() {
+    let report = validate(&b"1\n2"[..]).unwrap();
+    assert_eq!(report.lines, 2);
+    assert_eq!(report.values, 2);
+    assert!(!report.final_newline);
+    assert!(report.is_valid());
+}
+
+#[test]
+fn test_validate_crlf_tolerated() {
+    let report = validate(&b"{\"x\": 1}\r\n{\"x\": 2}\r\n3"[..]).unwrap();
+    assert_eq!(report.values, 3);
+    assert_eq!(
+        violations(&report),
+        [(1, 9, ViolationKind::CrLf), (2, 9, ViolationKind::CrLf)]
+    );
+    assert_eq!(report.count(ViolationKind::CrLf), 2);
+    assert!(report.is_valid());
+}
+
+#[test]
+fn test_validate_trailing_lone_cr() {
+    let report = validate(&b"{\"x\": 1}\n{\"x\": 2}\r"[..]).unwrap();
+    assert_eq!(report.values, 2);
+    assert!(!report.final_newline);
+    assert_eq!(violations(&report), [(2, 9, ViolationKind::LoneCr)]);
+    assert_eq!(report.violations[0].message, "line ends in a bare CR");
+    assert!(report.is_valid());
+}
+
+#[test]
+fn test_validate_bom() {
+    let report = validate(&b"\xEF\xBB\xBF{\"x\": 1}\n[1\n"[..]).unwrap();
+    assert_eq!(report.values, 1);
+    assert_eq!(
+        violations(&report),
+        [
+            (1, 1, ViolationKind::ByteOrderMark),
+            (2, 2, ViolationKind::InvalidJson),
+        ]
+    );
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn test_validate_invalid_utf8() {
+    let report = validate(&b"\"ab\xFFcd\"\n1\n"[..]).unwrap();
+    assert_eq!(report.lines, 2);
+    assert_eq!(report.values, 1);
+    assert_eq!(violations(&report), [(1, 4, ViolationKind::InvalidUtf8)]);
+}
+
+#[test]
+fn test_validate_empty_lines() {
+    let report = validate(&b"1\n\n  \n2\n"[..]).unwrap();
+    assert_eq!(report.lines, 4);
+    assert_eq!(report.values, 2);
+    assert_eq!(
+        violations(&report),
+        [
+            (2, 1, ViolationKind::EmptyLine),
+            (3, 1, ViolationKind::EmptyLine)
+        ]
+    );
+    assert_eq!(report.count(ViolationKind::EmptyLine), 2);
+}
+
+#[test]
+fn test_validate_trailing_and_invalid() {
+    let report = validate(&b"{\"x\": 1} {\"x\": 2}\n{\"x\": }\n"[..]).unwrap();
+    assert_eq!(report.values, 0);
+    assert_eq!(
+        report.violations,
+        [
+            Violation {
+                line: 1,
+                column: 10,
+                kind: ViolationKind::TrailingCharacters,
+                message: "trailing characters".into(),
+            },
+            Violation {
+                line: 2,
+                column: 7,
+                kind: ViolationKind::InvalidJson,
+                message: "expected value".into(),
+            },
+        ]
+    );
+    assert_eq!(
+        report.violations[0].to_string(),
+        "line 1, column 10: trailing characters"
+    );
+}
+
+#[test]
+fn test_validate_file() {
+    let tmpfile = NamedTempFile::new("test.jsonl").unwrap();
+    tmpfile.write_str("{\"x\": 1}\r\nnull\n").unwrap();
+    let report = validate_file(&tmpfile).unwrap();
+    assert_eq!(report.lines, 2);
+    assert_eq!(report.values, 2);
+    assert_eq!(violations(&report), [(1, 9, ViolationKind::CrLf)]);
+}
+
+#[test]
+fn test_validate_file_nonexistent() {
+    let r = validate_file(Path::new("does-not-exist.jsonl"));
+    assert_eq!(r.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+}